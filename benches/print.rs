@@ -0,0 +1,68 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use hline::print::{BufferedPrinter, FlushPolicy, PlainPrinter, Printer, WritePrinter};
+use std::io;
+
+/// The number of lines in each generated input.
+const NUM_LINES: usize = 100_000;
+
+/// The pattern that each benchmark scans for.
+const PATTERN: &str = "ERROR";
+
+/// Make an input of log lines, where every `match_every`th line matches [`PATTERN`]. A `match_every` of zero makes an
+/// input with no matches at all.
+fn make_input(match_every: usize) -> Vec<u8> {
+    let mut input = Vec::new();
+    for i in 0..NUM_LINES {
+        let level = if match_every != 0 && i % match_every == 0 {
+            "ERROR"
+        } else {
+            "INFO"
+        };
+
+        input.extend_from_slice(
+            format!("2021-11-07 12:00:00 {level} worker-{i} handled request in 42ms\n").as_bytes(),
+        );
+    }
+
+    input
+}
+
+fn scan_to<P: Printer>(input: &[u8], printer: P) {
+    hline::scan_pattern_to_printer(input, PATTERN, printer).expect("scan failed");
+}
+
+fn bench_printers(c: &mut Criterion) {
+    let inputs = [
+        ("passthrough", make_input(0)),
+        ("sparse", make_input(1000)),
+        ("dense", make_input(1)),
+    ];
+
+    for (name, input) in &inputs {
+        let mut group = c.benchmark_group(*name);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_function("unbuffered", |b| {
+            b.iter(|| scan_to(input, WritePrinter::new(io::sink())));
+        });
+        group.bench_function("buffered", |b| {
+            b.iter(|| {
+                scan_to(
+                    input,
+                    BufferedPrinter::new(io::sink(), FlushPolicy::WhenFull),
+                )
+            });
+        });
+        group.bench_function("buffered-plain", |b| {
+            b.iter(|| {
+                let printer = BufferedPrinter::new(io::sink(), FlushPolicy::WhenFull);
+                scan_to(input, PlainPrinter::new(printer));
+            });
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_printers);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+//! `ansi` provides utilities for handling ANSI escape sequences that are already present in the scanned text, such as
+//! those produced by `git diff --color=always`.
+use std::borrow::Cow;
+
+const ESCAPE: u8 = b'\x1b';
+
+/// `strip_sgr_sequences` removes all SGR ("Select Graphic Rendition") escape sequences (i.e. those that set colors and
+/// text attributes) from the given bytes. If there are none, the original bytes are returned without copying.
+///
+/// This is useful when applying our own highlighting to text that is already colored; if these sequences were left in
+/// place, any reset in the original text would cancel our highlight part way through the line.
+pub(crate) fn strip_sgr_sequences(s: &[u8]) -> Cow<'_, [u8]> {
+    if !s.contains(&ESCAPE) {
+        return Cow::Borrowed(s);
+    }
+
+    let mut stripped = Vec::with_capacity(s.len());
+    let mut remaining = s;
+    while let Some(escape_idx) = remaining.iter().position(|&b| b == ESCAPE) {
+        stripped.extend_from_slice(&remaining[..escape_idx]);
+        let sequence_len = sgr_sequence_len(&remaining[escape_idx..]);
+        if sequence_len == 0 {
+            stripped.push(ESCAPE);
+            remaining = &remaining[escape_idx + 1..];
+        } else {
+            remaining = &remaining[escape_idx + sequence_len..];
+        }
+    }
+
+    stripped.extend_from_slice(remaining);
+
+    Cow::Owned(stripped)
+}
+
+/// `sgr_sequence_len` gets the length of the SGR sequence at the start of the given string. If the string does not
+/// start with an SGR sequence, zero is returned.
+pub(crate) fn sgr_sequence_len(s: &[u8]) -> usize {
+    let Some(parameters) = s.strip_prefix(b"\x1b[") else {
+        return 0;
+    };
+
+    let num_parameter_bytes = parameters
+        .iter()
+        .take_while(|&&b| b.is_ascii_digit() || b == b';' || b == b':')
+        .count();
+
+    if parameters.get(num_parameter_bytes) == Some(&b'm') {
+        // The +3 accounts for the leading "\x1b[" and the trailing "m"
+        num_parameter_bytes + 3
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("hello world", "hello world"; "no escapes")]
+    #[test_case("\x1b[32m+hello world\x1b[m", "+hello world"; "git diff line")]
+    #[test_case("\x1b[1;31mhello\x1b[0m world", "hello world"; "sequences with parameters")]
+    #[test_case("\x1b[38;5;208mhello\x1b[39m", "hello"; "extended colors")]
+    #[test_case("\x1b[2Jhello", "\x1b[2Jhello"; "non-sgr sequences are left alone")]
+    #[test_case("hello\x1b", "hello\x1b"; "trailing escape is left alone")]
+    fn test_strips_sgr_sequences(s: &str, expected: &str) {
+        assert_eq!(expected.as_bytes(), &*strip_sgr_sequences(s.as_bytes()));
+    }
+}
@@ -0,0 +1,8 @@
+//! `cli` provides everything the `hline` binary needs beyond the library: the arguments it takes, checking and parsing
+//! them into [`Args`](args::Args), and scanning each file to stdout in the output format they ask for.
+pub mod app;
+pub mod args;
+pub mod notify;
+pub mod output;
+pub mod run_stats;
+mod validate;
@@ -0,0 +1,936 @@
+use clap::{crate_name, crate_version, App, AppSettings, Arg};
+use hline::style::ATTRIBUTE_NAMES;
+use hline::theme::THEME_NAMES;
+
+pub const FILENAME_ARG_NAME: &str = "filename";
+pub const PATTERN_ARG_NAME: &str = "pattern";
+pub const CASE_INSENSITIVE_ARG_NAME: &str = "case-insensitive";
+pub const FIXED_STRINGS_ARG_NAME: &str = "fixed-strings";
+pub const OK_IF_BINARY_ARG_NAME: &str = "ok-if-binary";
+pub const STRINGS_ARG_NAME: &str = "strings";
+pub const SHOW_CONTROL_CHARS_ARG_NAME: &str = "show-control-chars";
+pub const SQUEEZE_BLANK_ARG_NAME: &str = "squeeze-blank";
+pub const LESSOPEN_ARG_NAME: &str = "lessopen";
+pub const NOTIFY_URL_ARG_NAME: &str = "notify-url";
+pub const SIGNAL_STATS_ARG_NAME: &str = "signal-stats";
+pub const INTERACTIVE_ARG_NAME: &str = "interactive";
+pub const TAIL_HEADERS_ARG_NAME: &str = "tail-headers";
+pub const TAIL_PREFIX_ARG_NAME: &str = "tail-prefix";
+pub const PREFIXED_ARG_NAME: &str = "prefixed";
+pub const MATCHES_TO_ARG_NAME: &str = "matches-to";
+pub const FORMAT_ARG_NAME: &str = "format";
+pub const JSON_ARG_NAME: &str = "json";
+pub const VIMGREP_ARG_NAME: &str = "vimgrep";
+pub const COUNT_ARG_NAME: &str = "count";
+pub const COUNT_ONLY_ARG_NAME: &str = "count-only";
+pub const STATS_ARG_NAME: &str = "stats";
+pub const MAX_COUNT_ARG_NAME: &str = "max-count";
+pub const STOP_AFTER_ARG_NAME: &str = "stop-after";
+pub const MAX_COLUMNS_ARG_NAME: &str = "max-columns";
+pub const REPLACE_ARG_NAME: &str = "replace";
+pub const NULL_DATA_ARG_NAME: &str = "null-data";
+pub const LINE_TERMINATOR_ARG_NAME: &str = "line-terminator";
+pub const CRLF_ARG_NAME: &str = "crlf";
+pub const DECORATIONS_ARG_NAME: &str = "decorations";
+pub const GUTTER_COLOR_ARG_NAME: &str = "gutter-color";
+pub const GUTTER_SEPARATOR_ARG_NAME: &str = "gutter-separator";
+pub const GUTTER_PADDING_ARG_NAME: &str = "gutter-padding";
+pub const WRAP_ARG_NAME: &str = "wrap";
+pub const RULES_ARG_NAME: &str = "rules";
+pub const PLUGIN_ARG_NAME: &str = "plugin";
+pub const REGEXP_ARG_NAME: &str = "regexp";
+pub const INVERT_ARG_NAME: &str = "invert";
+pub const NO_PASSTHRU_ARG_NAME: &str = "no-passthru";
+pub const AFTER_CONTEXT_ARG_NAME: &str = "after-context";
+pub const BEFORE_CONTEXT_ARG_NAME: &str = "before-context";
+pub const CONTEXT_ARG_NAME: &str = "context";
+pub const RECURSIVE_ARG_NAME: &str = "recursive";
+pub const GLOB_ARG_NAME: &str = "glob";
+pub const EXCLUDE_ARG_NAME: &str = "exclude";
+pub const NO_IGNORE_ARG_NAME: &str = "no-ignore";
+pub const HIDDEN_ARG_NAME: &str = "hidden";
+pub const TYPE_ARG_NAME: &str = "type";
+pub const TYPE_ADD_ARG_NAME: &str = "type-add";
+pub const MAX_FILESIZE_ARG_NAME: &str = "max-filesize";
+pub const COLOR_MATCH_ARG_NAME: &str = "color-match";
+pub const ALTERNATE_COLOR_ARG_NAME: &str = "alternate-color";
+pub const BG_ARG_NAME: &str = "bg";
+pub const STYLE_ARG_NAME: &str = "style";
+pub const COLOR_GROUPS_ARG_NAME: &str = "color-groups";
+pub const DIM_ARG_NAME: &str = "dim";
+pub const FOCUS_ARG_NAME: &str = "focus";
+pub const LINE_NUMBER_ARG_NAME: &str = "line-number";
+pub const BYTE_OFFSET_ARG_NAME: &str = "byte-offset";
+pub const MULTILINE_ARG_NAME: &str = "multiline";
+pub const MARKER_ARG_NAME: &str = "marker";
+pub const THEME_ARG_NAME: &str = "theme";
+pub const FOLLOW_ARG_NAME: &str = "follow";
+pub const WATCH_ARG_NAME: &str = "watch";
+pub const RESET_LINE_NUMBERS_ARG_NAME: &str = "reset-line-numbers";
+pub const WITH_FILENAME_ARG_NAME: &str = "with-filename";
+pub const NO_FILENAME_ARG_NAME: &str = "no-filename";
+pub const NULL_ARG_NAME: &str = "null";
+pub const STDIN_TTY_ARG_NAME: &str = "stdin-tty";
+pub const DECOMPRESS_ARG_NAME: &str = "decompress";
+pub const ENCODING_ARG_NAME: &str = "encoding";
+pub const NO_MMAP_ARG_NAME: &str = "no-mmap";
+pub const COLOR_ARG_NAME: &str = "color";
+
+const HIGHLIGHT_FORMAT: &str = "highlight";
+pub const CSV_FORMAT: &str = "csv";
+pub const JSON_FORMAT: &str = "json";
+pub const VIMGREP_FORMAT: &str = "vimgrep";
+
+const FOLLOW_DESCRIPTOR: &str = "descriptor";
+pub const FOLLOW_NAME: &str = "name";
+
+const COLOR_AUTO: &str = "auto";
+pub const COLOR_ALWAYS: &str = "always";
+pub const COLOR_NEVER: &str = "never";
+
+/// The arguments that cannot be used when only matching lines are printed, as they rely on every line being printed.
+const ONLY_MATCHING_CONFLICTS: &[&str] = &[
+    RULES_ARG_NAME,
+    REGEXP_ARG_NAME,
+    STATS_ARG_NAME,
+    DECORATIONS_ARG_NAME,
+    TAIL_HEADERS_ARG_NAME,
+    TAIL_PREFIX_ARG_NAME,
+];
+
+/// Setup the argument parser for the program with all possible flags
+// This is long, but it's only a flat list of arguments; splitting it up would not make it any clearer.
+#[allow(clippy::too_many_lines)]
+pub fn setup_arg_parser() -> App<'static, 'static> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .about("Highlights lines that match the given regular expression")
+        .setting(AppSettings::DisableVersion)
+        .arg(
+            Arg::with_name("pattern")
+                .takes_value(true)
+                .required_unless_one(&[RULES_ARG_NAME, REGEXP_ARG_NAME])
+                .allow_hyphen_values(true)
+                .help(concat!(
+                    "The regular expression to search for. Note that this is not anchored, and if ",
+                    "anchoring is desired, should be done manually with ^ or $. Not given with -e or --rules."
+                )),
+        )
+        .arg(
+            Arg::with_name(FILENAME_ARG_NAME)
+                .takes_value(true)
+                .multiple(true)
+                .help(concat!(
+                    "The files to scan. Several files are scanned one after another, as if they were a single file, ",
+                    "and - reads from stdin. If not specified, reads from stdin. If a directory is given, each file ",
+                    "directly within it is scanned, printing the name of each before its contents. Scanning a file ",
+                    "stops once binary data (a NUL byte) is found in it, unless -b is given."
+                )),
+        )
+        .arg(
+            Arg::with_name(CASE_INSENSITIVE_ARG_NAME)
+                .short("-i")
+                .long("--ignore-case")
+                .help("Ignore case when performing matching. If not specified, the matching is case-sensitive."),
+        )
+        .arg(
+            Arg::with_name(FIXED_STRINGS_ARG_NAME)
+                .short("-F")
+                .long("--fixed-strings")
+                .conflicts_with(RULES_ARG_NAME)
+                .help(concat!(
+                    "Treat the pattern as a literal string, rather than a regular expression, so that characters ",
+                    "such as . and [ match themselves."
+                )),
+        )
+        .arg(
+            Arg::with_name(INVERT_ARG_NAME)
+                .short("-v")
+                .long("--invert")
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME, PLUGIN_ARG_NAME])
+                .help("Highlight the lines that do not match the pattern, rather than those that do."),
+        )
+        .arg(
+            Arg::with_name(NO_PASSTHRU_ARG_NAME)
+                .long("--no-passthru")
+                .conflicts_with_all(ONLY_MATCHING_CONFLICTS)
+                .help(concat!(
+                    "Only print the lines that match the pattern, like grep, rather than every line. With -m, ",
+                    "scanning stops once num lines have matched."
+                )),
+        )
+        .arg(
+            Arg::with_name(AFTER_CONTEXT_ARG_NAME)
+                .short("-A")
+                .long("--after-context")
+                .takes_value(true)
+                .value_name("num")
+                .conflicts_with_all(ONLY_MATCHING_CONFLICTS)
+                .help("Print num lines after each matching line, as context. Implies --no-passthru."),
+        )
+        .arg(
+            Arg::with_name(BEFORE_CONTEXT_ARG_NAME)
+                .short("-B")
+                .long("--before-context")
+                .takes_value(true)
+                .value_name("num")
+                .conflicts_with_all(ONLY_MATCHING_CONFLICTS)
+                .help("Print num lines before each matching line, as context. Implies --no-passthru."),
+        )
+        .arg(
+            Arg::with_name(CONTEXT_ARG_NAME)
+                .short("-C")
+                .long("--context")
+                .takes_value(true)
+                .value_name("num")
+                .conflicts_with_all(ONLY_MATCHING_CONFLICTS)
+                .help(concat!(
+                    "Print num lines before and after each matching line, as context. -A and -B take precedence. ",
+                    "Implies --no-passthru."
+                )),
+        )
+        .arg(
+            Arg::with_name(RECURSIVE_ARG_NAME)
+                .short("-r")
+                .long("--recursive")
+                .help(concat!(
+                    "If the given file is a directory, scan the files in its subdirectories too, rather than only ",
+                    "the files directly within it."
+                )),
+        )
+        .arg(
+            Arg::with_name(GLOB_ARG_NAME)
+                .long("--glob")
+                .takes_value(true)
+                .value_name("glob")
+                .multiple(true)
+                .number_of_values(1)
+                .help(concat!(
+                    "When scanning a directory, only scan the files whose paths within it match the given glob ",
+                    "(e.g. --glob '*.log'), which may be given multiple times to scan the files matching any of them."
+                )),
+        )
+        .arg(
+            Arg::with_name(EXCLUDE_ARG_NAME)
+                .long("--exclude")
+                .takes_value(true)
+                .value_name("glob")
+                .multiple(true)
+                .number_of_values(1)
+                .help(concat!(
+                    "When scanning a directory, skip the files and directories whose paths within it match the given ",
+                    "glob (e.g. --exclude '*.gz' --exclude node_modules), even if they match --glob. May be given ",
+                    "multiple times."
+                )),
+        )
+        .arg(
+            Arg::with_name(TYPE_ARG_NAME)
+                .short("-t")
+                .long("--type")
+                .takes_value(true)
+                .value_name("type")
+                .multiple(true)
+                .number_of_values(1)
+                .help(concat!(
+                    "When scanning a directory, only scan files of the given type, such as log, rust, py, or json, ",
+                    "which may be given multiple times to scan the files of any of them. Types are matched by the ",
+                    "names of files (e.g. *.rs for rust)."
+                )),
+        )
+        .arg(
+            Arg::with_name(TYPE_ADD_ARG_NAME)
+                .long("--type-add")
+                .takes_value(true)
+                .value_name("name:glob")
+                .multiple(true)
+                .number_of_values(1)
+                .help(concat!(
+                    "Add a glob to a file type for -t, making the type if it does not exist (e.g. --type-add ",
+                    "'web:*.html'). May be given multiple times."
+                )),
+        )
+        .arg(
+            Arg::with_name(MAX_FILESIZE_ARG_NAME)
+                .long("--max-filesize")
+                .takes_value(true)
+                .value_name("bytes")
+                .help(concat!(
+                    "When scanning a directory, skip files larger than the given size, with a warning. The size may ",
+                    "be followed by K, M, or G (e.g. --max-filesize 50M)."
+                )),
+        )
+        .arg(
+            Arg::with_name(NO_IGNORE_ARG_NAME)
+                .long("--no-ignore")
+                .help(concat!(
+                    "When scanning a directory, scan the files listed in .gitignore and .ignore files too. Without ",
+                    "this, they are skipped, as git would."
+                )),
+        )
+        .arg(
+            Arg::with_name(HIDDEN_ARG_NAME)
+                .long("--hidden")
+                .help(concat!(
+                    "When scanning a directory, scan hidden files and directories, whose names start with a dot, too. ",
+                    "Without this, they are skipped."
+                )),
+        )
+        .arg(
+            Arg::with_name(DECOMPRESS_ARG_NAME)
+                .short("-z")
+                .long("--decompress")
+                .conflicts_with(FOLLOW_ARG_NAME)
+                .help(concat!(
+                    "Decompress the input before scanning it, if it was compressed with gzip, bzip2, or xz, like ",
+                    "zgrep. Input that is not compressed is scanned as-is."
+                )),
+        )
+        .arg(
+            Arg::with_name(ENCODING_ARG_NAME)
+                .long("--encoding")
+                .takes_value(true)
+                .value_name("label")
+                .conflicts_with_all(&[DECOMPRESS_ARG_NAME, FOLLOW_ARG_NAME])
+                .help(concat!(
+                    "Transcode the input from the given encoding (e.g. latin-1, shift_jis, or utf-16le) before ",
+                    "scanning it. Without this, UTF-16 input is detected by its byte order mark, and all other input ",
+                    "is assumed to be UTF-8."
+                )),
+        )
+        .arg(
+            Arg::with_name(NO_MMAP_ARG_NAME)
+                .long("--no-mmap")
+                .help(concat!(
+                    "Read files, rather than mapping them into memory. Mapped files are scanned faster, but may ",
+                    "cause a crash if they are truncated while being scanned, which is more likely on network ",
+                    "filesystems."
+                )),
+        )
+        .arg(
+            Arg::with_name(FOLLOW_ARG_NAME)
+                .short("-f")
+                .long("--follow")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .value_name("mode")
+                .possible_values(&[FOLLOW_DESCRIPTOR, FOLLOW_NAME])
+                .conflicts_with_all(&[RECURSIVE_ARG_NAME, LESSOPEN_ARG_NAME])
+                .help(concat!(
+                    "Once the end of the file is reached, wait for more lines to be appended and highlight them as ",
+                    "they arrive, like tail -f. With --follow=name, the file is reopened by its name if it is ",
+                    "replaced or truncated, like tail -F, so that logs can be followed through rotation. Has no ",
+                    "effect when reading from stdin."
+                )),
+        )
+        .arg(
+            Arg::with_name(WATCH_ARG_NAME)
+                .long("--watch")
+                .conflicts_with_all(&[FOLLOW_ARG_NAME, RECURSIVE_ARG_NAME, LESSOPEN_ARG_NAME])
+                .help(concat!(
+                    "Once the file has been scanned, wait for it to change and scan it again, clearing the screen ",
+                    "first if printing to a terminal. Has no effect when reading from stdin."
+                )),
+        )
+        .arg(
+            Arg::with_name(RESET_LINE_NUMBERS_ARG_NAME)
+                .long("--reset-line-numbers")
+                .help(concat!(
+                    "When given more than one file, scan each one separately, so that line numbers, --max-count, ",
+                    "--count, and --stats start over for each file, rather than continuing across them."
+                )),
+        )
+        .arg(
+            Arg::with_name(WITH_FILENAME_ARG_NAME)
+                .short("-H")
+                .long("--with-filename")
+                .overrides_with(NO_FILENAME_ARG_NAME)
+                .conflicts_with_all(&[DECORATIONS_ARG_NAME, TAIL_PREFIX_ARG_NAME])
+                .help(concat!(
+                    "Prefix each line with the name of the file it came from. This is the default when more than one ",
+                    "file is given, in which case each is scanned separately, as with --reset-line-numbers."
+                )),
+        )
+        .arg(
+            Arg::with_name(NO_FILENAME_ARG_NAME)
+                .long("--no-filename")
+                .overrides_with(WITH_FILENAME_ARG_NAME)
+                .help(concat!(
+                    "Never prefix lines with the name of the file they came from. When more than one file is given, ",
+                    "they are scanned as if they were a single file."
+                )),
+        )
+        .arg(
+            Arg::with_name(NULL_ARG_NAME)
+                .short("-0")
+                .long("--null")
+                .help(concat!(
+                    "Follow each filename that is printed with a NUL byte, rather than the colon or newline that ",
+                    "usually follows it, so that filenames holding those characters can be told apart from the rest ",
+                    "of the output. This applies to headings, lines prefixed with -H, --count, and --stats."
+                )),
+        )
+        .arg(
+            Arg::with_name(STDIN_TTY_ARG_NAME)
+                .long("--stdin-tty")
+                .help(concat!(
+                    "Read from stdin even when it is a terminal, to highlight lines as they are typed. Without this, ",
+                    "hline refuses to wait for input from a terminal when no file is given."
+                )),
+        )
+        .arg(
+            Arg::with_name(LINE_NUMBER_ARG_NAME)
+                .short("-n")
+                .long("--line-number")
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME, DECORATIONS_ARG_NAME])
+                .help(concat!(
+                    "Prefix each line with its line number, followed by a colon, as grep -n does. This comes ",
+                    "before the byte offset, if --byte-offset is given too."
+                )),
+        )
+        .arg(
+            Arg::with_name(BYTE_OFFSET_ARG_NAME)
+                .long("--byte-offset")
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME])
+                .help(concat!(
+                    "Prefix each line with the byte offset of its start within the input, followed by a colon, as ",
+                    "grep -b does."
+                )),
+        )
+        .arg(
+            Arg::with_name(MARKER_ARG_NAME)
+                .long("--marker")
+                .conflicts_with_all(&[
+                    RULES_ARG_NAME,
+                    REGEXP_ARG_NAME,
+                    INVERT_ARG_NAME,
+                    DECORATIONS_ARG_NAME,
+                    NULL_DATA_ARG_NAME,
+                    LINE_TERMINATOR_ARG_NAME,
+                ])
+                .help(concat!(
+                    "After each matching line, print a line with ^ under each match, so that matches can be seen ",
+                    "without colors, such as in CI logs."
+                )),
+        )
+        .arg(
+            Arg::with_name(OK_IF_BINARY_ARG_NAME)
+                .short("-b")
+                .help(concat!(
+                    "Treat the given input file as text, even if it has binary data (NUL bytes). Control characters are ",
+                    "printed as escapes (e.g. \\x00), so that they cannot garble the terminal."
+                )),
+        )
+        .arg(
+            Arg::with_name(STRINGS_ARG_NAME)
+                .long("--strings")
+                .conflicts_with_all(&[OK_IF_BINARY_ARG_NAME, NULL_DATA_ARG_NAME, LINE_TERMINATOR_ARG_NAME])
+                .help(concat!(
+                    "Scan only the runs of at least 4 printable characters in the input, each on its own line, ",
+                    "like strings(1). This allows the text within binary files to be scanned."
+                )),
+        )
+        .arg(
+            Arg::with_name(SHOW_CONTROL_CHARS_ARG_NAME)
+                .long("--show-control-chars")
+                .help(concat!(
+                    "Print control characters other than tabs in caret notation (e.g. ^L or ^[), as cat -v does, ",
+                    "so that they can be seen and do not garble the terminal. This takes precedence over the escapes ",
+                    "printed with -b."
+                )),
+        )
+        .arg(
+            Arg::with_name(SQUEEZE_BLANK_ARG_NAME)
+                .short("-s")
+                .long("--squeeze-blank")
+                .help("Collapse each run of blank lines into a single blank line, as cat -s does."),
+        )
+        .arg(
+            Arg::with_name(LESSOPEN_ARG_NAME)
+                .long("--lessopen")
+                .help(concat!(
+                    "Run as a LESSOPEN filter for less (e.g. LESSOPEN='||hline --lessopen <pattern> %s'). ",
+                    "Errors are not reported, and any failure, including binary files, exits with status 1 ",
+                    "and no output so that less can display the file as-is."
+                )),
+        )
+        .arg(
+            Arg::with_name(NOTIFY_URL_ARG_NAME)
+                .long("--notify-url")
+                .takes_value(true)
+                .value_name("url")
+                .conflicts_with_all(&[JSON_ARG_NAME, VIMGREP_ARG_NAME, FORMAT_ARG_NAME])
+                .help(concat!(
+                    "POST each matching line to the given http:// URL as a JSON object with the fields line, ",
+                    "timestamp (in seconds since the Unix epoch), file, and pattern, so that matches in a log that ",
+                    "is being followed (e.g. with tail -f) can raise alerts. Each request holds a JSON array of the ",
+                    "matches found within a second of each other, and requests that fail are retried up to 3 times."
+                )),
+        )
+        .arg(
+            Arg::with_name(SIGNAL_STATS_ARG_NAME)
+                .long("--signal-stats")
+                .conflicts_with_all(&[JSON_ARG_NAME, VIMGREP_ARG_NAME, FORMAT_ARG_NAME])
+                .help(concat!(
+                    "Print the number of lines seen and matched so far, and how long hl has been running for, to ",
+                    "stderr each time hl receives SIGUSR1 (e.g. from kill -USR1), so that a long-running hl (such as ",
+                    "one reading from tail -f) can be checked on. Only available on Unix."
+                )),
+        )
+        .arg(
+            Arg::with_name(INTERACTIVE_ARG_NAME)
+                .long("--interactive")
+                .conflicts_with_all(&[
+                    RULES_ARG_NAME,
+                    REGEXP_ARG_NAME,
+                    RECURSIVE_ARG_NAME,
+                    FOLLOW_ARG_NAME,
+                    WATCH_ARG_NAME,
+                    FORMAT_ARG_NAME,
+                    JSON_ARG_NAME,
+                    VIMGREP_ARG_NAME,
+                    COUNT_ARG_NAME,
+                    COUNT_ONLY_ARG_NAME,
+                    LESSOPEN_ARG_NAME,
+                    MATCHES_TO_ARG_NAME,
+                    PLUGIN_ARG_NAME,
+                    NOTIFY_URL_ARG_NAME,
+                    SIGNAL_STATS_ARG_NAME,
+                ])
+                .help(concat!(
+                    "Show the highlighted lines in a full-screen pager. Use the arrow keys, j/k, space/b, and g/G ",
+                    "to scroll, n/N to jump to the next/previous match, / to search within the results (after ",
+                    "which n/N jump between the lines it found, until esc is pressed), and q to quit. Options that ",
+                    "change how lines are printed have no effect."
+                )),
+        )
+        .arg(
+            Arg::with_name(TAIL_HEADERS_ARG_NAME)
+                .long("--tail-headers")
+                .help(concat!(
+                    "Treat \"==> file <==\" lines, as produced by tail when given multiple files, as headers. ",
+                    "These will be styled separately and never highlighted."
+                )),
+        )
+        .arg(
+            Arg::with_name(TAIL_PREFIX_ARG_NAME)
+                .long("--tail-prefix")
+                .help("Same as --tail-headers, but also prefix each line with the file named in the preceding header."),
+        )
+        .arg(
+            Arg::with_name(PREFIXED_ARG_NAME)
+                .long("--prefixed")
+                .conflicts_with_all(&[TAIL_HEADERS_ARG_NAME, TAIL_PREFIX_ARG_NAME])
+                .help(concat!(
+                    "Treat the input as lines prefixed with their source, such as from `kubectl logs --prefix` ",
+                    "or `docker compose logs`. Each source is given its own color, and the pattern is only ",
+                    "matched against the remainder of the line."
+                )),
+        )
+        .arg(
+            Arg::with_name(MATCHES_TO_ARG_NAME)
+                .long("--matches-to")
+                .takes_value(true)
+                .value_name("path")
+                .help("Append a copy of every matching line, without colors, to the given file."),
+        )
+        .arg(
+            Arg::with_name(FORMAT_ARG_NAME)
+                .long("--format")
+                .takes_value(true)
+                .value_name("format")
+                .possible_values(&[HIGHLIGHT_FORMAT, CSV_FORMAT, JSON_FORMAT, VIMGREP_FORMAT])
+                // This has no default value, as clap would then always consider it to conflict with the options below
+                .conflicts_with_all(&[
+                    TAIL_HEADERS_ARG_NAME,
+                    TAIL_PREFIX_ARG_NAME,
+                    MATCHES_TO_ARG_NAME,
+                    JSON_ARG_NAME,
+                ])
+                .help(concat!(
+                    "The format to output results in. \"highlight\" prints the whole input with matching lines ",
+                    "highlighted. \"csv\" prints only matching lines, as rows of ",
+                    "file,line_number,byte_offset,matched_text,full_line. \"json\" prints every line as a JSON ",
+                    "object in the format of ripgrep's --json output: a \"begin\" message for each file, a ",
+                    "\"match\" or \"context\" message for each line, an \"end\" message with the statistics of ",
+                    "each file, and a \"summary\" message once every file is scanned. \"vimgrep\" prints ",
+                    "file:line:column:text for each match, so that the results can be loaded into an editor's ",
+                    "quickfix list. Defaults to \"highlight\"."
+                )),
+        )
+        .arg(
+            Arg::with_name(COUNT_ARG_NAME)
+                .short("-c")
+                .long("--count")
+                .conflicts_with(FOLLOW_ARG_NAME)
+                .help(concat!(
+                    "After printing the output, print the number of matching lines to stderr. When scanning a ",
+                    "directory, the count for each file is printed after it, preceded by its name."
+                )),
+        )
+        .arg(
+            Arg::with_name(COUNT_ONLY_ARG_NAME)
+                .long("--count-only")
+                .conflicts_with_all(&[COUNT_ARG_NAME, FOLLOW_ARG_NAME])
+                .help("Only print the number of matching lines, in place of the output, like grep -c."),
+        )
+        .arg(
+            Arg::with_name(MAX_COUNT_ARG_NAME)
+                .short("-m")
+                .long("--max-count")
+                .takes_value(true)
+                .value_name("num")
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME])
+                .help(concat!(
+                    "Only highlight the first num matching lines. Any matching lines after these are printed as if ",
+                    "they did not match."
+                )),
+        )
+        .arg(
+            Arg::with_name(STOP_AFTER_ARG_NAME)
+                .long("--stop-after")
+                .takes_value(true)
+                .value_name("num")
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME])
+                .help(concat!(
+                    "Stop scanning once num matching lines have been printed, without printing any of the lines ",
+                    "after them. This is quicker than --max-count for checking that a large file has a match."
+                )),
+        )
+        .arg(
+            Arg::with_name(MAX_COLUMNS_ARG_NAME)
+                .long("--max-columns")
+                .takes_value(true)
+                .value_name("num")
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME])
+                .help(concat!(
+                    "Truncate lines that are wider than num columns, marking the text removed from them with an ",
+                    "ellipsis. Matching lines are truncated around their first match, so that it stays visible ",
+                    "where it fits."
+                )),
+        )
+        .arg(
+            Arg::with_name(REPLACE_ARG_NAME)
+                .long("--replace")
+                .takes_value(true)
+                .value_name("template")
+                .conflicts_with_all(&[
+                    RULES_ARG_NAME,
+                    REGEXP_ARG_NAME,
+                    INVERT_ARG_NAME,
+                    COLOR_GROUPS_ARG_NAME,
+                    MARKER_ARG_NAME,
+                ])
+                .help(concat!(
+                    "In matching lines, replace each match with template, in which $1 or ${name} is replaced by ",
+                    "the text matched by that capture group. Lines that do not match are printed as-is."
+                )),
+        )
+        .arg(
+            Arg::with_name(NULL_DATA_ARG_NAME)
+                .long("--null-data")
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME])
+                .help(concat!(
+                    "Treat the input as records terminated by NUL bytes, rather than lines terminated by newlines ",
+                    "(e.g. the output of find -print0). The NUL bytes are printed as-is."
+                )),
+        )
+        .arg(
+            Arg::with_name(MULTILINE_ARG_NAME)
+                .short("-U")
+                .long("--multiline")
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME, FOLLOW_ARG_NAME])
+                .help(concat!(
+                    "Allow the pattern to match across lines, such as with \\n, highlighting every line a match ",
+                    "spans. The whole input is read before any of it is printed."
+                )),
+        )
+        .arg(
+            Arg::with_name(CRLF_ARG_NAME)
+                .long("--crlf")
+                .conflicts_with_all(&[
+                    NULL_DATA_ARG_NAME,
+                    LINE_TERMINATOR_ARG_NAME,
+                    RULES_ARG_NAME,
+                    REGEXP_ARG_NAME,
+                ])
+                .help(concat!(
+                    "Treat lines as terminated by \\r\\n, so that $ in the pattern matches before the \\r, ",
+                    "for files with Windows line endings."
+                )),
+        )
+        .arg(
+            Arg::with_name(LINE_TERMINATOR_ARG_NAME)
+                .long("--line-terminator")
+                .takes_value(true)
+                .value_name("byte")
+                .conflicts_with_all(&[NULL_DATA_ARG_NAME, RULES_ARG_NAME, REGEXP_ARG_NAME])
+                .help(concat!(
+                    "Treat the input as records terminated by the given ASCII character, rather than lines ",
+                    "terminated by newlines. Escapes such as \\f, \\t, \\0, and \\x1e may be used for ",
+                    "characters that are hard to type."
+                )),
+        )
+        .arg(
+            Arg::with_name(STATS_ARG_NAME)
+                .long("--stats")
+                .conflicts_with(FOLLOW_ARG_NAME)
+                .help(concat!(
+                    "After printing the output, print the number of lines scanned and matched, the number of bytes ",
+                    "processed, and the time taken to stderr."
+                )),
+        )
+        .arg(
+            Arg::with_name(JSON_ARG_NAME)
+                .long("--json")
+                .conflicts_with_all(&[TAIL_HEADERS_ARG_NAME, TAIL_PREFIX_ARG_NAME, MATCHES_TO_ARG_NAME])
+                .help("Shorthand for --format json."),
+        )
+        .arg(
+            Arg::with_name(VIMGREP_ARG_NAME)
+                .long("--vimgrep")
+                .conflicts_with_all(&[
+                    JSON_ARG_NAME,
+                    TAIL_HEADERS_ARG_NAME,
+                    TAIL_PREFIX_ARG_NAME,
+                    MATCHES_TO_ARG_NAME,
+                ])
+                .help("Shorthand for --format vimgrep."),
+        )
+        .arg(
+            Arg::with_name(REGEXP_ARG_NAME)
+                .short("-e")
+                .long("--regexp")
+                .takes_value(true)
+                .value_name("pattern[=style]")
+                .multiple(true)
+                .number_of_values(1)
+                .allow_hyphen_values(true)
+                .conflicts_with_all(&[
+                    RULES_ARG_NAME,
+                    PREFIXED_ARG_NAME,
+                    NOTIFY_URL_ARG_NAME,
+                    PLUGIN_ARG_NAME,
+                ])
+                .help(concat!(
+                    "A pattern to search for, which may be given multiple times. Each may be followed by =style ",
+                    "(e.g. -e 'ERROR=bold red' -e 'WARN=black on yellow'), where style is a list of attributes and ",
+                    "color names, such as red or light-blue; a color after \"on\" sets the background. Without a ",
+                    "color, the --color-match color is used, and without a style, the --style attributes are used ",
+                    "too. If a line matches more than one pattern, the first given decides its style."
+                )),
+        )
+        .arg(
+            Arg::with_name(COLOR_MATCH_ARG_NAME)
+                .long("--color-match")
+                .takes_value(true)
+                .value_name("color")
+                // Rules and plugins choose their own colors
+                .conflicts_with_all(&[RULES_ARG_NAME, PLUGIN_ARG_NAME])
+                .help(concat!(
+                    "The color to highlight matching lines in, either a color name such as red or light-blue, or a ",
+                    "number from 0-255. Overrides the color from --theme."
+                )),
+        )
+        .arg(
+            Arg::with_name(BG_ARG_NAME)
+                .long("--bg")
+                .takes_value(true)
+                .value_name("color")
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME, PLUGIN_ARG_NAME])
+                .help(concat!(
+                    "The background color to highlight matching lines with. The text keeps its usual color, unless ",
+                    "--color-match is also given. Overrides the colors from --theme."
+                )),
+        )
+        .arg(
+            Arg::with_name(ALTERNATE_COLOR_ARG_NAME)
+                .long("--alternate-color")
+                .takes_value(true)
+                .value_name("color")
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME])
+                .help(concat!(
+                    "Highlight matches that directly follow another match in this color, switching back and forth ",
+                    "between it and the usual color, so that adjacent matches can be told apart."
+                )),
+        )
+        .arg(
+            Arg::with_name(STYLE_ARG_NAME)
+                .long("--style")
+                .takes_value(true)
+                .value_name("attribute")
+                .multiple(true)
+                .number_of_values(1)
+                .use_delimiter(true)
+                .possible_values(ATTRIBUTE_NAMES)
+                .conflicts_with(RULES_ARG_NAME)
+                .help(concat!(
+                    "A text attribute to highlight matching lines with, alongside their color. May be given multiple ",
+                    "times, or as a comma-separated list (e.g. --style bold,underline)."
+                )),
+        )
+        .arg(
+            Arg::with_name(FOCUS_ARG_NAME)
+                .long("--focus")
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME])
+                .help(concat!(
+                    "Print the lines that do not match in a dim gray, so that matching lines stand out while the ",
+                    "rest of the output is still shown."
+                )),
+        )
+        .arg(
+            Arg::with_name(DIM_ARG_NAME)
+                .long("--dim")
+                .takes_value(true)
+                .value_name("pattern")
+                .allow_hyphen_values(true)
+                .conflicts_with_all(&[RULES_ARG_NAME, REGEXP_ARG_NAME])
+                .help(concat!(
+                    "Print the lines that do not match the pattern, but do match this one, dimmed, so that noisy ",
+                    "lines such as health checks recede into the background."
+                )),
+        )
+        .arg(
+            Arg::with_name(COLOR_GROUPS_ARG_NAME)
+                .long("--color-groups")
+                .conflicts_with_all(&[
+                    RULES_ARG_NAME,
+                    REGEXP_ARG_NAME,
+                    INVERT_ARG_NAME,
+                    FIXED_STRINGS_ARG_NAME,
+                    PREFIXED_ARG_NAME,
+                ])
+                .help(concat!(
+                    "Highlight each capture group of the pattern in its own color within matching lines: group 1 in ",
+                    "red, group 2 in green, then yellow, blue, magenta, and cyan."
+                )),
+        )
+        .arg(
+            Arg::with_name(COLOR_ARG_NAME)
+                .long("--color")
+                .takes_value(true)
+                .value_name("when")
+                .possible_values(&[COLOR_AUTO, COLOR_ALWAYS, COLOR_NEVER])
+                .default_value(COLOR_AUTO)
+                .help(concat!(
+                    "When to color the output. By default, it is only colored when printing to a terminal, to less ",
+                    "with --lessopen, or to less as part of git's pager (e.g. core.pager='hl <pattern> | less') if ",
+                    "the LESS environment variable git sets has less display colors. This can also be set with the ",
+                    "NO_COLOR and CLICOLOR_FORCE environment variables."
+                )),
+        )
+        .arg(
+            Arg::with_name(THEME_ARG_NAME)
+                .long("--theme")
+                .takes_value(true)
+                .value_name("name")
+                .possible_values(THEME_NAMES)
+                .default_value("default")
+                .help(concat!(
+                    "The theme to style the output with, which sets the colors of matching lines, other lines, line ",
+                    "numbers, and headers."
+                )),
+        )
+        .arg(
+            Arg::with_name(RULES_ARG_NAME)
+                .long("--rules")
+                .takes_value(true)
+                .value_name("path")
+                .conflicts_with_all(&[PREFIXED_ARG_NAME, NOTIFY_URL_ARG_NAME])
+                .help(concat!(
+                    "Handle lines using the rules in the given TOML file, rather than a single pattern. Each ",
+                    "[[rule]] has a pattern, an optional color or style, and an optional action (highlight, suppress, stop, ",
+                    "or bell); the first rule matching a line decides how it is handled. -i, --format csv, and ",
+                    "--format json cannot be used with rules."
+                )),
+        )
+        .arg(
+            Arg::with_name(PLUGIN_ARG_NAME)
+                .long("--plugin")
+                .takes_value(true)
+                .value_name("path")
+                .conflicts_with_all(&[RULES_ARG_NAME, JSON_ARG_NAME, VIMGREP_ARG_NAME, FORMAT_ARG_NAME])
+                .help(concat!(
+                    "Pass each line through the on_line function of the given Lua script, which can replace the ",
+                    "line, change its highlighting, or drop it. See the documentation of hline's plugin module for ",
+                    "details. Only available when hl is built with the lua feature."
+                )),
+        )
+        .arg(
+            Arg::with_name(DECORATIONS_ARG_NAME)
+                .long("--decorations")
+                // The gutter numbers each line it prints, which are not the lines of the input if any are squeezed out
+                // or split on another terminator
+                .conflicts_with_all(&[
+                    SQUEEZE_BLANK_ARG_NAME,
+                    NULL_DATA_ARG_NAME,
+                    LINE_TERMINATOR_ARG_NAME,
+                    STRINGS_ARG_NAME,
+                ])
+                .help(concat!(
+                    "Decorate the output in the style of bat, with a header naming the file and line numbers ",
+                    "in a gutter. The grid spans $COLUMNS columns, or 80 if it is not set."
+                )),
+        )
+        .arg(
+            Arg::with_name(GUTTER_COLOR_ARG_NAME)
+                .long("--gutter-color")
+                .takes_value(true)
+                .value_name("color")
+                .requires(DECORATIONS_ARG_NAME)
+                .help(concat!(
+                    "The color of the line numbers in the --decorations gutter, and of the grid around them. ",
+                    "Overrides the color from --theme."
+                )),
+        )
+        .arg(
+            Arg::with_name(GUTTER_SEPARATOR_ARG_NAME)
+                .long("--gutter-separator")
+                .takes_value(true)
+                .value_name("char")
+                .requires(DECORATIONS_ARG_NAME)
+                .help("The character that separates the --decorations gutter from each line. Defaults to │."),
+        )
+        .arg(
+            Arg::with_name(GUTTER_PADDING_ARG_NAME)
+                .long("--gutter-padding")
+                .takes_value(true)
+                .value_name("num")
+                .requires(DECORATIONS_ARG_NAME)
+                .help(concat!(
+                    "The number of spaces between each line number and the separator in the --decorations gutter. ",
+                    "Defaults to 3."
+                )),
+        )
+        .arg(
+            Arg::with_name(WRAP_ARG_NAME)
+                .long("--wrap")
+                .help(concat!(
+                    "Wrap lines that are wider than the terminal ($COLUMNS columns, or 80 if it is not set) before ",
+                    "printing them, so that highlights continue onto every row of a long line, even in terminals ",
+                    "that reset colors when they wrap lines. Has no effect when output is not colored."
+                )),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("-s"; "squeeze blank")]
+    #[test_case("--null-data"; "null data")]
+    #[test_case("--line-terminator=;"; "line terminator")]
+    #[test_case("--strings"; "strings")]
+    fn test_decorations_conflict_with_flags_that_renumber_lines(flag: &str) {
+        let res = setup_arg_parser().get_matches_from_safe([
+            "hl",
+            "--decorations",
+            flag,
+            "foo",
+            "bar.log",
+        ]);
+        let err = res.expect_err("parsed conflicting flags");
+        assert_eq!(clap::ErrorKind::ArgumentConflict, err.kind);
+    }
+}
@@ -0,0 +1,811 @@
+use super::app::{
+    AFTER_CONTEXT_ARG_NAME, ALTERNATE_COLOR_ARG_NAME, BEFORE_CONTEXT_ARG_NAME, BG_ARG_NAME,
+    BYTE_OFFSET_ARG_NAME, CASE_INSENSITIVE_ARG_NAME, COLOR_ALWAYS, COLOR_ARG_NAME,
+    COLOR_GROUPS_ARG_NAME, COLOR_MATCH_ARG_NAME, COLOR_NEVER, CONTEXT_ARG_NAME, COUNT_ARG_NAME,
+    COUNT_ONLY_ARG_NAME, CRLF_ARG_NAME, CSV_FORMAT, DECOMPRESS_ARG_NAME, DECORATIONS_ARG_NAME,
+    DIM_ARG_NAME, ENCODING_ARG_NAME, EXCLUDE_ARG_NAME, FILENAME_ARG_NAME, FIXED_STRINGS_ARG_NAME,
+    FOCUS_ARG_NAME, FOLLOW_ARG_NAME, FOLLOW_NAME, FORMAT_ARG_NAME, GLOB_ARG_NAME,
+    GUTTER_COLOR_ARG_NAME, GUTTER_PADDING_ARG_NAME, GUTTER_SEPARATOR_ARG_NAME, HIDDEN_ARG_NAME,
+    INTERACTIVE_ARG_NAME, INVERT_ARG_NAME, JSON_ARG_NAME, JSON_FORMAT, LESSOPEN_ARG_NAME,
+    LINE_NUMBER_ARG_NAME, LINE_TERMINATOR_ARG_NAME, MARKER_ARG_NAME, MATCHES_TO_ARG_NAME,
+    MAX_COLUMNS_ARG_NAME, MAX_COUNT_ARG_NAME, MAX_FILESIZE_ARG_NAME, MULTILINE_ARG_NAME,
+    NOTIFY_URL_ARG_NAME, NO_FILENAME_ARG_NAME, NO_IGNORE_ARG_NAME, NO_MMAP_ARG_NAME,
+    NO_PASSTHRU_ARG_NAME, NULL_ARG_NAME, NULL_DATA_ARG_NAME, OK_IF_BINARY_ARG_NAME,
+    PATTERN_ARG_NAME, PLUGIN_ARG_NAME, PREFIXED_ARG_NAME, RECURSIVE_ARG_NAME, REGEXP_ARG_NAME,
+    REPLACE_ARG_NAME, RESET_LINE_NUMBERS_ARG_NAME, RULES_ARG_NAME, SHOW_CONTROL_CHARS_ARG_NAME,
+    SIGNAL_STATS_ARG_NAME, SQUEEZE_BLANK_ARG_NAME, STATS_ARG_NAME, STOP_AFTER_ARG_NAME,
+    STRINGS_ARG_NAME, STYLE_ARG_NAME, TAIL_HEADERS_ARG_NAME, TAIL_PREFIX_ARG_NAME, THEME_ARG_NAME,
+    TYPE_ADD_ARG_NAME, TYPE_ARG_NAME, VIMGREP_ARG_NAME, VIMGREP_FORMAT, WATCH_ARG_NAME,
+    WITH_FILENAME_ARG_NAME, WRAP_ARG_NAME,
+};
+use super::notify::NotifyUrl;
+use super::validate::{validate_args, validate_files, validate_stdin};
+use clap::{ArgMatches, ErrorKind};
+use encoding_rs::Encoding;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use hline::color::AnsiValue;
+use hline::file::walk::{FileTypeError, FileTypes, Walk};
+use hline::options::ScanOptions;
+use hline::print::Gutter;
+use hline::style::{Attribute, Attributes, Style};
+use hline::theme::Theme;
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+/// The color that patterns given with `-e` are highlighted in if no other color is given and the theme does not set
+/// one. This is light red.
+const DEFAULT_COLOR: AnsiValue = AnsiValue(9);
+
+/// The color that lines that did not match are printed in with `--focus`, unless the theme sets one. This is gray.
+const FOCUS_CONTEXT_COLOR: AnsiValue = AnsiValue(8);
+
+/// `PassedFile` represents some kind of file that will be passed in an argument
+pub enum PassedFile {
+    Stdin,
+    Path(PathBuf),
+    /// Several files, scanned one after another as if they were a single file
+    Concatenated(Vec<PassedFile>),
+}
+
+/// `Args` represents arguments passed to the program
+// Most flags are independent switches, so there's no better representation for them than bools.
+#[allow(clippy::struct_excessive_bools)]
+pub struct Args {
+    pub matching: Matching,
+    pub plugin_file: Option<PathBuf>,
+    pub file: PassedFile,
+    pub ok_if_binary_file: bool,
+    pub strings: bool,
+    pub show_control_chars: bool,
+    pub squeeze_blank: bool,
+    pub lessopen: bool,
+    pub notify_url: Option<NotifyUrl>,
+    pub signal_stats: bool,
+    pub interactive: bool,
+    pub tail_headers: TailHeaders,
+    pub prefixed: bool,
+    pub recursive: bool,
+    pub walk_options: WalkOptions,
+    pub follow: Option<Follow>,
+    pub watch: bool,
+    pub reset_line_numbers: bool,
+    pub decompress: bool,
+    pub encoding: Option<&'static Encoding>,
+    pub mmap: bool,
+    pub theme: Theme,
+    pub color: bool,
+    pub scan_options: ScanOptions,
+    pub matches_to: Option<PathBuf>,
+    pub format: OutputFormat,
+    pub decorations: Option<Gutter>,
+    pub with_filename: bool,
+    pub null: bool,
+    pub wrap: bool,
+    pub count: Count,
+    pub stats: bool,
+}
+
+/// `WalkOptions` represents which of the files within a directory should be scanned
+pub struct WalkOptions {
+    include_globs: Option<GlobSet>,
+    type_globs: Option<GlobSet>,
+    exclude_globs: Option<GlobSet>,
+    respect_ignore_files: bool,
+    hidden: bool,
+    max_filesize: Option<u64>,
+}
+
+/// `Matching` represents how lines should be matched
+pub enum Matching {
+    /// A single pattern, given positionally
+    Pattern(String),
+    /// One or more patterns given with -e, each with the style they should be highlighted with
+    Patterns(Vec<(String, Style)>),
+    /// Rules loaded from the file at the given path
+    RulesFile(PathBuf),
+}
+
+/// `OutputFormat` represents the format that results should be output in
+pub enum OutputFormat {
+    Highlight,
+    Csv,
+    Json,
+    Vimgrep,
+}
+
+/// `Count` represents whether the number of matching lines should be reported
+pub enum Count {
+    Off,
+    /// Report the count on stderr, after the output
+    AfterOutput,
+    /// Report the count on stdout, in place of the output
+    Only,
+}
+
+/// `Follow` represents what is followed once the end of a file is reached
+pub enum Follow {
+    /// Follow the file that was opened, even if it is renamed or removed
+    Descriptor,
+    /// Follow whichever file has the name that was given, reopening it if it is replaced or truncated
+    Name,
+}
+
+/// `TailHeaders` represents how headers from `tail`'s multi-file output should be handled
+pub enum TailHeaders {
+    Ignore,
+    Style,
+    StyleAndPrefix,
+}
+
+impl Display for PassedFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stdin => write!(f, "<stdin>"),
+            Self::Path(path) => write!(f, "{}", path.display()),
+            Self::Concatenated(files) => {
+                let names: Vec<String> = files.iter().map(ToString::to_string).collect();
+                write!(f, "{}", names.join(", "))
+            }
+        }
+    }
+}
+
+impl WalkOptions {
+    /// Make a walk over the files within the given directory that should be scanned
+    pub fn walk(&self, dir: &Path) -> Walk {
+        let mut walk = Walk::new(dir);
+        if let Some(include_globs) = &self.include_globs {
+            walk = walk.include(include_globs.clone());
+        }
+        if let Some(type_globs) = &self.type_globs {
+            walk = walk.include(type_globs.clone());
+        }
+        if let Some(exclude_globs) = &self.exclude_globs {
+            walk = walk.exclude(exclude_globs.clone());
+        }
+        if self.respect_ignore_files {
+            walk = walk.respect_ignore_files();
+        }
+        if !self.hidden {
+            walk = walk.skip_hidden();
+        }
+
+        walk
+    }
+
+    /// Get the size of the given file if it is larger than the maximum size of file to scan, or `None` if it is not
+    /// (or its size could not be found, in which case opening it will report the problem).
+    pub fn oversized_file_size(&self, file: &PassedFile) -> Option<u64> {
+        let (Some(max_filesize), PassedFile::Path(path)) = (self.max_filesize, file) else {
+            return None;
+        };
+
+        fs::metadata(path)
+            .ok()
+            .map(|metadata| metadata.len())
+            .filter(|&size| size > max_filesize)
+    }
+}
+
+impl TryFrom<ArgMatches<'_>> for Args {
+    type Error = clap::Error;
+
+    fn try_from(args: ArgMatches) -> Result<Self, Self::Error> {
+        validate_args(&args)?;
+
+        let ok_if_binary_file = args.is_present(OK_IF_BINARY_ARG_NAME);
+        let strings = args.is_present(STRINGS_ARG_NAME);
+        let show_control_chars = args.is_present(SHOW_CONTROL_CHARS_ARG_NAME);
+        let squeeze_blank = args.is_present(SQUEEZE_BLANK_ARG_NAME);
+        let lessopen = args.is_present(LESSOPEN_ARG_NAME);
+        let notify_url = parse_arg(&args, NOTIFY_URL_ARG_NAME, NotifyUrl::parse)?;
+        let signal_stats = args.is_present(SIGNAL_STATS_ARG_NAME);
+        let interactive = args.is_present(INTERACTIVE_ARG_NAME);
+        let tail_headers = tail_headers_arg(&args);
+        let prefixed = args.is_present(PREFIXED_ARG_NAME);
+        let recursive = args.is_present(RECURSIVE_ARG_NAME);
+        let walk_options = make_walk_options(&args)?;
+        let follow = follow_arg(&args);
+        let watch = args.is_present(WATCH_ARG_NAME);
+        let reset_line_numbers = args.is_present(RESET_LINE_NUMBERS_ARG_NAME);
+        let decompress = args.is_present(DECOMPRESS_ARG_NAME);
+        let mmap = !args.is_present(NO_MMAP_ARG_NAME);
+        let encoding = parse_arg(&args, ENCODING_ARG_NAME, parse_encoding)?;
+        let theme = make_theme(&args)?;
+        let color = should_color(&args, lessopen);
+        let matches_to = args.value_of_os(MATCHES_TO_ARG_NAME).map(PathBuf::from);
+        let format = output_format_arg(&args);
+        let decorations = decorations_arg(&args, &theme)?;
+        let plugin_file = args.value_of_os(PLUGIN_ARG_NAME).map(PathBuf::from);
+        let null = args.is_present(NULL_ARG_NAME);
+        let wrap = args.is_present(WRAP_ARG_NAME);
+        let count = count_arg(&args);
+        let stats = args.is_present(STATS_ARG_NAME);
+        let (matching, filenames) = matching_arg(&args, &theme)?;
+        let with_filename = with_filename_arg(&args, &filenames);
+        validate_stdin(&args, &filenames)?;
+        validate_files(&args, &filenames)?;
+        let file = make_passed_file(&filenames);
+
+        let scan_options = make_scan_options(&args, theme)?;
+
+        Ok(Args {
+            matching,
+            plugin_file,
+            file,
+            ok_if_binary_file,
+            strings,
+            show_control_chars,
+            squeeze_blank,
+            lessopen,
+            notify_url,
+            signal_stats,
+            interactive,
+            tail_headers,
+            prefixed,
+            recursive,
+            walk_options,
+            follow,
+            watch,
+            reset_line_numbers,
+            decompress,
+            encoding,
+            mmap,
+            theme,
+            color,
+            scan_options,
+            matches_to,
+            format,
+            decorations,
+            with_filename,
+            null,
+            wrap,
+            count,
+            stats,
+        })
+    }
+}
+
+/// Whether the output should be colored, as chosen with `--color`. By default, the `NO_COLOR` and `CLICOLOR_FORCE`
+/// conventions are followed (see <https://no-color.org> and <https://bixense.com/clicolors/>), and otherwise output is
+/// only colored when it will be displayed, i.e. when stdout is a terminal, when filtering for `less`, or when piping to
+/// `less` as part of git's pager.
+fn should_color(args: &ArgMatches, lessopen: bool) -> bool {
+    match args.value_of(COLOR_ARG_NAME) {
+        Some(COLOR_ALWAYS) => true,
+        Some(COLOR_NEVER) => false,
+        _ if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) => false,
+        _ if env::var_os("CLICOLOR_FORCE").is_some_and(|value| value != "0") => true,
+        _ => lessopen || stdout_displays_colors() || in_colored_git_pager(),
+    }
+}
+
+/// Whether we are running as part of git's pager, such as with `core.pager = hl PATTERN | less`, where the `less` that
+/// our output is piped to will display colors. git sets `GIT_PAGER_IN_USE` for its pager, along with `LESS` (`FRX`,
+/// unless it is already set), whose `R` option has `less` display colors.
+fn in_colored_git_pager() -> bool {
+    env::var_os("GIT_PAGER_IN_USE").is_some()
+        && env::var("LESS").is_ok_and(|less_options| less_displays_colors(&less_options))
+}
+
+/// Whether the given options for `less`, as given in `LESS`, have it display colors, with `-R` or `-r` (or their long
+/// forms).
+fn less_displays_colors(less_options: &str) -> bool {
+    less_options.split_whitespace().any(|option| {
+        match option.strip_prefix("--") {
+            Some(long_option) => {
+                long_option == "RAW-CONTROL-CHARS" || long_option == "raw-control-chars"
+            }
+            // Single letter options may be grouped together, and need not start with a dash
+            None => option.trim_start_matches('-').contains(['R', 'r']),
+        }
+    })
+}
+
+/// Whether stdout is a terminal that can display colors. Legacy Windows consoles can only do so if
+/// [`enable_ansi_colors`] succeeded, and will otherwise print escape sequences verbatim.
+#[cfg(windows)]
+fn stdout_displays_colors() -> bool {
+    use winapi_util::console;
+    use winapi_util::HandleRef;
+
+    const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+
+    match console::mode(HandleRef::stdout()) {
+        Ok(mode) => mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0,
+        // Terminals that are not consoles, such as mintty, display colors natively
+        Err(_) => io::stdout().is_terminal(),
+    }
+}
+
+/// Whether stdout is a terminal that can display colors. All terminals on other platforms do.
+#[cfg(not(windows))]
+fn stdout_displays_colors() -> bool {
+    io::stdout().is_terminal()
+}
+
+/// Get the format to print results in, as given by `--format`
+fn output_format_arg(args: &ArgMatches) -> OutputFormat {
+    match format_arg(args) {
+        Some(CSV_FORMAT) => OutputFormat::Csv,
+        Some(JSON_FORMAT) => OutputFormat::Json,
+        Some(VIMGREP_FORMAT) => OutputFormat::Vimgrep,
+        _ => OutputFormat::Highlight,
+    }
+}
+
+/// Get how headers from `tail`'s multi-file output should be handled, as chosen with `--tail-headers` or `--tail-prefix`.
+fn tail_headers_arg(args: &ArgMatches) -> TailHeaders {
+    if args.is_present(TAIL_PREFIX_ARG_NAME) {
+        TailHeaders::StyleAndPrefix
+    } else if args.is_present(TAIL_HEADERS_ARG_NAME) {
+        TailHeaders::Style
+    } else {
+        TailHeaders::Ignore
+    }
+}
+
+/// Get whether the number of matching lines should be reported, as chosen with `--count` or `--count-only`.
+fn count_arg(args: &ArgMatches) -> Count {
+    if args.is_present(COUNT_ONLY_ARG_NAME) {
+        Count::Only
+    } else if args.is_present(COUNT_ARG_NAME) {
+        Count::AfterOutput
+    } else {
+        Count::Off
+    }
+}
+
+/// Parse the value given to the argument with the given name, if it was given, failing with an error that names the
+/// argument if the value is invalid.
+fn parse_arg<T, F: Fn(&str) -> Result<T, String>>(
+    args: &ArgMatches,
+    arg_name: &str,
+    parse: F,
+) -> Result<Option<T>, clap::Error> {
+    args.value_of(arg_name)
+        .map(|value| parse(value).map_err(|err| invalid_value(arg_name, &err)))
+        .transpose()
+}
+
+/// Identical to [`parse_arg`], except that every value given to the argument is parsed.
+fn parse_args<T, F: Fn(&str) -> Result<T, String>>(
+    args: &ArgMatches,
+    arg_name: &str,
+    parse: F,
+) -> Result<Option<Vec<T>>, clap::Error> {
+    args.values_of(arg_name)
+        .map(|values| {
+            values
+                .map(|value| parse(value).map_err(|err| invalid_value(arg_name, &err)))
+                .collect()
+        })
+        .transpose()
+}
+
+/// Make the error for an invalid value given to the argument with the given name.
+fn invalid_value(arg_name: &str, err: &str) -> clap::Error {
+    clap::Error::with_description(
+        &format!("Invalid value for '--{arg_name}': {err}"),
+        ErrorKind::ValueValidation,
+    )
+}
+
+/// Get how lines should be matched, as given by the pattern, `-e`, or `--rules`, along with the names of the files to
+/// scan, which are the positional arguments that are not the pattern.
+fn matching_arg<'a>(
+    args: &'a ArgMatches,
+    theme: &Theme,
+) -> Result<(Matching, Vec<&'a OsStr>), clap::Error> {
+    let case_insensitive = args.is_present(CASE_INSENSITIVE_ARG_NAME);
+    let fixed_strings = args.is_present(FIXED_STRINGS_ARG_NAME);
+    let make_pattern = |pat: &str| {
+        let pat = if fixed_strings {
+            regex::escape(pat)
+        } else {
+            pat.to_string()
+        };
+
+        if case_insensitive {
+            make_pattern_case_insensitive(&pat)
+        } else {
+            pat
+        }
+    };
+
+    let mut positionals = positional_args(args);
+    let matching = if let Some(rules_file) = args.value_of_os(RULES_ARG_NAME) {
+        Matching::RulesFile(PathBuf::from(rules_file))
+    } else if let Some(pattern_args) = args.values_of(REGEXP_ARG_NAME) {
+        let default_style = default_pattern_style(theme);
+        let patterns = pattern_args
+            .map(|pattern_arg| {
+                let (pattern, style) = split_pattern_style(pattern_arg, default_style);
+                (make_pattern(pattern), style)
+            })
+            .collect();
+
+        Matching::Patterns(patterns)
+    } else if positionals.is_empty() {
+        return Err(clap::Error::with_description(
+            "No pattern was given. Give one, or pass -e or --rules.",
+            ErrorKind::MissingRequiredArgument,
+        ));
+    } else {
+        // Without --rules or -e, the first positional argument is the pattern, and the rest are filenames. A single
+        // pattern is matched as configured by the scan options, so it is kept as-is.
+        let pattern = positionals.remove(0).to_str().ok_or_else(|| {
+            clap::Error::with_description("The pattern is not valid UTF-8.", ErrorKind::InvalidUtf8)
+        })?;
+
+        Matching::Pattern(pattern.to_string())
+    };
+
+    Ok((matching, positionals))
+}
+
+/// Get all of the positional arguments, in order. These are the pattern, unless `--rules` or `-e` are given, followed by
+/// the names of the files to scan. clap can't tell these apart itself, as it would need to know whether a pattern is
+/// given positionally before it parses the positional arguments.
+fn positional_args<'a>(args: &'a ArgMatches) -> Vec<&'a OsStr> {
+    args.values_of_os(PATTERN_ARG_NAME)
+        .into_iter()
+        .flatten()
+        .chain(args.values_of_os(FILENAME_ARG_NAME).into_iter().flatten())
+        .collect()
+}
+
+/// Make the file to scan from the given filenames, where - names stdin. Several files are concatenated, and no files
+/// at all means reading from stdin.
+fn make_passed_file(filenames: &[&OsStr]) -> PassedFile {
+    let mut files: Vec<PassedFile> = filenames
+        .iter()
+        .map(|&filename| {
+            if filename == "-" {
+                PassedFile::Stdin
+            } else {
+                PassedFile::Path(PathBuf::from(filename))
+            }
+        })
+        .collect();
+
+    match files.len() {
+        0 => PassedFile::Stdin,
+        1 => files.remove(0),
+        _ => PassedFile::Concatenated(files),
+    }
+}
+
+/// Get how the file should be followed, if `--follow` was given. `--follow` on its own follows the file descriptor.
+fn follow_arg(args: &ArgMatches) -> Option<Follow> {
+    if !args.is_present(FOLLOW_ARG_NAME) {
+        None
+    } else if args.value_of(FOLLOW_ARG_NAME) == Some(FOLLOW_NAME) {
+        Some(Follow::Name)
+    } else {
+        Some(Follow::Descriptor)
+    }
+}
+
+/// Get the output format that was chosen, either with `--format`, `--json`, or `--vimgrep`.
+pub fn format_arg<'a>(args: &'a ArgMatches) -> Option<&'a str> {
+    if args.is_present(JSON_ARG_NAME) {
+        Some(JSON_FORMAT)
+    } else if args.is_present(VIMGREP_ARG_NAME) {
+        Some(VIMGREP_FORMAT)
+    } else {
+        args.value_of(FORMAT_ARG_NAME)
+    }
+}
+
+/// The style that patterns given with `-e` are highlighted with when they do not give their own
+fn default_pattern_style(theme: &Theme) -> Style {
+    Style {
+        fg: Some(theme.match_fg.unwrap_or(DEFAULT_COLOR)),
+        bg: None,
+        attributes: theme.match_attributes,
+    }
+}
+
+/// Split a pattern given with `-e` into its pattern and style, if it is of the form `PATTERN=style`. If it is not, the
+/// whole argument is the pattern, which will be highlighted with the given default style. A style that names no
+/// foreground color takes the default style's.
+///
+/// Styles may not start with a color number, so that patterns such as `status=1` are not mistaken for a pattern and a
+/// style.
+fn split_pattern_style(pattern_arg: &str, default_style: Style) -> (&str, Style) {
+    pattern_arg
+        .rsplit_once('=')
+        .filter(|(_, style)| !style.trim_start().starts_with(|c: char| c.is_ascii_digit()))
+        .and_then(|(pattern, style)| Some((pattern, style.parse::<Style>().ok()?)))
+        .map_or((pattern_arg, default_style), |(pattern, mut style)| {
+            style.fg = style.fg.or(default_style.fg);
+            (pattern, style)
+        })
+}
+
+/// Parse a color, by its name or number.
+fn parse_color(color: &str) -> Result<AnsiValue, String> {
+    color.parse::<AnsiValue>().map_err(|err| err.to_string())
+}
+
+/// Make the options that decide which files within a directory are scanned
+fn make_walk_options(args: &ArgMatches) -> Result<WalkOptions, clap::Error> {
+    Ok(WalkOptions {
+        include_globs: glob_set_arg(args, GLOB_ARG_NAME)?,
+        type_globs: type_globs_arg(args)?,
+        exclude_globs: glob_set_arg(args, EXCLUDE_ARG_NAME)?,
+        respect_ignore_files: !args.is_present(NO_IGNORE_ARG_NAME),
+        hidden: args.is_present(HIDDEN_ARG_NAME),
+        max_filesize: parse_arg(args, MAX_FILESIZE_ARG_NAME, parse_file_size)?,
+    })
+}
+
+/// Get the globs for the file types given with `-t`, if any were, using the types added with `--type-add` too.
+fn type_globs_arg(args: &ArgMatches) -> Result<Option<GlobSet>, clap::Error> {
+    let Some(names) = args.values_of(TYPE_ARG_NAME) else {
+        return Ok(None);
+    };
+
+    let mut file_types = FileTypes::default();
+    for definition in args.values_of(TYPE_ADD_ARG_NAME).into_iter().flatten() {
+        file_types
+            .add(definition)
+            .map_err(|err| invalid_value(TYPE_ADD_ARG_NAME, &err.to_string()))?;
+    }
+
+    let names: Vec<&str> = names.collect();
+    file_types
+        .globs(&names)
+        .map(Some)
+        .map_err(|err: FileTypeError| invalid_value(TYPE_ARG_NAME, &err.to_string()))
+}
+
+/// Make the options that a single pattern is matched and highlighted with
+fn make_scan_options(args: &ArgMatches, theme: Theme) -> Result<ScanOptions, clap::Error> {
+    let max_count = parse_arg(args, MAX_COUNT_ARG_NAME, parse_max_count)?;
+    let stop_after = parse_arg(args, STOP_AFTER_ARG_NAME, parse_max_count)?;
+    let max_columns = parse_arg(args, MAX_COLUMNS_ARG_NAME, parse_max_columns)?;
+    let alternate_color = parse_arg(args, ALTERNATE_COLOR_ARG_NAME, parse_color)?;
+    let context = parse_arg(args, CONTEXT_ARG_NAME, parse_context_lines)?;
+    let before_context = parse_arg(args, BEFORE_CONTEXT_ARG_NAME, parse_context_lines)?.or(context);
+    let after_context = parse_arg(args, AFTER_CONTEXT_ARG_NAME, parse_context_lines)?.or(context);
+    let passthru = !args.is_present(NO_PASSTHRU_ARG_NAME)
+        && before_context.is_none()
+        && after_context.is_none();
+
+    let mut scan_options = ScanOptions::new();
+    scan_options
+        .case_insensitive(args.is_present(CASE_INSENSITIVE_ARG_NAME))
+        .fixed_strings(args.is_present(FIXED_STRINGS_ARG_NAME))
+        .invert(args.is_present(INVERT_ARG_NAME))
+        .multi_line(args.is_present(MULTILINE_ARG_NAME))
+        .passthru(passthru)
+        .capture_groups(args.is_present(COLOR_GROUPS_ARG_NAME))
+        .dim(args.value_of(DIM_ARG_NAME))
+        .line_number(args.is_present(LINE_NUMBER_ARG_NAME))
+        .byte_offset(args.is_present(BYTE_OFFSET_ARG_NAME))
+        .markers(args.is_present(MARKER_ARG_NAME))
+        .before_context(before_context.unwrap_or(0))
+        .after_context(after_context.unwrap_or(0))
+        .prefixed(args.is_present(PREFIXED_ARG_NAME))
+        .theme(theme)
+        .alternate_color(alternate_color)
+        .max_count(max_count)
+        .stop_after(stop_after)
+        .max_columns(max_columns)
+        .replace(args.value_of(REPLACE_ARG_NAME))
+        .crlf(args.is_present(CRLF_ARG_NAME));
+
+    let line_terminator = line_terminator_arg(args)?;
+    // Input separated by NUL bytes would always be detected as binary, so there's no point in checking it
+    scan_options
+        .line_terminator(line_terminator)
+        .quit_on_binary(!args.is_present(OK_IF_BINARY_ARG_NAME) && line_terminator != b'\0');
+
+    Ok(scan_options)
+}
+
+/// Get the byte that terminates each line of the input, as given by `--null-data` or `--line-terminator`
+fn line_terminator_arg(args: &ArgMatches) -> Result<u8, clap::Error> {
+    if args.is_present(NULL_DATA_ARG_NAME) {
+        return Ok(b'\0');
+    }
+
+    parse_arg(args, LINE_TERMINATOR_ARG_NAME, parse_line_terminator)
+        .map(|line_terminator| line_terminator.unwrap_or(b'\n'))
+}
+
+/// Parse a line terminator, which is either a single ASCII character, or an escape for one (e.g. `\f` or `\x1e`)
+fn parse_line_terminator(line_terminator: &str) -> Result<u8, String> {
+    let byte = match line_terminator.as_bytes() {
+        [byte] => *byte,
+        [b'\\', b'0'] => b'\0',
+        [b'\\', b't'] => b'\t',
+        [b'\\', b'n'] => b'\n',
+        [b'\\', b'r'] => b'\r',
+        [b'\\', b'f'] => b'\x0c',
+        [b'\\', b'\\'] => b'\\',
+        [b'\\', b'x', ..] => u8::from_str_radix(&line_terminator[2..], 16)
+            .map_err(|err| format!("{line_terminator} is not a valid escape: {err}"))?,
+        _ if line_terminator.chars().count() == 1 => {
+            return Err(format!("{line_terminator} is not an ASCII character"))
+        }
+        _ => return Err(format!("{line_terminator} is not a single character")),
+    };
+
+    if byte.is_ascii() {
+        Ok(byte)
+    } else {
+        Err(format!("{line_terminator} is not an ASCII character"))
+    }
+}
+
+/// Whether each line should be prefixed with the name of its file, as chosen with `-H` or `--no-filename`, whichever was
+/// given last. By default, lines are prefixed when more than one file is given, unless decorations or `--tail-prefix`
+/// already name each file.
+fn with_filename_arg(args: &ArgMatches, filenames: &[&OsStr]) -> bool {
+    if args.is_present(WITH_FILENAME_ARG_NAME) {
+        true
+    } else if args.is_present(NO_FILENAME_ARG_NAME) {
+        false
+    } else {
+        filenames.len() > 1
+            && !args.is_present(DECORATIONS_ARG_NAME)
+            && !args.is_present(TAIL_PREFIX_ARG_NAME)
+    }
+}
+
+/// Get the style of the gutter to decorate the output with, if decorations were requested with `--decorations`. The
+/// gutter is colored as the theme's line numbers, unless `--gutter-color` is given.
+fn decorations_arg(args: &ArgMatches, theme: &Theme) -> Result<Option<Gutter>, clap::Error> {
+    if !args.is_present(DECORATIONS_ARG_NAME) {
+        return Ok(None);
+    }
+
+    let default_gutter = Gutter::default();
+    let color = parse_arg(args, GUTTER_COLOR_ARG_NAME, parse_color)?;
+    let separator = parse_arg(args, GUTTER_SEPARATOR_ARG_NAME, parse_gutter_separator)?;
+    let padding = parse_arg(args, GUTTER_PADDING_ARG_NAME, parse_gutter_padding)?;
+
+    Ok(Some(Gutter {
+        color: color.unwrap_or(theme.line_number),
+        separator: separator.unwrap_or(default_gutter.separator),
+        padding: padding.unwrap_or(default_gutter.padding),
+    }))
+}
+
+/// Parse the character that separates the gutter from each line
+fn parse_gutter_separator(separator: &str) -> Result<char, String> {
+    let mut chars = separator.chars();
+    match (chars.next(), chars.next()) {
+        (Some(separator), None) => Ok(separator),
+        _ => Err(format!("{separator} is not a single character")),
+    }
+}
+
+/// Parse the number of spaces between each line number and the gutter's separator
+fn parse_gutter_padding(padding: &str) -> Result<usize, String> {
+    padding
+        .parse()
+        .map_err(|err| format!("{padding} is not a valid number of spaces: {err}"))
+}
+
+/// Make a set of the globs given to the argument with the given name, if any were.
+fn glob_set_arg(args: &ArgMatches, arg_name: &str) -> Result<Option<GlobSet>, clap::Error> {
+    let Some(globs) = parse_args(args, arg_name, |glob| {
+        Glob::new(glob).map_err(|err| err.to_string())
+    })?
+    else {
+        return Ok(None);
+    };
+
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(glob);
+    }
+
+    builder
+        .build()
+        .map(Some)
+        .map_err(|err| invalid_value(arg_name, &err.to_string()))
+}
+
+/// Parse the label of a known encoding.
+fn parse_encoding(label: &str) -> Result<&'static Encoding, String> {
+    encoding_for_label(label).ok_or_else(|| format!("{label} is not a known encoding"))
+}
+
+/// Find the encoding with the given label. Labels are as defined by the WHATWG Encoding Standard, though hyphens may
+/// also be added to them (e.g. latin-1, rather than latin1), as they often are elsewhere.
+fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.as_bytes())
+        .or_else(|| Encoding::for_label(label.replace('-', "").as_bytes()))
+}
+
+/// Parse a file size in bytes, which may be followed by a K, M, or G suffix to give it in kibibytes, mebibytes, or
+/// gibibytes.
+fn parse_file_size(size: &str) -> Result<u64, String> {
+    let (digits, multiplier) = match size.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&size[..i], 1 << 10),
+        Some((i, 'M' | 'm')) => (&size[..i], 1 << 20),
+        Some((i, 'G' | 'g')) => (&size[..i], 1 << 30),
+        _ => (size, 1),
+    };
+
+    digits
+        .parse::<u64>()
+        .map_err(|err| format!("{size} is not a valid file size: {err}"))?
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("{size} is too large a file size"))
+}
+
+/// Parse a number of matching lines
+fn parse_max_count(max_count: &str) -> Result<u64, String> {
+    max_count
+        .parse()
+        .map_err(|err| format!("{max_count} is not a valid count: {err}"))
+}
+
+/// Parse a number of lines to print as context
+fn parse_context_lines(num_lines: &str) -> Result<usize, String> {
+    num_lines
+        .parse()
+        .map_err(|err| format!("{num_lines} is not a valid number of lines: {err}"))
+}
+
+/// Parse a number of columns to truncate lines to
+fn parse_max_columns(max_columns: &str) -> Result<usize, String> {
+    match max_columns.parse::<usize>() {
+        Ok(0) => Err("lines cannot be truncated to zero columns".to_string()),
+        Ok(max_columns) => Ok(max_columns),
+        Err(err) => Err(format!(
+            "{max_columns} is not a valid number of columns: {err}"
+        )),
+    }
+}
+
+/// Make the theme that output should be styled with, applying any colors given with `--color-match` or `--bg`, any
+/// attributes given with `--style`, and the dimming of `--focus`, to the chosen theme. When only a background color is
+/// given, the text of matching lines keeps its usual color.
+fn make_theme(args: &ArgMatches) -> Result<Theme, clap::Error> {
+    let mut theme = parse_arg(args, THEME_ARG_NAME, |theme| {
+        theme.parse::<Theme>().map_err(|err| err.to_string())
+    })?
+    .unwrap_or(Theme::DEFAULT);
+
+    let match_color = parse_arg(args, COLOR_MATCH_ARG_NAME, parse_color)?;
+    let match_bg = parse_arg(args, BG_ARG_NAME, parse_color)?;
+    if match_bg.is_some() {
+        theme.match_fg = match_color;
+        theme.match_bg = match_bg;
+    } else if match_color.is_some() {
+        theme.match_fg = match_color;
+    }
+
+    let attributes = parse_args(args, STYLE_ARG_NAME, |attribute| {
+        attribute
+            .parse::<Attribute>()
+            .map_err(|err| err.to_string())
+    })?;
+    if let Some(attributes) = attributes {
+        theme.match_attributes = attributes.into_iter().collect::<Attributes>();
+    }
+
+    if args.is_present(FOCUS_ARG_NAME) {
+        theme.context = theme.context.or(Some(FOCUS_CONTEXT_COLOR));
+        theme.context_attributes = theme.context_attributes.with(Attribute::Dim);
+    }
+
+    Ok(theme)
+}
+
+fn make_pattern_case_insensitive(pattern: &str) -> String {
+    format!("(?i){pattern}")
+}
@@ -0,0 +1,306 @@
+use crate::print_warning;
+use serde::Serialize;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long matches are collected for after the first match of a batch, before the batch is sent.
+const BATCH_WINDOW: Duration = Duration::from_secs(1);
+
+/// The most matches that will be sent in a single request.
+const MAX_BATCH_LEN: usize = 100;
+
+/// How many times a request that failed is retried before its matches are given up on.
+const MAX_RETRIES: u32 = 3;
+
+/// How long to wait before retrying a request for the first time. This doubles with each retry.
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// How long to wait for the server to accept or respond to a request before treating it as failed.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `NotifyUrl` represents the `http://` URL that notifications are posted to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotifyUrl {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+/// `Notifier` posts matching lines to a [`NotifyUrl`] as JSON. Requests are made from a background thread, so that
+/// scanning is never held up by a slow server. Matches that arrive within [`BATCH_WINDOW`] of each other are sent
+/// together, and requests that fail are retried, with a warning if they never succeed.
+pub struct Notifier {
+    pattern: String,
+    sender: Sender<Notification>,
+    sending_thread: JoinHandle<()>,
+}
+
+/// `NotifyingWriter` receives the matching lines of a single file, as copied by a
+/// [`MatchTeePrinter`](hline::print::MatchTeePrinter), and notifies its [`Notifier`] of each of them.
+pub struct NotifyingWriter<'a> {
+    notifier: &'a Notifier,
+    file: String,
+    line: Vec<u8>,
+}
+
+/// `Notification` is the JSON object sent for each matching line
+#[derive(Serialize)]
+struct Notification {
+    line: String,
+    /// The time the line was matched, in seconds since the Unix epoch
+    timestamp: f64,
+    file: String,
+    pattern: String,
+}
+
+impl NotifyUrl {
+    /// Parse the given URL, which must be an `http://` URL. Other schemes are rejected, as there is no support for TLS.
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let Some(rest) = url.strip_prefix("http://") else {
+            return Err(format!(
+                "{url} is not an http:// URL; other schemes, such as https://, are not supported"
+            ));
+        };
+
+        let (authority, path) = match rest.find('/') {
+            Some(path_start) => rest.split_at(path_start),
+            None => (rest, "/"),
+        };
+
+        let (host, port) = match authority.rsplit_once(':') {
+            // The colons in an IPv6 address are enclosed in brackets, so a colon within them doesn't start the port
+            Some((host, port)) if !port.ends_with(']') => {
+                let port = port
+                    .parse()
+                    .map_err(|err| format!("{url} does not have a valid port: {err}"))?;
+                (host, port)
+            }
+            _ => (authority, 80),
+        };
+
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.is_empty() {
+            return Err(format!("{url} does not have a host"));
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
+
+impl Display for NotifyUrl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "http://[{}]:{}{}", self.host, self.port, self.path)
+        } else {
+            write!(f, "http://{}:{}{}", self.host, self.port, self.path)
+        }
+    }
+}
+
+impl Notifier {
+    /// Make a new `Notifier`, which will post matches of the given pattern to the given URL.
+    pub fn new(url: NotifyUrl, pattern: &str) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let sending_thread = thread::spawn(move || send_batches(&url, &receiver));
+
+        Self {
+            pattern: pattern.to_string(),
+            sender,
+            sending_thread,
+        }
+    }
+
+    /// Make a new [`NotifyingWriter`] for the matching lines of the file with the given name.
+    pub fn writer(&self, file: &str) -> NotifyingWriter<'_> {
+        NotifyingWriter {
+            notifier: self,
+            file: file.to_string(),
+            line: Vec::new(),
+        }
+    }
+
+    /// Wait for every match that has been found to be sent, or given up on.
+    pub fn finish(self) {
+        // The thread sends its last batch once every sender is gone
+        drop(self.sender);
+        // A panic within the thread has already been reported, and there is nothing else to do about it
+        let _ = self.sending_thread.join();
+    }
+
+    fn notify(&self, file: &str, line: &[u8]) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let notification = Notification {
+            line: String::from_utf8_lossy(line).into_owned(),
+            timestamp,
+            file: file.to_string(),
+            pattern: self.pattern.clone(),
+        };
+
+        // The thread only stops once every sender is gone, so this can't fail
+        let _ = self.sender.send(notification);
+    }
+}
+
+impl Write for NotifyingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            if byte == b'\n' {
+                let line = self.line.strip_suffix(b"\r").unwrap_or(&self.line);
+                self.notifier.notify(&self.file, line);
+                self.line.clear();
+            } else {
+                self.line.push(byte);
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Send each batch of notifications from the given receiver to the given URL, until every sender is gone.
+fn send_batches(url: &NotifyUrl, receiver: &Receiver<Notification>) {
+    while let Ok(first_notification) = receiver.recv() {
+        let mut batch = vec![first_notification];
+        let deadline = Instant::now() + BATCH_WINDOW;
+        while batch.len() < MAX_BATCH_LEN {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(timeout) {
+                Ok(notification) => batch.push(notification),
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        send_batch(url, &batch);
+    }
+}
+
+/// Post the given batch of notifications to the given URL as a JSON array, retrying if the request fails.
+fn send_batch(url: &NotifyUrl, batch: &[Notification]) {
+    // Serializing these types cannot fail
+    let body = serde_json::to_vec(batch).expect("failed to encode notifications as JSON");
+    let mut retry_delay = FIRST_RETRY_DELAY;
+    for attempt in 0..=MAX_RETRIES {
+        match post(url, &body) {
+            Ok(()) => return,
+            Err(err) if attempt == MAX_RETRIES => print_warning(&format!(
+                "giving up on notifying {url} of a batch of matches, after retrying {MAX_RETRIES} times: {err}"
+            )),
+            Err(_) => {
+                thread::sleep(retry_delay);
+                retry_delay *= 2;
+            }
+        }
+    }
+}
+
+/// Post the given JSON body to the given URL, failing unless the server responds with a 2xx status.
+fn post(url: &NotifyUrl, body: &[u8]) -> io::Result<()> {
+    let mut stream = connect(url)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let host = if url.host.contains(':') {
+        format!("[{}]", url.host)
+    } else {
+        url.host.clone()
+    };
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {host}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        url.path,
+        url.port,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(io::Error::other(format!(
+            "the server responded with {:?}",
+            status_line.trim_end()
+        ))),
+    }
+}
+
+/// Connect to the server of the given URL, trying each of the addresses its host resolves to in turn, and giving up
+/// on each after [`REQUEST_TIMEOUT`].
+fn connect(url: &NotifyUrl) -> io::Result<TcpStream> {
+    let mut last_err = None;
+    for addr in (url.host.as_str(), url.port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, REQUEST_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} did not resolve to any addresses", url.host),
+        )
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("http://example.com/hook", "example.com", 80, "/hook"; "host and path")]
+    #[test_case("http://example.com:8080/a/b", "example.com", 8080, "/a/b"; "port")]
+    #[test_case("http://example.com", "example.com", 80, "/"; "no path")]
+    #[test_case("http://example.com:8080", "example.com", 8080, "/"; "port and no path")]
+    #[test_case("http://[::1]/hook", "::1", 80, "/hook"; "ipv6 without port")]
+    #[test_case("http://[::1]:8080/hook", "::1", 8080, "/hook"; "ipv6 with port")]
+    #[test_case("http://[fe80::1]", "fe80::1", 80, "/"; "ipv6 with no path")]
+    fn test_parses_url(url: &str, host: &str, port: u16, path: &str) {
+        let expected = NotifyUrl {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        };
+
+        assert_eq!(Ok(expected), NotifyUrl::parse(url));
+    }
+
+    #[test_case("https://example.com/hook"; "https")]
+    #[test_case("example.com/hook"; "no scheme")]
+    #[test_case("http://example.com:http/hook"; "non-numeric port")]
+    #[test_case("http://example.com:99999/hook"; "port out of range")]
+    #[test_case("http://[::1]:/hook"; "empty port")]
+    #[test_case("http:///hook"; "no host")]
+    #[test_case("http://:8080/hook"; "port without host")]
+    fn test_rejects_invalid_url(url: &str) {
+        let res = NotifyUrl::parse(url);
+        assert!(res.is_err(), "parsed {url} as {res:?}");
+    }
+
+    #[test_case("http://example.com/hook", "http://example.com:80/hook"; "domain")]
+    #[test_case("http://[::1]:8080", "http://[::1]:8080/"; "ipv6")]
+    fn test_displays_url(url: &str, expected: &str) {
+        let url = NotifyUrl::parse(url).expect("failed to parse url");
+        assert_eq!(expected, url.to_string());
+    }
+}
@@ -0,0 +1,586 @@
+use super::args::{Args, Count, OutputFormat, PassedFile, TailHeaders};
+use super::notify::{Notifier, NotifyingWriter};
+use super::run_stats::{LineCountingReader, RunStats};
+use crate::{exit_for_broken_pipe, scan_failure, Failure, OpenedFile};
+use hline::options::ScanOptions;
+#[cfg(feature = "lua")]
+pub use hline::plugin::LuaPlugin as Plugin;
+use hline::print;
+use hline::print::{
+    BufferedPrinter, DecoratedPrinter, EscapingPrinter, FilenamePrinter, FlushPolicy,
+    MatchTeePrinter, PlainPrinter, PrefixedPrinter, Printer, ResettingPrinter, SqueezingPrinter,
+    StderrPrinter, TailHeaderPrinter, WrappingPrinter, WritePrinter,
+};
+use hline::rules::Rules;
+use hline::sink::ScanStats;
+use hline::JsonStats;
+use memmap2::Mmap;
+use std::cell::Cell;
+use std::env;
+use std::fs::File;
+use std::io;
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+
+/// The width decorations will span if the terminal's width is not known.
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// `Scan` represents everything needed to scan a single file
+pub struct Scan<'a> {
+    pub args: &'a Args,
+    pub file: &'a PassedFile,
+    pub matcher: &'a Matcher,
+    pub plugin: Option<&'a Plugin>,
+    pub matches_file: Option<&'a File>,
+    pub notifier: Option<&'a Notifier>,
+    pub run_stats: Option<&'a Arc<RunStats>>,
+    /// The statistics of every file scanned to JSON so far, for the summary written once they have all been scanned
+    pub json_stats: &'a Cell<JsonStats>,
+    /// The scan options given by the arguments, which name the file in any error from scanning it
+    options: ScanOptions,
+}
+
+/// `WatchedStdout` writes to stdout, noting whether it was found to be closed. Scans stop without failing when this
+/// happens, so this is the only way to learn of it when writing to stdout directly.
+#[derive(Default)]
+struct WatchedStdout {
+    closed: bool,
+}
+
+/// `Matcher` represents what lines are matched with, once any rules have been built or loaded
+pub enum Matcher {
+    /// A single pattern, matched as configured by the scan options
+    Pattern(String),
+    /// Rules, which decide how each line is matched and highlighted
+    Rules(Rules),
+}
+
+/// `Plugin` stands in for [`LuaPlugin`](hline::plugin::LuaPlugin) when built without the `lua` feature, in which case
+/// no plugin can ever be loaded.
+#[cfg(not(feature = "lua"))]
+pub enum Plugin {}
+
+/// `MatchesWriter` receives the matching lines copied by a [`MatchTeePrinter`], and passes them on to the matches file,
+/// the notifier, and the run stats, whichever were given. These are all handled by the one writer so that the printers
+/// it is wrapped in are only built once, rather than once for each combination of them.
+struct MatchesWriter<'a> {
+    matches_file: Option<&'a File>,
+    notifying_writer: Option<NotifyingWriter<'a>>,
+    run_stats: Option<&'a RunStats>,
+}
+
+impl Write for MatchesWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(matches_file) = &mut self.matches_file {
+            matches_file.write_all(buf)?;
+        }
+        if let Some(notifying_writer) = &mut self.notifying_writer {
+            notifying_writer.write_all(buf)?;
+        }
+        if let Some(run_stats) = self.run_stats {
+            run_stats.record_matched(buf);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(matches_file) = &mut self.matches_file {
+            matches_file.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Scan<'a> {
+    /// Make a new `Scan` of the given file, whose scan options name the file in any error from scanning it. If writing
+    /// JSON, the statistics of the scan are added to `json_stats`.
+    pub fn new(
+        args: &'a Args,
+        file: &'a PassedFile,
+        matcher: &'a Matcher,
+        json_stats: &'a Cell<JsonStats>,
+    ) -> Self {
+        let mut options = args.scan_options.clone();
+        options.input_name(Some(&file.to_string()));
+
+        Self {
+            args,
+            file,
+            matcher,
+            plugin: None,
+            matches_file: None,
+            notifier: None,
+            run_stats: None,
+            json_stats,
+            options,
+        }
+    }
+
+    /// Make a `Scan` of the given file that is otherwise the same as this one.
+    pub fn for_file(&self, file: &'a PassedFile) -> Self {
+        let mut options = self.args.scan_options.clone();
+        options.input_name(Some(&file.to_string()));
+
+        Self {
+            file,
+            options,
+            ..*self
+        }
+    }
+
+    /// Highlight lines with the given [`Plugin`], if one is given.
+    #[must_use]
+    pub fn plugin(mut self, plugin: Option<&'a Plugin>) -> Self {
+        self.plugin = plugin;
+        self
+    }
+
+    /// Copy every matching line to the given file, if one is given.
+    #[must_use]
+    pub fn matches_file(mut self, matches_file: Option<&'a File>) -> Self {
+        self.matches_file = matches_file;
+        self
+    }
+
+    /// Notify the given [`Notifier`] of every matching line, if one is given.
+    #[must_use]
+    pub fn notifier(mut self, notifier: Option<&'a Notifier>) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    /// Count every line seen and matched in the given [`RunStats`], if they are given.
+    #[must_use]
+    pub fn run_stats(mut self, run_stats: Option<&'a Arc<RunStats>>) -> Self {
+        self.run_stats = run_stats;
+        self
+    }
+
+    /// Make the error for a failure to print the results of this scan, naming the file that was scanned.
+    fn print_failure(&self, source: io::Error) -> hline::Error {
+        hline::Error::PrintFailure {
+            source,
+            position: None,
+        }
+        .in_input(&self.file.to_string())
+    }
+}
+
+impl WatchedStdout {
+    /// Note if the given result is a broken pipe, before handing it back.
+    fn record_result<T>(&mut self, res: io::Result<T>) -> io::Result<T> {
+        self.closed |= matches!(&res, Err(err) if err.kind() == io::ErrorKind::BrokenPipe);
+        res
+    }
+}
+
+impl Write for WatchedStdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let res = io::stdout().write(buf);
+        self.record_result(res)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let res = io::stdout().flush();
+        self.record_result(res)
+    }
+}
+
+/// Scan the given file as specified by the arguments
+pub fn scan_opened_file(opened_file: OpenedFile, scan: &Scan) -> Result<(), Failure> {
+    let args = scan.args;
+    // Lines must be read to be counted, so files that are counted are never scanned through a memory map
+    let opened_file = match scan.run_stats {
+        Some(run_stats) => OpenedFile::LineCounted(LineCountingReader::new(
+            Box::new(opened_file),
+            Arc::clone(run_stats),
+        )),
+        None => opened_file,
+    };
+
+    let scan_result = match (&args.format, scan.matcher) {
+        (OutputFormat::Csv, Matcher::Pattern(pattern)) => scan_to_csv(opened_file, scan, pattern),
+        (OutputFormat::Json, Matcher::Pattern(pattern)) => scan_to_json(opened_file, scan, pattern),
+        (OutputFormat::Vimgrep, Matcher::Pattern(pattern)) => {
+            scan_to_vimgrep(opened_file, scan, pattern)
+        }
+        // Only highlighted output can be given with rules, so every other case is highlighted
+        _ => scan_to_stdout(opened_file, scan),
+    };
+
+    scan_result.map_err(|err| scan_failure(&err))
+}
+
+/// Scan the given file, writing every line to stdout as a JSON object in the format of ripgrep's `--json` output,
+/// matching the given pattern as specified by the arguments. The statistics of the scan are added to the totals kept by
+/// the [`Scan`], so that the summary can be written once every file is scanned.
+fn scan_to_json(opened_file: OpenedFile, scan: &Scan, pattern: &str) -> Result<(), hline::Error> {
+    let mut stdout = WatchedStdout::default();
+    let scan_result = scan.options.scan_pattern_to_json(
+        opened_file,
+        pattern,
+        &scan.file.to_string(),
+        &mut stdout,
+    );
+    if stdout.closed {
+        exit_for_broken_pipe();
+    }
+
+    scan.json_stats.set(scan.json_stats.get() + scan_result?);
+    Ok(())
+}
+
+/// Whether each file is scanned on its own when more than one may be, in which case output about each scan should name
+/// the file it was for.
+fn names_each_file(args: &Args) -> bool {
+    match &args.file {
+        PassedFile::Path(path) => args.recursive || path.is_dir(),
+        PassedFile::Concatenated(_) => args.reset_line_numbers || args.with_filename,
+        PassedFile::Stdin => args.recursive,
+    }
+}
+
+/// Get what should follow a printed filename: a NUL byte if `--null` was given, or `terminator` otherwise.
+pub fn filename_terminator(args: &Args, terminator: &'static str) -> &'static str {
+    if args.null {
+        "\0"
+    } else {
+        terminator
+    }
+}
+
+/// Print the number of matching lines in the given file, naming the file if there may be more than one.
+fn print_count<P: Printer>(
+    printer: P,
+    scan: &Scan,
+    matched_lines: u64,
+) -> Result<(), hline::Error> {
+    let print_result = if names_each_file(scan.args) {
+        printer.print(format!(
+            "{}{}{matched_lines}\n",
+            scan.file,
+            filename_terminator(scan.args, ":")
+        ))
+    } else {
+        printer.print(format!("{matched_lines}\n"))
+    };
+
+    match print_result {
+        Err(print::Error::Other(err)) => Err(scan.print_failure(err)),
+        // A broken pipe means there's nowhere left to print to, which is not a failure
+        _ => Ok(()),
+    }
+}
+
+/// Scan the given file to stdout, styling the output as specified by the arguments, and reporting the number of
+/// matching lines and the statistics of the scan if they were asked for.
+fn scan_to_stdout(opened_file: OpenedFile, scan: &Scan) -> Result<(), hline::Error> {
+    let args = scan.args;
+    let stdout = BufferedPrinter::new(io::stdout().lock(), stdout_flush_policy(args));
+    let scan_result = match args.count {
+        Count::Only => scan_matching_to_printer(opened_file, scan, WritePrinter::new(io::sink())),
+        // Colors must not be left on in the terminal if the scan fails partway through a line
+        Count::AfterOutput | Count::Off if args.color && args.wrap => {
+            let printer = WrappingPrinter::new(ResettingPrinter::new(&stdout), terminal_width());
+            scan_highlighted(opened_file, scan, printer)
+        }
+        Count::AfterOutput | Count::Off if args.color => {
+            scan_highlighted(opened_file, scan, ResettingPrinter::new(&stdout))
+        }
+        Count::AfterOutput | Count::Off => {
+            scan_highlighted(opened_file, scan, PlainPrinter::new(&stdout))
+        }
+    };
+
+    scan_result.and_then(|stats| {
+        if let Count::Only = args.count {
+            print_count(&stdout, scan, stats.lines_matched)?;
+        }
+
+        if let Err(print::Error::Other(err)) = stdout.flush() {
+            return Err(scan.print_failure(err));
+        }
+
+        // The scan stops early if stdout is closed, in which case there's nowhere left to print to
+        if stdout.broken_pipe() {
+            exit_for_broken_pipe();
+        }
+
+        if let Count::AfterOutput = args.count {
+            print_count(StderrPrinter::new(), scan, stats.lines_matched)?;
+        }
+
+        if args.stats {
+            print_stats(StderrPrinter::new(), scan, &stats)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Print the statistics of the scan of the given file, naming the file if there may be more than one.
+fn print_stats<P: Printer>(printer: P, scan: &Scan, stats: &ScanStats) -> Result<(), hline::Error> {
+    let heading = if names_each_file(scan.args) {
+        format!("{}{}", scan.file, filename_terminator(scan.args, ":\n"))
+    } else {
+        String::new()
+    };
+
+    let print_result = printer.print(format!(
+        "{heading}lines scanned: {}\nlines matched: {}\nbytes processed: {}\nelapsed: {:.3}s\n",
+        stats.lines_scanned,
+        stats.lines_matched,
+        stats.bytes_processed,
+        stats.elapsed.as_secs_f64(),
+    ));
+
+    match print_result {
+        Err(print::Error::Other(err)) => Err(scan.print_failure(err)),
+        // A broken pipe means there's nowhere left to print to, which is not a failure
+        _ => Ok(()),
+    }
+}
+
+/// Scan the given file to the given printer for stdout, styling the output as specified by the arguments. The
+/// statistics of the scan are returned.
+fn scan_highlighted<P: Printer>(
+    opened_file: OpenedFile,
+    scan: &Scan,
+    stdout: P,
+) -> Result<ScanStats, hline::Error> {
+    let args = scan.args;
+    if args.with_filename {
+        let separator = if args.null { b'\0' } else { b':' };
+        let printer =
+            FilenamePrinter::with_color(stdout, &scan.file.to_string(), args.theme.header)
+                .separator(separator);
+        return scan_styling_prefixes(opened_file, scan, printer);
+    }
+
+    scan_styling_prefixes(opened_file, scan, stdout)
+}
+
+/// Scan the given file to the given printer, styling any prefixes in the input as specified by the arguments. The
+/// statistics of the scan are returned.
+fn scan_styling_prefixes<P: Printer>(
+    opened_file: OpenedFile,
+    scan: &Scan,
+    printer: P,
+) -> Result<ScanStats, hline::Error> {
+    let args = scan.args;
+    match args.tail_headers {
+        // --prefixed and the tail flags are mutually exclusive
+        TailHeaders::Ignore if args.prefixed => {
+            scan_decorated(opened_file, scan, PrefixedPrinter::new(printer))
+        }
+        TailHeaders::Ignore => scan_decorated(opened_file, scan, printer),
+        TailHeaders::Style | TailHeaders::StyleAndPrefix => {
+            let prefix_lines = matches!(args.tail_headers, TailHeaders::StyleAndPrefix);
+            let printer = TailHeaderPrinter::with_color(printer, prefix_lines, args.theme.header);
+            scan_decorated(opened_file, scan, printer)
+        }
+    }
+}
+
+/// Scan the given file to the given printer, adding decorations around the output if they were requested. The
+/// statistics of the scan are returned.
+fn scan_decorated<P: Printer>(
+    opened_file: OpenedFile,
+    scan: &Scan,
+    printer: P,
+) -> Result<ScanStats, hline::Error> {
+    let Some(gutter) = scan.args.decorations else {
+        return scan_teeing_matches(opened_file, scan, printer);
+    };
+
+    let printer =
+        DecoratedPrinter::with_gutter(printer, &scan.file.to_string(), terminal_width(), gutter);
+    let stats = scan_teeing_matches(opened_file, scan, &printer)?;
+    match printer.finish() {
+        Err(print::Error::Other(err)) => Err(scan.print_failure(err)),
+        // A broken pipe means there's nowhere left to print to, which is not a failure
+        _ => Ok(stats),
+    }
+}
+
+/// Get the policy for flushing output to stdout. Output is flushed after every line when it can be seen as it is
+/// printed, such as in a terminal or when following a file, and is otherwise only flushed once the buffer is full.
+fn stdout_flush_policy(args: &Args) -> FlushPolicy {
+    if args.follow.is_some() || io::stdout().is_terminal() {
+        FlushPolicy::Line
+    } else {
+        FlushPolicy::WhenFull
+    }
+}
+
+/// Get the width of the terminal, as given by `$COLUMNS`.
+fn terminal_width() -> usize {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(DEFAULT_TERMINAL_WIDTH)
+}
+
+/// Scan the given file to the given printer, copying matching lines to the matches file, sending them to the notifier,
+/// and counting them in the run stats, if any of those are given.
+fn scan_teeing_matches<P: Printer>(
+    opened_file: OpenedFile,
+    scan: &Scan,
+    printer: P,
+) -> Result<ScanStats, hline::Error> {
+    if scan.matches_file.is_none() && scan.notifier.is_none() && scan.run_stats.is_none() {
+        return scan_to_printer(opened_file, scan, printer);
+    }
+
+    let matches_writer = MatchesWriter {
+        matches_file: scan.matches_file,
+        notifying_writer: scan
+            .notifier
+            .map(|notifier| notifier.writer(&scan.file.to_string())),
+        run_stats: scan.run_stats.map(Arc::as_ref),
+    };
+    scan_to_printer(
+        opened_file,
+        scan,
+        MatchTeePrinter::new(printer, matches_writer),
+    )
+}
+
+/// Scan the given file to the given printer, collapsing runs of blank lines if asked to
+fn scan_to_printer<P: Printer>(
+    opened_file: OpenedFile,
+    scan: &Scan,
+    printer: P,
+) -> Result<ScanStats, hline::Error> {
+    if scan.args.squeeze_blank {
+        scan_escaping_to_printer(opened_file, scan, SqueezingPrinter::new(printer))
+    } else {
+        scan_escaping_to_printer(opened_file, scan, printer)
+    }
+}
+
+/// Scan the given file to the given printer, escaping control characters if they were asked to be shown, or if the file
+/// may have binary data
+fn scan_escaping_to_printer<P: Printer>(
+    opened_file: OpenedFile,
+    scan: &Scan,
+    printer: P,
+) -> Result<ScanStats, hline::Error> {
+    if scan.args.show_control_chars {
+        let printer = EscapingPrinter::with_caret_notation(printer);
+        scan_matching_to_printer(opened_file, scan, printer)
+    } else if scan.args.ok_if_binary_file {
+        scan_matching_to_printer(opened_file, scan, EscapingPrinter::new(printer))
+    } else {
+        scan_matching_to_printer(opened_file, scan, printer)
+    }
+}
+
+/// Scan the given file to the given printer, using the scan's rules, or its pattern with the matching behavior specified
+/// by the arguments, and passing each line through the given plugin, if there is one. The statistics of the scan are
+/// returned.
+fn scan_matching_to_printer<P: Printer>(
+    opened_file: OpenedFile,
+    scan: &Scan,
+    printer: P,
+) -> Result<ScanStats, hline::Error> {
+    let args = scan.args;
+    let pattern = match scan.matcher {
+        Matcher::Pattern(pattern) => pattern,
+        Matcher::Rules(rules) => {
+            return scan
+                .options
+                .scan_rules_to_printer(opened_file, rules, printer)
+        }
+    };
+
+    if let Some(plugin) = scan.plugin {
+        return scan_with_plugin(opened_file, scan, pattern, plugin, printer);
+    }
+
+    if let (true, OpenedFile::File(file)) = (args.mmap, &opened_file) {
+        if let Some(mmap) = map_file(file) {
+            return scan.options.scan_slice_to_printer(&mmap, pattern, printer);
+        }
+    }
+
+    scan.options
+        .scan_pattern_to_printer(opened_file, pattern, printer)
+}
+
+/// Scan the given file to the given printer, passing each line through the given plugin, matching the given pattern as
+/// specified by the arguments
+#[cfg(feature = "lua")]
+fn scan_with_plugin<P: Printer>(
+    opened_file: OpenedFile,
+    scan: &Scan,
+    pattern: &str,
+    plugin: &Plugin,
+    printer: P,
+) -> Result<ScanStats, hline::Error> {
+    scan.options
+        .scan_pattern_with_plugin_to_printer(opened_file, pattern, plugin, printer)
+}
+
+#[cfg(not(feature = "lua"))]
+fn scan_with_plugin<P: Printer>(
+    _opened_file: OpenedFile,
+    _scan: &Scan,
+    _pattern: &str,
+    plugin: &Plugin,
+    _printer: P,
+) -> Result<ScanStats, hline::Error> {
+    match *plugin {}
+}
+
+/// Map the given file into memory, so that it can be scanned without reading it. If it can't be mapped (e.g. because
+/// it is not a regular file), `None` is returned, and it should be read instead.
+fn map_file(file: &File) -> Option<Mmap> {
+    if !file.metadata().is_ok_and(|metadata| metadata.is_file()) {
+        return None;
+    }
+
+    // SAFETY: The map is only valid so long as the file is not truncated while it is being scanned, which can't be
+    // prevented. This is why --no-mmap is available.
+    unsafe { Mmap::map(file) }.ok()
+}
+
+/// Scan the given file, writing rows for matching lines to stdout as CSV, matching the given pattern as specified by the
+/// arguments. The header row must be written separately.
+fn scan_to_csv(opened_file: OpenedFile, scan: &Scan, pattern: &str) -> Result<(), hline::Error> {
+    let mut stdout = WatchedStdout::default();
+    let scan_result = scan.options.scan_pattern_to_csv_rows(
+        opened_file,
+        pattern,
+        &scan.file.to_string(),
+        &mut stdout,
+    );
+    if stdout.closed {
+        exit_for_broken_pipe();
+    }
+
+    scan_result
+}
+
+/// Scan the given file, writing each match to stdout as `file:line:column:text`, matching the given pattern as
+/// specified by the arguments.
+fn scan_to_vimgrep(
+    opened_file: OpenedFile,
+    scan: &Scan,
+    pattern: &str,
+) -> Result<(), hline::Error> {
+    let mut stdout = WatchedStdout::default();
+    let scan_result = scan.options.scan_pattern_to_vimgrep(
+        opened_file,
+        pattern,
+        &scan.file.to_string(),
+        &mut stdout,
+    );
+    if stdout.closed {
+        exit_for_broken_pipe();
+    }
+
+    scan_result
+}
@@ -0,0 +1,149 @@
+use hline::print::{Printer, StderrPrinter};
+use std::io;
+use std::io::Read;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// `RunStats` counts the lines seen and matched while `hl` runs, so that they can be reported while a long-running scan
+/// (such as of the output of `tail -f`) is still in progress.
+pub struct RunStats {
+    started_at: Instant,
+    lines_seen: AtomicU64,
+    lines_matched: AtomicU64,
+}
+
+/// `LineCountingReader` wraps a [`Read`], counting each line read from it as seen in its [`RunStats`]
+pub struct LineCountingReader<R: Read> {
+    read: R,
+    stats: Arc<RunStats>,
+}
+
+impl RunStats {
+    /// Start counting, and timing how long `hl` has been running for from now.
+    pub fn start() -> Self {
+        Self {
+            started_at: Instant::now(),
+            lines_seen: AtomicU64::new(0),
+            lines_matched: AtomicU64::new(0),
+        }
+    }
+
+    /// Count the lines in the given bytes as matched.
+    pub fn record_matched(&self, bytes: &[u8]) {
+        self.lines_matched
+            .fetch_add(count_lines(bytes), Ordering::Relaxed);
+    }
+
+    /// Print the counts so far, and how long `hl` has been running for, to stderr.
+    fn print(&self) {
+        let report = format!(
+            "lines seen: {}\nlines matched: {}\nuptime: {:.3}s\n",
+            self.lines_seen.load(Ordering::Relaxed),
+            self.lines_matched.load(Ordering::Relaxed),
+            self.started_at.elapsed().as_secs_f64(),
+        );
+
+        // There is nowhere left to report a failure to if stderr has gone away
+        let _ = StderrPrinter::new().print(report);
+    }
+}
+
+impl<R: Read> LineCountingReader<R> {
+    /// Make a new `LineCountingReader`, counting the lines read from the given [`Read`] in the given stats.
+    pub fn new(read: R, stats: Arc<RunStats>) -> Self {
+        Self { read, stats }
+    }
+}
+
+impl<R: Read> Read for LineCountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes_read = self.read.read(buf)?;
+        self.stats
+            .lines_seen
+            .fetch_add(count_lines(&buf[..bytes_read]), Ordering::Relaxed);
+
+        Ok(bytes_read)
+    }
+}
+
+/// Print the given stats to stderr each time the program receives SIGUSR1, from a background thread. The signal is
+/// relayed to that thread through a pipe, as next to nothing can be done safely within a signal handler itself.
+#[cfg(unix)]
+pub fn report_on_signal(stats: Arc<RunStats>) -> io::Result<()> {
+    use std::fs::File;
+    use std::os::fd::FromRawFd;
+    use std::sync::atomic::AtomicI32;
+    use std::thread;
+
+    /// The end of the pipe that the signal handler writes to
+    static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+    extern "C" fn handle_signal(_: libc::c_int) {
+        let byte = 0_u8;
+        // SAFETY: write is async-signal-safe, and the pipe is never closed. If the pipe is full, a report is already
+        // pending, so there's nothing lost by this failing.
+        unsafe {
+            libc::write(
+                SIGNAL_PIPE.load(Ordering::Relaxed),
+                std::ptr::addr_of!(byte).cast(),
+                1,
+            );
+        }
+    }
+
+    let mut pipe_fds = [0; 2];
+    // SAFETY: pipe_fds has room for the two descriptors that pipe writes
+    if unsafe { libc::pipe(pipe_fds.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let [read_fd, write_fd] = pipe_fds;
+    // SAFETY: write_fd was just opened by pipe. It must not block, or the handler could block whatever it interrupted.
+    if unsafe { libc::fcntl(write_fd, libc::F_SETFL, libc::O_NONBLOCK) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    SIGNAL_PIPE.store(write_fd, Ordering::Relaxed);
+    // SAFETY: read_fd was just opened by pipe, and nothing else owns it
+    let mut signals = unsafe { File::from_raw_fd(read_fd) };
+    thread::spawn(move || {
+        let mut byte = [0];
+        while signals.read_exact(&mut byte).is_ok() {
+            stats.print();
+        }
+    });
+
+    // SAFETY: the handler only does what is safe within a signal handler. SA_RESTART keeps the signal from interrupting
+    // reads of the scanned file.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handle_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        if libc::sigaction(
+            libc::SIGUSR1,
+            std::ptr::addr_of!(action),
+            std::ptr::null_mut(),
+        ) != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+/// Other platforms have no SIGUSR1 to report on.
+#[cfg(not(unix))]
+pub fn report_on_signal(_stats: Arc<RunStats>) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "SIGUSR1 is only available on Unix",
+    ))
+}
+
+/// Count the newlines in the given bytes.
+fn count_lines(bytes: &[u8]) -> u64 {
+    // Splitting always gives one more part than there are separators
+    bytes.split(|&byte| byte == b'\n').count() as u64 - 1
+}
@@ -0,0 +1,111 @@
+use super::app::{
+    CASE_INSENSITIVE_ARG_NAME, COUNT_ARG_NAME, COUNT_ONLY_ARG_NAME, CSV_FORMAT, FOLLOW_ARG_NAME,
+    JSON_FORMAT, LESSOPEN_ARG_NAME, RECURSIVE_ARG_NAME, REGEXP_ARG_NAME, RULES_ARG_NAME,
+    STATS_ARG_NAME, STDIN_TTY_ARG_NAME, VIMGREP_FORMAT, WATCH_ARG_NAME,
+};
+use super::args::format_arg;
+use clap::{ArgMatches, ErrorKind};
+use std::ffi::OsStr;
+use std::io;
+use std::io::IsTerminal;
+
+/// Reject the combinations of arguments that clap can't reject itself.
+pub fn validate_args(args: &ArgMatches) -> Result<(), clap::Error> {
+    validate_pattern_args(args)?;
+    validate_count_args(args)
+}
+
+/// Reject the arguments that cannot be used alongside `--rules` or `-e`. clap can't express the conflicts with
+/// `--format` itself, as they depend on the value it is given.
+fn validate_pattern_args(args: &ArgMatches) -> Result<(), clap::Error> {
+    let flag = if args.is_present(RULES_ARG_NAME) {
+        "--rules"
+    } else if args.is_present(REGEXP_ARG_NAME) {
+        "-e"
+    } else {
+        return Ok(());
+    };
+
+    if flag == "--rules" && args.is_present(CASE_INSENSITIVE_ARG_NAME) {
+        return Err(clap::Error::with_description(
+            "--ignore-case cannot be used with --rules",
+            ErrorKind::ArgumentConflict,
+        ));
+    }
+
+    validate_highlight_format(args, flag)
+}
+
+/// Reject reading from stdin when it is a terminal and `--stdin-tty` was not given, as hline would otherwise appear
+/// to hang while it waits for input that was most likely never meant to be typed.
+pub fn validate_stdin(args: &ArgMatches, filenames: &[&OsStr]) -> Result<(), clap::Error> {
+    if !filenames.is_empty() || args.is_present(STDIN_TTY_ARG_NAME) || !io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    Err(clap::Error::with_description(
+        concat!(
+            "No file was given, and stdin is a terminal. Give a file to read, pipe input into hline, or pass ",
+            "--stdin-tty to type the input in."
+        ),
+        ErrorKind::MissingRequiredArgument,
+    ))
+}
+
+/// Reject the arguments that cannot be used alongside `--count`, `--count-only`, or `--stats`. clap can't express the
+/// conflicts with `--format` itself, as they depend on the value it is given.
+fn validate_count_args(args: &ArgMatches) -> Result<(), clap::Error> {
+    let flag = if args.is_present(COUNT_ONLY_ARG_NAME) {
+        "--count-only"
+    } else if args.is_present(COUNT_ARG_NAME) {
+        "--count"
+    } else if args.is_present(STATS_ARG_NAME) {
+        "--stats"
+    } else {
+        return Ok(());
+    };
+
+    validate_highlight_format(args, flag)
+}
+
+/// Reject any `--format` other than highlight, as the given flag can only be used when highlighting.
+fn validate_highlight_format(args: &ArgMatches, flag: &str) -> Result<(), clap::Error> {
+    match format_arg(args) {
+        Some(format @ (CSV_FORMAT | JSON_FORMAT | VIMGREP_FORMAT)) => {
+            let msg = format!("--format {format} cannot be used with {flag}");
+            Err(clap::Error::with_description(
+                &msg,
+                ErrorKind::ArgumentConflict,
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Reject the arguments that cannot be used with more than one file. clap can't express these itself, as it can't tell
+/// the pattern apart from the filenames.
+pub fn validate_files(args: &ArgMatches, filenames: &[&OsStr]) -> Result<(), clap::Error> {
+    if filenames.len() < 2 {
+        return Ok(());
+    }
+
+    let conflicting_flags = [
+        (FOLLOW_ARG_NAME, "--follow"),
+        (WATCH_ARG_NAME, "--watch"),
+        (RECURSIVE_ARG_NAME, "--recursive"),
+        (LESSOPEN_ARG_NAME, "--lessopen"),
+    ];
+    let conflict = conflicting_flags
+        .iter()
+        .find(|(arg_name, _)| args.is_present(arg_name));
+    match conflict {
+        Some((_, flag)) => {
+            let msg = format!("{flag} cannot be used with more than one file");
+            Err(clap::Error::with_description(
+                &msg,
+                ErrorKind::ArgumentConflict,
+            ))
+        }
+        None => Ok(()),
+    }
+}
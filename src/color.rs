@@ -0,0 +1,186 @@
+//! `color` provides the terminal colors used to highlight output, as ANSI escape sequences.
+//!
+//! These are written directly as escape sequences, so they are usable on any platform whose terminal understands
+//! them, including the Windows console once virtual terminal processing is enabled.
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The names of the colors that can be parsed into an [`AnsiValue`], in the order of their values.
+const COLOR_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "light-black",
+    "light-red",
+    "light-green",
+    "light-yellow",
+    "light-blue",
+    "light-magenta",
+    "light-cyan",
+    "light-white",
+];
+
+/// `ParseColorError` is returned when a string does not name a valid color.
+#[derive(Error, Debug)]
+#[error("\"{0}\" is not a valid color; expected a color name (e.g. \"red\" or \"light-red\") or a number from 0-255")]
+pub struct ParseColorError(String);
+
+/// A terminal color.
+pub trait Color: Debug {
+    /// Write the foreground version of this color.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the formatter fails.
+    fn write_fg(&self, f: &mut Formatter<'_>) -> fmt::Result;
+
+    /// Write the background version of this color.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the formatter fails.
+    fn write_bg(&self, f: &mut Formatter<'_>) -> fmt::Result;
+}
+
+impl<C: Color + ?Sized> Color for &C {
+    fn write_fg(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        (*self).write_fg(f)
+    }
+
+    fn write_bg(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        (*self).write_bg(f)
+    }
+}
+
+macro_rules! ansi_color {
+    ($doc:expr, $name:ident, $value:expr) => {
+        #[doc = $doc]
+        #[derive(Copy, Clone, Debug)]
+        pub struct $name;
+
+        impl Color for $name {
+            fn write_fg(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str(concat!("\x1b[38;5;", $value, "m"))
+            }
+
+            fn write_bg(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str(concat!("\x1b[48;5;", $value, "m"))
+            }
+        }
+    };
+}
+
+ansi_color!("Black.", Black, "0");
+ansi_color!("Red.", Red, "1");
+ansi_color!("Green.", Green, "2");
+ansi_color!("Yellow.", Yellow, "3");
+ansi_color!("Blue.", Blue, "4");
+ansi_color!("Magenta.", Magenta, "5");
+ansi_color!("Cyan.", Cyan, "6");
+ansi_color!("White.", White, "7");
+ansi_color!("High-intensity light black.", LightBlack, "8");
+ansi_color!("High-intensity light red.", LightRed, "9");
+ansi_color!("High-intensity light green.", LightGreen, "10");
+ansi_color!("High-intensity light yellow.", LightYellow, "11");
+ansi_color!("High-intensity light blue.", LightBlue, "12");
+ansi_color!("High-intensity light magenta.", LightMagenta, "13");
+ansi_color!("High-intensity light cyan.", LightCyan, "14");
+ansi_color!("High-intensity light white.", LightWhite, "15");
+
+/// An arbitrary color from the terminal's 256 color palette.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AnsiValue(pub u8);
+
+impl FromStr for AnsiValue {
+    type Err = ParseColorError;
+
+    /// Parse a color from either its name, such as `red` or `light-red`, or its number in the terminal's palette.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase();
+        if let Some(idx) = COLOR_NAMES.iter().position(|&name| name == normalized) {
+            // There are only 16 names, so this can never truncate
+            #[allow(clippy::cast_possible_truncation)]
+            return Ok(AnsiValue(idx as u8));
+        }
+
+        normalized
+            .parse()
+            .map(AnsiValue)
+            .map_err(|_| ParseColorError(s.to_string()))
+    }
+}
+
+impl Color for AnsiValue {
+    fn write_fg(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b[38;5;{}m", self.0)
+    }
+
+    fn write_bg(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b[48;5;{}m", self.0)
+    }
+}
+
+/// The terminal's default color.
+#[derive(Copy, Clone, Debug)]
+pub struct Reset;
+
+impl Color for Reset {
+    fn write_fg(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[39m")
+    }
+
+    fn write_bg(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[49m")
+    }
+}
+
+/// `Fg` sets the foreground to the given color when displayed.
+#[derive(Copy, Clone, Debug)]
+pub struct Fg<C: Color>(pub C);
+
+impl<C: Color> Display for Fg<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.write_fg(f)
+    }
+}
+
+/// `Bg` sets the background to the given color when displayed.
+#[derive(Copy, Clone, Debug)]
+pub struct Bg<C: Color>(pub C);
+
+impl<C: Color> Display for Bg<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.write_bg(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(&Fg(LightRed), "\x1b[38;5;9m"; "named foreground")]
+    #[test_case(&Bg(Magenta), "\x1b[48;5;5m"; "named background")]
+    #[test_case(&Fg(AnsiValue(208)), "\x1b[38;5;208m"; "palette foreground")]
+    #[test_case(&Fg(Reset), "\x1b[39m"; "reset foreground")]
+    #[test_case(&Bg(Reset), "\x1b[49m"; "reset background")]
+    fn test_escape_sequences(color: &dyn Display, expected: &str) {
+        assert_eq!(expected, color.to_string());
+    }
+
+    #[test_case("red", Some(1); "name")]
+    #[test_case("Light-Red", Some(9); "name with capitals")]
+    #[test_case("208", Some(208); "number")]
+    #[test_case("256", None; "number out of range")]
+    #[test_case("reddish", None; "unknown name")]
+    fn test_parse_color(s: &str, expected: Option<u8>) {
+        assert_eq!(expected, s.parse::<AnsiValue>().ok().map(|color| color.0));
+    }
+}
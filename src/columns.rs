@@ -0,0 +1,197 @@
+//! `columns` measures how text is laid out in the columns of a terminal, so that output can be fitted to its width.
+use crate::ansi;
+use std::borrow::Cow;
+use std::ops::Range;
+use unicode_width::UnicodeWidthChar;
+
+/// The columns that tab stops are placed at multiples of.
+const TAB_WIDTH: usize = 8;
+
+/// The marker for text removed by [`truncate`], which takes up a single column.
+const ELLIPSIS: &str = "…";
+
+/// `Unit` is a piece of text that is displayed as a whole, such as a character or an escape sequence.
+pub(crate) struct Unit {
+    /// The range of bytes of the text that make up this unit.
+    pub(crate) bytes: Range<usize>,
+    advance: Advance,
+}
+
+/// `Advance` is how displaying a [`Unit`] moves the cursor.
+enum Advance {
+    /// Move the given number of columns to the right, which is zero for escape sequences.
+    Columns(usize),
+    /// Move to the next tab stop.
+    Tab,
+    /// Move back to the start of the line.
+    LineStart,
+}
+
+impl Unit {
+    /// Get the column the cursor is left in after this unit is displayed at the given column.
+    pub(crate) fn next_column(&self, column: usize) -> usize {
+        match self.advance {
+            Advance::Columns(width) => column + width,
+            Advance::Tab => (column / TAB_WIDTH + 1) * TAB_WIDTH,
+            Advance::LineStart => 0,
+        }
+    }
+
+    /// Whether this unit takes up any columns, such that it could be moved onto the next row.
+    pub(crate) fn is_visible(&self) -> bool {
+        match self.advance {
+            Advance::Columns(width) => width > 0,
+            Advance::Tab => true,
+            Advance::LineStart => false,
+        }
+    }
+}
+
+/// Split the given text into the [`Unit`]s it is displayed as. SGR escape sequences (i.e. those that set colors) take
+/// up no columns, and each byte that is not valid UTF-8 takes up one, as it is usually displayed as a replacement
+/// character.
+pub(crate) fn units(text: &[u8]) -> Vec<Unit> {
+    let mut units = Vec::with_capacity(text.len());
+    let mut pos = 0;
+    while pos < text.len() {
+        let sequence_len = ansi::sgr_sequence_len(&text[pos..]);
+        let (len, advance) = match text[pos] {
+            _ if sequence_len > 0 => (sequence_len, Advance::Columns(0)),
+            b'\n' | b'\r' => (1, Advance::LineStart),
+            b'\t' => (1, Advance::Tab),
+            _ => match decode_char(&text[pos..]) {
+                Some(c) => (c.len_utf8(), Advance::Columns(c.width().unwrap_or(0))),
+                None => (1, Advance::Columns(1)),
+            },
+        };
+
+        units.push(Unit {
+            bytes: pos..pos + len,
+            advance,
+        });
+        pos += len;
+    }
+
+    units
+}
+
+/// Truncate the given text, which must be a single line, so that it takes up no more than `max_columns` columns,
+/// marking the text that was removed with an ellipsis. If `keep` is given, the text is truncated around that range of
+/// bytes (e.g. a match) so that it stays visible, as far as it fits; otherwise the end of the text is removed. Escape
+/// sequences are always kept, so that colors are applied and undone as they would have been.
+pub(crate) fn truncate(
+    text: &[u8],
+    max_columns: usize,
+    keep: Option<Range<usize>>,
+) -> Cow<'_, [u8]> {
+    let units = units(text);
+    let mut unit_columns = Vec::with_capacity(units.len());
+    let mut column = 0;
+    let mut width = 0;
+    for unit in &units {
+        let next_column = unit.next_column(column);
+        unit_columns.push(column..next_column);
+        column = next_column;
+        width = width.max(column);
+    }
+
+    if width <= max_columns {
+        return Cow::Borrowed(text);
+    }
+
+    let column_of = |pos: usize| {
+        units
+            .iter()
+            .zip(&unit_columns)
+            .find(|(unit, _)| unit.bytes.end > pos)
+            .map_or(width, |(_, columns)| columns.start)
+    };
+
+    // The window of columns that is kept, leaving room for an ellipsis on each side that text is removed from. When
+    // the range to keep would be cut off, the window is moved along to it, removing text from the start too.
+    let mut window = 0..max_columns.saturating_sub(1);
+    let keep = keep
+        .map(|keep| column_of(keep.start)..column_of(keep.end))
+        .filter(|keep| keep.end > window.end);
+    if let Some(keep) = keep {
+        let end_window_start = width - window.end;
+        window = if end_window_start <= keep.start {
+            end_window_start..width
+        } else {
+            let room = max_columns.saturating_sub(2);
+            let start = keep
+                .start
+                .saturating_sub(room.saturating_sub(keep.len()) / 2);
+            start..start + room
+        };
+    }
+
+    let mut truncated = Vec::with_capacity(max_columns * 4);
+    if window.start > 0 {
+        truncated.extend_from_slice(ELLIPSIS.as_bytes());
+    }
+
+    // The ellipsis for the end goes right after the last character that is kept, ahead of any escape sequences or
+    // carriage return that follow it
+    let mut kept_end = truncated.len();
+    for (unit, columns) in units.iter().zip(&unit_columns) {
+        let in_window = columns.start >= window.start && columns.end <= window.end;
+        if in_window || !unit.is_visible() {
+            truncated.extend_from_slice(&text[unit.bytes.clone()]);
+        }
+
+        if in_window && unit.is_visible() {
+            kept_end = truncated.len();
+        }
+    }
+
+    if window.end < width {
+        truncated.splice(kept_end..kept_end, ELLIPSIS.bytes());
+    }
+
+    Cow::Owned(truncated)
+}
+
+/// Decode the character at the start of the given bytes, if they start with valid UTF-8.
+fn decode_char(bytes: &[u8]) -> Option<char> {
+    // No character is longer than four bytes, so there's no need to look any further
+    let chunk = bytes[..bytes.len().min(4)].utf8_chunks().next()?;
+
+    chunk.valid().chars().next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(b"hello", 5; "ascii")]
+    #[test_case("héllo".as_bytes(), 5; "multibyte characters")]
+    #[test_case("日本".as_bytes(), 4; "wide characters")]
+    #[test_case(b"\x1b[31mred\x1b[m", 3; "escape sequences")]
+    #[test_case(b"ab\tc", 9; "tab")]
+    #[test_case(b"\xffab", 3; "invalid utf-8")]
+    #[test_case(b"hello\nhi", 2; "newline")]
+    fn test_measures_columns(text: &[u8], expected: usize) {
+        let column = units(text)
+            .iter()
+            .fold(0, |column, unit| unit.next_column(column));
+
+        assert_eq!(expected, column);
+    }
+
+    #[test_case(b"hello world", 20, None, "hello world"; "short enough")]
+    #[test_case(b"hello world", 8, None, "hello w…"; "end removed")]
+    #[test_case(b"hello world foo bar baz qux", 10, Some(12..15), "…d foo ba…"; "kept range in middle")]
+    #[test_case(b"hello world foo", 8, Some(12..15), "…rld foo"; "kept range at end")]
+    #[test_case(b"hello world", 8, Some(0..5), "hello w…"; "kept range at start")]
+    #[test_case("日本語です".as_bytes(), 6, None, "日本…"; "wide characters")]
+    #[test_case(b"\x1b[31mhello world\x1b[m", 6, None, "\x1b[31mhello…\x1b[m"; "escape sequences")]
+    #[test_case(b"hello world\r", 8, None, "hello w…\r"; "carriage return")]
+    fn test_truncates(text: &[u8], max_columns: usize, keep: Option<Range<usize>>, expected: &str) {
+        assert_eq!(
+            expected,
+            String::from_utf8_lossy(&truncate(text, max_columns, keep))
+        );
+    }
+}
@@ -2,7 +2,25 @@
 //!
 //! These types are not generally require for using the methods defined in the crate root, but can be useful to
 //! ensure their output will be usable.
+mod chain;
+mod counter;
+mod decode;
+#[cfg(feature = "decompress")]
+mod decompress;
+mod follow;
 mod recorder;
-pub mod utf8;
+mod reopen;
+mod strings;
+pub mod walk;
+mod watch;
 
-pub use recorder::ReadRecorder;
+pub use chain::ChainReader;
+pub(crate) use counter::ReadCounter;
+pub use decode::{DecodeReader, Utf16};
+#[cfg(feature = "decompress")]
+pub use decompress::{Compression, DecompressReader};
+pub use follow::FollowReader;
+pub use recorder::{OverflowBehavior, ReadRecorder};
+pub use reopen::ReopeningFile;
+pub use strings::StringsReader;
+pub use watch::FileWatcher;
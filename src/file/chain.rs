@@ -0,0 +1,53 @@
+use std::collections::VecDeque;
+use std::io::{Read, Result};
+
+/// `ChainReader` reads each of a sequence of [`Read`]s in turn, as if they were a single input, like `cat`.
+///
+/// Nothing is inserted between the inputs, so an input that does not end in a line terminator will have its last line
+/// joined to the first line of the next. Each input is dropped as soon as it has been read to its end.
+#[allow(clippy::module_name_repetitions)]
+pub struct ChainReader<R: Read> {
+    reads: VecDeque<R>,
+}
+
+impl<R: Read> ChainReader<R> {
+    /// Make a new `ChainReader` that reads each of the given [`Read`]s in order.
+    pub fn new<I: IntoIterator<Item = R>>(reads: I) -> Self {
+        Self {
+            reads: reads.into_iter().collect(),
+        }
+    }
+}
+
+impl<R: Read> Read for ChainReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        while let Some(read) = self.reads.front_mut() {
+            let n = read.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+
+            self.reads.pop_front();
+        }
+
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(&[], ""; "no inputs")]
+    #[test_case(&["hello\n"], "hello\n"; "one input")]
+    #[test_case(&["hello\n", "", "world\n"], "hello\nworld\n"; "empty input in between")]
+    #[test_case(&["hello ", "world\n"], "hello world\n"; "input without a trailing newline")]
+    fn test_reads_inputs_in_order(inputs: &[&'static str], expected: &str) {
+        let mut chain = ChainReader::new(inputs.iter().map(|input| input.as_bytes()));
+
+        let mut read = String::new();
+        chain.read_to_string(&mut read).expect("read failed");
+        assert_eq!(expected, read);
+    }
+}
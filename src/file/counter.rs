@@ -0,0 +1,53 @@
+use std::io::{Error, Read};
+
+/// `ReadCounter` is a wrapper for [`Read`] that keeps track of how many bytes have been read through it. This is
+/// useful for reporting where in an input a failure occurred.
+#[allow(clippy::module_name_repetitions)]
+pub(crate) struct ReadCounter<R: Read> {
+    read: R,
+    bytes_read: u64,
+}
+
+impl<R: Read> ReadCounter<R> {
+    /// Make a new `ReadCounter` wrapping the given `Reader`.
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            read: reader,
+            bytes_read: 0,
+        }
+    }
+
+    /// `bytes_read` gets the total number of bytes that have been successfully read from the underlying [`Read`].
+    pub(crate) fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+}
+
+impl<R: Read> Read for ReadCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let bytes_read = self.read.read(buf)?;
+        self.bytes_read += bytes_read as u64;
+
+        Ok(bytes_read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_counts_bytes_read() {
+        let mut counter = ReadCounter::new(Cursor::new("hello world"));
+        counter
+            .read_exact(&mut [0_u8; 5])
+            .expect("reading failed unexpectedly");
+        assert_eq!(counter.bytes_read(), 5);
+
+        counter
+            .read_to_string(&mut String::new())
+            .expect("reading failed unexpectedly");
+        assert_eq!(counter.bytes_read(), 11);
+    }
+}
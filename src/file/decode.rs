@@ -0,0 +1,164 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE};
+use encoding_rs_io::{DecodeReaderBytes, DecodeReaderBytesBuilder};
+use std::io::{BufRead, Read, Result};
+
+const UTF16_LE_BOM: &[u8] = b"\xff\xfe";
+const UTF16_BE_BOM: &[u8] = b"\xfe\xff";
+
+/// `Utf16` represents the byte orders of UTF-16 that can be decoded by a [`DecodeReader`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Utf16 {
+    /// Little endian UTF-16, as written by most Windows programs.
+    LittleEndian,
+    /// Big endian UTF-16.
+    BigEndian,
+}
+
+impl Utf16 {
+    /// Detect the byte order of UTF-16 data beginning with the given bytes, by its byte order mark. If the data does
+    /// not begin with a UTF-16 byte order mark, `None` is returned.
+    #[must_use]
+    pub fn detect(start: &[u8]) -> Option<Self> {
+        if start.starts_with(UTF16_LE_BOM) {
+            Some(Self::LittleEndian)
+        } else if start.starts_with(UTF16_BE_BOM) {
+            Some(Self::BigEndian)
+        } else {
+            None
+        }
+    }
+
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            Self::LittleEndian => UTF_16LE,
+            Self::BigEndian => UTF_16BE,
+        }
+    }
+}
+
+/// `DecodeReader` is a wrapper for [`BufRead`] that transparently transcodes UTF-16 data read from it to UTF-8, so that
+/// it can be searched like any other text. UTF-16 is detected from the byte order mark at the start of the data, which
+/// is left out of the transcoded data. If there is no byte order mark, the data is read as-is.
+///
+/// Data in other encodings, which cannot be detected, can be transcoded by giving its [`Encoding`] to
+/// [`DecodeReader::with_encoding`].
+#[allow(clippy::module_name_repetitions)]
+pub enum DecodeReader<R: BufRead> {
+    /// Data that is not UTF-16, which is read as-is.
+    Plain(R),
+    /// Data being transcoded from UTF-16.
+    Utf16(Utf16, DecodeReaderBytes<R, Vec<u8>>),
+    /// Data being transcoded from an explicitly given encoding.
+    Explicit(&'static Encoding, DecodeReaderBytes<R, Vec<u8>>),
+}
+
+impl<R: BufRead> DecodeReader<R> {
+    /// Make a new `DecodeReader` that transcodes the data read from the given [`BufRead`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the start of the data could not be read to detect its byte order mark.
+    pub fn new(mut read: R) -> Result<Self> {
+        let decode_reader = match Utf16::detect(read.fill_buf()?) {
+            Some(utf16) => Self::Utf16(utf16, make_decoder(read, utf16.encoding())),
+            None => Self::Plain(read),
+        };
+
+        Ok(decode_reader)
+    }
+
+    /// Make a new `DecodeReader` that transcodes the data read from the given [`BufRead`] from the given [`Encoding`],
+    /// regardless of any byte order mark. Any byte order mark is still left out of the transcoded data. Data that is
+    /// invalid in the given encoding is replaced with `U+FFFD`.
+    pub fn with_encoding(read: R, encoding: &'static Encoding) -> Self {
+        Self::Explicit(encoding, make_decoder(read, encoding))
+    }
+
+    /// Get the byte order of the UTF-16 that is being transcoded, if it was detected.
+    pub fn utf16(&self) -> Option<Utf16> {
+        match self {
+            Self::Plain(_) | Self::Explicit(..) => None,
+            Self::Utf16(utf16, _) => Some(*utf16),
+        }
+    }
+
+    /// Get the encoding that is being transcoded from, if any.
+    pub fn encoding(&self) -> Option<&'static Encoding> {
+        match self {
+            Self::Plain(_) => None,
+            Self::Utf16(utf16, _) => Some(utf16.encoding()),
+            Self::Explicit(encoding, _) => Some(encoding),
+        }
+    }
+}
+
+fn make_decoder<R: BufRead>(read: R, encoding: &'static Encoding) -> DecodeReaderBytes<R, Vec<u8>> {
+    DecodeReaderBytesBuilder::new()
+        .encoding(Some(encoding))
+        .strip_bom(true)
+        .build(read)
+}
+
+impl<R: BufRead> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        match self {
+            Self::Plain(read) => read.read(buf),
+            Self::Utf16(_, read) | Self::Explicit(_, read) => read.read(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const CONTENTS: &str = "hello\nwörld\n";
+
+    fn encode(utf16: Option<Utf16>, data: &str) -> Vec<u8> {
+        let code_units = data.encode_utf16();
+        match utf16 {
+            None => data.as_bytes().to_vec(),
+            Some(Utf16::LittleEndian) => UTF16_LE_BOM
+                .iter()
+                .copied()
+                .chain(code_units.flat_map(u16::to_le_bytes))
+                .collect(),
+            Some(Utf16::BigEndian) => UTF16_BE_BOM
+                .iter()
+                .copied()
+                .chain(code_units.flat_map(u16::to_be_bytes))
+                .collect(),
+        }
+    }
+
+    #[test_case(None; "plain")]
+    #[test_case(Some(Utf16::LittleEndian); "little endian")]
+    #[test_case(Some(Utf16::BigEndian); "big endian")]
+    fn test_decodes(utf16: Option<Utf16>) {
+        let encoded = encode(utf16, CONTENTS);
+        let mut decode_reader =
+            DecodeReader::new(encoded.as_slice()).expect("failed to detect encoding");
+        assert_eq!(utf16, decode_reader.utf16());
+
+        let mut decoded = String::new();
+        decode_reader
+            .read_to_string(&mut decoded)
+            .expect("failed to decode");
+        assert_eq!(CONTENTS, decoded);
+    }
+
+    #[test_case(b"caf\xe9\n", encoding_rs::WINDOWS_1252, "café\n"; "latin-1")]
+    #[test_case(b"\x93\xfa\x96\x7b\n", encoding_rs::SHIFT_JIS, "日本\n"; "shift_jis")]
+    #[test_case(b"\xff\xfeh\x00i\x00", UTF_16LE, "hi"; "byte order mark is left out")]
+    fn test_decodes_with_encoding(encoded: &[u8], encoding: &'static Encoding, expected: &str) {
+        let mut decode_reader = DecodeReader::with_encoding(encoded, encoding);
+        assert_eq!(Some(encoding), decode_reader.encoding());
+
+        let mut decoded = String::new();
+        decode_reader
+            .read_to_string(&mut decoded)
+            .expect("failed to decode");
+        assert_eq!(expected, decoded);
+    }
+}
@@ -0,0 +1,142 @@
+use bzip2::bufread::MultiBzDecoder;
+use flate2::bufread::MultiGzDecoder;
+use std::io::{BufRead, Read, Result};
+use xz2::bufread::XzDecoder;
+
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const BZIP2_MAGIC: &[u8] = b"BZh";
+const XZ_MAGIC: &[u8] = b"\xfd7zXZ\x00";
+
+/// `Compression` represents the compression formats that can be decompressed by a [`DecompressReader`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// gzip, as produced by `gzip`.
+    Gzip,
+    /// bzip2, as produced by `bzip2`.
+    Bzip2,
+    /// xz, as produced by `xz`.
+    Xz,
+}
+
+impl Compression {
+    /// Detect the compression format of data beginning with the given bytes, by its magic number. If the data does not
+    /// appear to be compressed, `None` is returned.
+    #[must_use]
+    pub fn detect(start: &[u8]) -> Option<Self> {
+        if start.starts_with(GZIP_MAGIC) {
+            Some(Self::Gzip)
+        } else if start.starts_with(BZIP2_MAGIC) {
+            Some(Self::Bzip2)
+        } else if start.starts_with(XZ_MAGIC) {
+            Some(Self::Xz)
+        } else {
+            None
+        }
+    }
+}
+
+/// `DecompressReader` is a wrapper for [`BufRead`] that transparently decompresses the data read from it, like `zcat`.
+/// The compression format is detected from the start of the data, and if it is not compressed, the data is read as-is.
+#[allow(clippy::module_name_repetitions)]
+pub enum DecompressReader<R: BufRead> {
+    /// Data that is not compressed, which is read as-is.
+    Plain(R),
+    /// Data being decompressed from gzip.
+    Gzip(MultiGzDecoder<R>),
+    /// Data being decompressed from bzip2.
+    Bzip2(MultiBzDecoder<R>),
+    /// Data being decompressed from xz.
+    Xz(XzDecoder<R>),
+}
+
+impl<R: BufRead> DecompressReader<R> {
+    /// Make a new `DecompressReader` that decompresses the data read from the given [`BufRead`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the start of the data could not be read to detect its compression format.
+    pub fn new(mut read: R) -> Result<Self> {
+        let decompress_reader = match Compression::detect(read.fill_buf()?) {
+            Some(Compression::Gzip) => Self::Gzip(MultiGzDecoder::new(read)),
+            Some(Compression::Bzip2) => Self::Bzip2(MultiBzDecoder::new(read)),
+            Some(Compression::Xz) => Self::Xz(XzDecoder::new_multi_decoder(read)),
+            None => Self::Plain(read),
+        };
+
+        Ok(decompress_reader)
+    }
+
+    /// Get the compression format that is being decompressed, if any.
+    pub fn compression(&self) -> Option<Compression> {
+        match self {
+            Self::Plain(_) => None,
+            Self::Gzip(_) => Some(Compression::Gzip),
+            Self::Bzip2(_) => Some(Compression::Bzip2),
+            Self::Xz(_) => Some(Compression::Xz),
+        }
+    }
+}
+
+impl<R: BufRead> Read for DecompressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        match self {
+            Self::Plain(read) => read.read(buf),
+            Self::Gzip(read) => read.read(buf),
+            Self::Bzip2(read) => read.read(buf),
+            Self::Xz(read) => read.read(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bzip2::write::BzEncoder;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use test_case::test_case;
+    use xz2::write::XzEncoder;
+
+    const CONTENTS: &str = "hello\nworld\n";
+
+    fn compress(compression: Option<Compression>, data: &[u8]) -> Vec<u8> {
+        let mut compressed = Vec::new();
+        match compression {
+            None => compressed.extend_from_slice(data),
+            Some(Compression::Gzip) => {
+                let mut encoder = GzEncoder::new(&mut compressed, flate2::Compression::default());
+                encoder.write_all(data).expect("failed to compress");
+                encoder.finish().expect("failed to compress");
+            }
+            Some(Compression::Bzip2) => {
+                let mut encoder = BzEncoder::new(&mut compressed, bzip2::Compression::default());
+                encoder.write_all(data).expect("failed to compress");
+                encoder.finish().expect("failed to compress");
+            }
+            Some(Compression::Xz) => {
+                let mut encoder = XzEncoder::new(&mut compressed, 6);
+                encoder.write_all(data).expect("failed to compress");
+                encoder.finish().expect("failed to compress");
+            }
+        }
+
+        compressed
+    }
+
+    #[test_case(None; "plain")]
+    #[test_case(Some(Compression::Gzip); "gzip")]
+    #[test_case(Some(Compression::Bzip2); "bzip2")]
+    #[test_case(Some(Compression::Xz); "xz")]
+    fn test_decompresses(compression: Option<Compression>) {
+        let compressed = compress(compression, CONTENTS.as_bytes());
+        let mut decompress_reader =
+            DecompressReader::new(compressed.as_slice()).expect("failed to detect compression");
+        assert_eq!(compression, decompress_reader.compression());
+
+        let mut decompressed = String::new();
+        decompress_reader
+            .read_to_string(&mut decompressed)
+            .expect("failed to decompress");
+        assert_eq!(CONTENTS, decompressed);
+    }
+}
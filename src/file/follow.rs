@@ -0,0 +1,88 @@
+use std::io::{Read, Result};
+use std::thread;
+use std::time::Duration;
+
+/// How long [`FollowReader`] will wait for more data by default, after reaching the end of its input.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// `FollowReader` is a wrapper for [`Read`] that, like `tail -f`, will wait for more data to be written once it has
+/// reached the end of its input, rather than signalling the end of the input.
+///
+/// As a result, reads from a `FollowReader` will never signal the end of the input (i.e. return `Ok(0)`) unless
+/// given an empty buffer, and searching one will only finish if an error occurs. Data is returned as soon as it is
+/// read, so each new line can be handled as soon as it is written.
+#[allow(clippy::module_name_repetitions)]
+pub struct FollowReader<R: Read> {
+    read: R,
+    poll_interval: Duration,
+}
+
+impl<R: Read> FollowReader<R> {
+    /// Make a new `FollowReader` that follows the given [`Read`].
+    pub fn new(read: R) -> Self {
+        Self::with_poll_interval(read, DEFAULT_POLL_INTERVAL)
+    }
+
+    /// Make a new `FollowReader` that follows the given [`Read`], checking for new data every `poll_interval` once
+    /// the end of the input has been reached.
+    pub fn with_poll_interval(read: R, poll_interval: Duration) -> Self {
+        Self {
+            read,
+            poll_interval,
+        }
+    }
+
+    /// Get a mutable reference to the underlying [`Read`].
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.read
+    }
+}
+
+impl<R: Read> Read for FollowReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let n = self.read.read(buf)?;
+            if n > 0 {
+                return Ok(n);
+            }
+
+            thread::sleep(self.poll_interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    // A reader that produces each of its chunks in turn, where an empty chunk is the end of the input at that time
+    struct ChunkedReader(VecDeque<&'static [u8]>);
+
+    impl Read for ChunkedReader {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let chunk = self.0.pop_front().expect("read past the final chunk");
+            buf[..chunk.len()].copy_from_slice(chunk);
+
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn test_waits_for_more_data() {
+        let chunks = [b"hello\n".as_slice(), b"", b"", b"world\n"];
+        let reader = ChunkedReader(chunks.into_iter().collect());
+        let mut follow_reader = FollowReader::with_poll_interval(reader, Duration::ZERO);
+
+        let mut buf = [0_u8; 16];
+        let n = follow_reader.read(&mut buf).expect("read failed");
+        assert_eq!(b"hello\n", &buf[..n]);
+
+        let n = follow_reader.read(&mut buf).expect("read failed");
+        assert_eq!(b"world\n", &buf[..n]);
+    }
+}
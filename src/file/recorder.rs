@@ -1,5 +1,22 @@
 use std::cmp;
-use std::io::{Error, Read};
+use std::env;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The size of the buffer used to read from the wrapped [`Read`] through [`BufRead`], and to read back data that has
+/// been spilled to disk.
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// The number of attempts that will be made to find an unused name for a spill file, before giving up.
+const MAX_SPILL_FILE_ATTEMPTS: usize = 16;
+
+/// Used to give each spill file made by this process a distinct name.
+static NEXT_SPILL_FILE_ID: AtomicUsize = AtomicUsize::new(0);
 
 // Having main() here helps with readability with the types I have to declare. Sorry clippy
 #[allow(clippy::needless_doctest_main)]
@@ -55,9 +72,40 @@ use std::io::{Error, Read};
 #[allow(clippy::module_name_repetitions)]
 pub struct ReadRecorder<R: Read> {
     read: R,
-    recorded_data: Vec<u8>,
+    recorded_data: Recording,
     cursor_pos: Option<usize>,
     recording: bool,
+    memory_limit: Option<(usize, OverflowBehavior)>,
+    // Data read from the wrapped Read through BufRead, which has not yet been consumed
+    buffer: Vec<u8>,
+    buffer_pos: usize,
+}
+
+/// `OverflowBehavior` decides what a [`ReadRecorder`] does once it has recorded more data than its memory limit
+/// allows (see [`memory_limit`](`ReadRecorder::memory_limit`)).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OverflowBehavior {
+    /// Fail the read that would have exceeded the limit.
+    Fail,
+    /// Move the recorded data to a temporary file, and keep recording there. The file is removed once the recorded
+    /// data is dropped.
+    SpillToDisk,
+}
+
+/// `Recording` holds the recorded data, either in memory or, once it has grown too large, in a temporary file.
+enum Recording {
+    Memory(Vec<u8>),
+    Disk(SpillFile),
+}
+
+/// `SpillFile` is a temporary file that recorded data has been moved to. It is removed when dropped.
+struct SpillFile {
+    file: File,
+    path: PathBuf,
+    len: usize,
+    // The portion of the file that was last read back, starting at chunk_start
+    chunk: Vec<u8>,
+    chunk_start: usize,
 }
 
 impl<R: Read> ReadRecorder<R> {
@@ -65,12 +113,24 @@ impl<R: Read> ReadRecorder<R> {
     pub fn new(reader: R) -> Self {
         Self {
             read: reader,
-            recorded_data: Vec::new(),
+            recorded_data: Recording::Memory(Vec::new()),
             cursor_pos: None,
             recording: false,
+            memory_limit: None,
+            buffer: Vec::new(),
+            buffer_pos: 0,
         }
     }
 
+    /// Limit the amount of recorded data that will be kept in memory to `limit` bytes. Once a read would record more
+    /// than this, `behavior` decides whether that read fails, or the recorded data is moved to a temporary file. By
+    /// default, there is no limit.
+    #[must_use]
+    pub fn memory_limit(mut self, limit: usize, behavior: OverflowBehavior) -> Self {
+        self.memory_limit = Some((limit, behavior));
+        self
+    }
+
     /// `start_recording` begins the recording process. Once this is started, any reads that call to the underlying
     /// [`Read`] (i.e. not through the recorded portion) will be copied to an internal buffer.
     pub fn start_recording(&mut self) {
@@ -89,29 +149,94 @@ impl<R: Read> ReadRecorder<R> {
     }
 
     /// `copy_from_recording` will copy as much data as possible from the current recorded data to the given buffer
-    fn copy_from_recording(&mut self, buf: &mut [u8]) -> usize {
+    fn copy_from_recording(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
         if self.cursor_pos.is_none() {
-            return 0;
+            return Ok(0);
         }
 
         let cursor_pos = self.cursor_pos.unwrap();
         if cursor_pos >= self.recorded_data.len() {
-            return 0;
+            return Ok(0);
         }
 
         let bytes_remaining_in_recording = self.recorded_data.len() - cursor_pos;
         let bytes_to_read = cmp::min(buf.len(), bytes_remaining_in_recording);
         self.recorded_data
-            .iter()
-            .skip(cursor_pos)
-            .take(bytes_to_read)
-            .enumerate()
-            .for_each(|(idx, &chr)| buf[idx] = chr);
+            .copy_to(cursor_pos, &mut buf[..bytes_to_read])?;
 
         self.cursor_pos = Some(cursor_pos + bytes_to_read);
+        Ok(bytes_to_read)
+    }
+
+    /// `record` adds the given data to the recording, spilling it to disk or failing if this would exceed the memory
+    /// limit.
+    fn record(&mut self, data: &[u8]) -> Result<(), Error> {
+        match (&mut self.recorded_data, self.memory_limit) {
+            (Recording::Memory(recorded), Some((limit, behavior)))
+                if recorded.len() + data.len() > limit =>
+            {
+                if behavior == OverflowBehavior::Fail {
+                    return Err(Error::new(
+                        ErrorKind::OutOfMemory,
+                        format!("recording exceeded the memory limit of {limit} bytes"),
+                    ));
+                }
+
+                let mut spill_file = SpillFile::create()?;
+                spill_file.append(recorded)?;
+                spill_file.append(data)?;
+                self.recorded_data = Recording::Disk(spill_file);
+            }
+            (Recording::Memory(recorded), _) => recorded.extend_from_slice(data),
+            (Recording::Disk(spill_file), _) => spill_file.append(data)?,
+        }
+
+        Ok(())
+    }
+
+    /// `copy_from_buffer` will copy as much data as possible from the data buffered through [`BufRead`] to the given
+    /// buffer
+    fn copy_from_buffer(&mut self, buf: &mut [u8]) -> usize {
+        let buffered = &self.buffer[self.buffer_pos..];
+        let bytes_to_read = cmp::min(buf.len(), buffered.len());
+        buf[..bytes_to_read].copy_from_slice(&buffered[..bytes_to_read]);
+        self.buffer_pos += bytes_to_read;
+
         bytes_to_read
     }
 
+    fn has_buffered_data(&self) -> bool {
+        self.buffer_pos < self.buffer.len()
+    }
+
+    /// `record_buffered_data` moves any data buffered through [`BufRead`] into the recording, and places the cursor at
+    /// its start, so that it is read from the recording from then on.
+    fn record_buffered_data(&mut self) -> Result<(), Error> {
+        if !self.has_buffered_data() {
+            return Ok(());
+        }
+
+        let recording_len = self.recorded_data.len();
+        let cursor_pos = cmp::min(self.cursor_pos.unwrap_or(recording_len), recording_len);
+        let buffer = mem::take(&mut self.buffer);
+        let record_res = self.record(&buffer[self.buffer_pos..]);
+        self.buffer = buffer;
+        record_res?;
+
+        self.buffer.clear();
+        self.buffer_pos = 0;
+        self.cursor_pos = Some(cursor_pos);
+
+        Ok(())
+    }
+
+    fn cursor_in_recording_bounds(&self) -> bool {
+        match self.cursor_pos {
+            None => false,
+            Some(cursor_pos) => cursor_pos < self.recorded_data.len(),
+        }
+    }
+
     fn cursor_out_of_recording_bounds(&self) -> bool {
         match self.cursor_pos {
             None => false,
@@ -130,12 +255,170 @@ impl<R: Read> ReadRecorder<R> {
     }
 
     fn drop_recorded_data(&mut self) {
-        self.recorded_data.clear();
-        self.recorded_data.shrink_to_fit();
+        // Replacing a spilled recording will remove its file
+        self.recorded_data = Recording::Memory(Vec::new());
         self.cursor_pos = None;
     }
 }
 
+impl Recording {
+    fn len(&self) -> usize {
+        match self {
+            Self::Memory(recorded) => recorded.len(),
+            Self::Disk(spill_file) => spill_file.len,
+        }
+    }
+
+    /// Get the recorded data starting at the given position, which must be within the recording. Only a portion of the
+    /// remaining data may be given if it has been spilled to disk.
+    fn chunk_at(&mut self, pos: usize) -> Result<&[u8], Error> {
+        match self {
+            Self::Memory(recorded) => Ok(&recorded[pos..]),
+            Self::Disk(spill_file) => spill_file.chunk_at(pos),
+        }
+    }
+
+    /// Fill the given buffer with the recorded data, starting at the given position. There must be enough recorded data
+    /// after this position to fill it.
+    fn copy_to(&mut self, pos: usize, buf: &mut [u8]) -> Result<(), Error> {
+        match self {
+            Self::Memory(recorded) => {
+                buf.copy_from_slice(&recorded[pos..pos + buf.len()]);
+                Ok(())
+            }
+            Self::Disk(spill_file) => {
+                spill_file.file.seek(SeekFrom::Start(pos as u64))?;
+                spill_file.file.read_exact(buf)
+            }
+        }
+    }
+}
+
+impl SpillFile {
+    /// Make a new, empty, spill file in the system's temporary directory.
+    fn create() -> Result<Self, Error> {
+        let temp_dir = env::temp_dir();
+        for _ in 0..MAX_SPILL_FILE_ATTEMPTS {
+            let id = NEXT_SPILL_FILE_ID.fetch_add(1, Ordering::Relaxed);
+            let path = temp_dir.join(format!("hline-recording-{}-{id}", process::id()));
+            let open_res = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path);
+
+            match open_res {
+                Ok(file) => {
+                    return Ok(Self {
+                        file,
+                        path,
+                        len: 0,
+                        chunk: Vec::new(),
+                        chunk_start: 0,
+                    })
+                }
+                // Another process may have left a file with this name behind, so we must try another
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(Error::new(
+            ErrorKind::AlreadyExists,
+            "could not find an unused name for a recording's temporary file",
+        ))
+    }
+
+    fn append(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(data)?;
+        self.len += data.len();
+
+        Ok(())
+    }
+
+    fn chunk_at(&mut self, pos: usize) -> Result<&[u8], Error> {
+        let chunk_end = self.chunk_start + self.chunk.len();
+        if !(self.chunk_start..chunk_end).contains(&pos) {
+            self.chunk.resize(cmp::min(BUFFER_SIZE, self.len - pos), 0);
+            self.file.seek(SeekFrom::Start(pos as u64))?;
+            self.file.read_exact(&mut self.chunk)?;
+            self.chunk_start = pos;
+        }
+
+        Ok(&self.chunk[pos - self.chunk_start..])
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        // There's nothing that can be done if this fails; at worst, the file is left for the OS to clean up
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl<R: Read + Seek> Seek for ReadRecorder<R> {
+    /// `seek` seeks to a position in the stream as [`Seek::seek`] would on the wrapped [`Read`], accounting for any
+    /// recorded data that has been rewound over. Seeking to a position within the recorded data moves the "rewind
+    /// cursor" (see [`read`](`ReadRecorder::read`)), without calling the wrapped [`Read`], so the recorded data can be
+    /// read again. Seeking anywhere else is delegated to the wrapped [`Read`], and drops the recorded data, as it would
+    /// no longer be contiguous with what is read next.
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, Error> {
+        // Any data buffered through BufRead is given back to the wrapped Read, so that it is always positioned at the
+        // end of the recorded data
+        if self.has_buffered_data() {
+            let buffered_len = i64::try_from(self.buffer.len() - self.buffer_pos)
+                .expect("buffer cannot be larger than BUFFER_SIZE");
+            self.read.seek(SeekFrom::Current(-buffered_len))?;
+            self.buffer.clear();
+            self.buffer_pos = 0;
+        }
+
+        let recording_end = self.read.stream_position()?;
+        let recording_start = recording_end - self.recorded_data.len() as u64;
+        let current_pos = match self.cursor_pos {
+            Some(cursor_pos) if cursor_pos < self.recorded_data.len() => {
+                recording_start + cursor_pos as u64
+            }
+            _ => recording_end,
+        };
+
+        let target = match pos {
+            SeekFrom::Start(offset) => offset,
+            SeekFrom::Current(offset) => offset_position(current_pos, offset)?,
+            SeekFrom::End(_) => {
+                // We can't know where the end is without asking the wrapped Read, so we must seek it, and then return
+                // to where we were if the target is within the recording.
+                let target = self.read.seek(pos)?;
+                self.read.seek(SeekFrom::Start(recording_end))?;
+                target
+            }
+        };
+
+        if (recording_start..=recording_end).contains(&target) {
+            // The target can be no further from the start than the recording's length, which is a usize
+            #[allow(clippy::cast_possible_truncation)]
+            let cursor_pos = (target - recording_start) as usize;
+            self.cursor_pos = Some(cursor_pos);
+        } else {
+            self.read.seek(SeekFrom::Start(target))?;
+            self.drop_recorded_data();
+        }
+
+        Ok(target)
+    }
+}
+
+/// Offset the given position by the given signed offset, failing if the result would be before the start of the stream.
+fn offset_position(pos: u64, offset: i64) -> Result<u64, Error> {
+    pos.checked_add_signed(offset).ok_or_else(|| {
+        Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "invalid seek to a negative or overflowing position",
+        )
+    })
+}
+
 impl<R: Read> Read for ReadRecorder<R> {
     /// `read` serves two purposes. In the general case, it will forward reads to the wrapped [`Read`], and, if
     /// recording currently taking place (see [`start_recording`](`ReadRecorder::start_recording`)),
@@ -149,12 +432,24 @@ impl<R: Read> Read for ReadRecorder<R> {
     /// [`rewind_to_start_of_recording`](`ReadRecorder::rewind_to_start_of_recording`), which sets it to zero. Every
     /// byte read will advance this cursor, until it is outside the bounds of the recorded data, at which point the
     /// recorded data is dropped.
+    ///
+    /// If a memory limit has been set (see [`memory_limit`](`ReadRecorder::memory_limit`)), recording data past it will
+    /// either fail the read, or move the recorded data to a temporary file, as decided by its [`OverflowBehavior`].
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
-        let bytes_copied_from_recording = self.copy_from_recording(buf);
-        let bytes_read_from_file = self.read.read(&mut buf[bytes_copied_from_recording..])?;
         if self.recording {
-            self.recorded_data
-                .extend(buf.iter().take(bytes_read_from_file));
+            self.record_buffered_data()?;
+        }
+
+        let bytes_copied_from_recording = self.copy_from_recording(buf)?;
+        let bytes_read_from_file = if self.has_buffered_data() {
+            self.copy_from_buffer(&mut buf[bytes_copied_from_recording..])
+        } else {
+            self.read.read(&mut buf[bytes_copied_from_recording..])?
+        };
+
+        if self.recording {
+            let read_from_file = &buf[bytes_copied_from_recording..][..bytes_read_from_file];
+            self.record(read_from_file)?;
         } else if self.should_clear_recorded_data(bytes_read_from_file) {
             self.drop_recorded_data();
         }
@@ -163,10 +458,59 @@ impl<R: Read> Read for ReadRecorder<R> {
     }
 }
 
+impl<R: Read> BufRead for ReadRecorder<R> {
+    /// `fill_buf` gives the recorded data if the "rewind cursor" is within it, as [`read`](`ReadRecorder::read`)
+    /// would, and otherwise the contents of an internal buffer, which is filled from the wrapped [`Read`]. While
+    /// recording, data read into this buffer is recorded, and given from the recording.
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        if self.recording {
+            self.record_buffered_data()?;
+        }
+
+        if !self.cursor_in_recording_bounds() && !self.has_buffered_data() {
+            self.buffer.resize(BUFFER_SIZE, 0);
+            self.buffer_pos = 0;
+            match self.read.read(&mut self.buffer) {
+                Ok(bytes_read) => self.buffer.truncate(bytes_read),
+                Err(err) => {
+                    self.buffer.clear();
+                    return Err(err);
+                }
+            }
+
+            if self.recording {
+                self.record_buffered_data()?;
+            }
+        }
+
+        match self.cursor_pos {
+            Some(cursor_pos) if self.cursor_in_recording_bounds() => {
+                self.recorded_data.chunk_at(cursor_pos)
+            }
+            _ => Ok(&self.buffer[self.buffer_pos..]),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self.cursor_pos {
+            Some(cursor_pos) if self.cursor_in_recording_bounds() => {
+                self.cursor_pos = Some(cursor_pos + amt);
+            }
+            _ => {
+                self.buffer_pos = cmp::min(self.buffer_pos + amt, self.buffer.len());
+                if self.should_clear_recorded_data(amt) {
+                    self.drop_recorded_data();
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::{Cursor, Error};
+    use test_case::test_case;
 
     // A small wrapper for Cursor to provide a read "mock"
     struct ReadCountingCursor<R> {
@@ -195,6 +539,12 @@ mod tests {
         }
     }
 
+    impl<R: AsRef<[u8]>> Seek for ReadCountingCursor<R> {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64, Error> {
+            self.wrapped_cursor.seek(pos)
+        }
+    }
+
     #[test]
     fn test_reads_transparently_by_default() {
         let s_reader = Cursor::new("hello world");
@@ -309,6 +659,163 @@ mod tests {
         assert_eq!(read_contents, "hello world");
     }
 
+    #[test]
+    fn test_can_seek_within_recorded_portion_without_calling_underlying_reader() {
+        let s_reader = ReadCountingCursor::new(Cursor::new("hello world"));
+        let mut recorder = ReadRecorder::new(s_reader);
+
+        recorder.start_recording();
+        recorder
+            .read_exact(&mut [0_u8; 5])
+            .expect("reading failed unexpectedly");
+        recorder.stop_recording();
+
+        let num_reads_before = recorder.read.num_reads;
+        let pos = recorder
+            .seek(SeekFrom::Start(1))
+            .expect("seeking failed unexpectedly");
+        assert_eq!(1, pos);
+
+        let mut read_contents = [0_u8; 4];
+        recorder
+            .read_exact(&mut read_contents)
+            .expect("reading failed unexpectedly");
+        assert_eq!(
+            "ello",
+            std::str::from_utf8(&read_contents).expect("did not read utf-8")
+        );
+        assert_eq!(
+            num_reads_before, recorder.read.num_reads,
+            "underlying Read was called more times than it should"
+        );
+    }
+
+    #[test_case(SeekFrom::Start(6), 6, "world"; "from start")]
+    #[test_case(SeekFrom::Current(-2), 3, "lo world"; "backwards from current")]
+    #[test_case(SeekFrom::Current(2), 7, "orld"; "forwards from current")]
+    #[test_case(SeekFrom::End(-3), 8, "rld"; "from end")]
+    fn test_seeks_like_underlying_reader(pos: SeekFrom, expected_pos: u64, expected_rest: &str) {
+        let s_reader = Cursor::new("hello world");
+        let mut recorder = ReadRecorder::new(s_reader);
+
+        recorder.start_recording();
+        recorder
+            .read_exact(&mut [0_u8; 5])
+            .expect("reading failed unexpectedly");
+        recorder.stop_recording();
+
+        let pos = recorder.seek(pos).expect("seeking failed unexpectedly");
+        assert_eq!(expected_pos, pos);
+        assert_eq!(
+            expected_pos,
+            recorder
+                .stream_position()
+                .expect("seeking failed unexpectedly")
+        );
+
+        let mut read_contents = String::new();
+        recorder
+            .read_to_string(&mut read_contents)
+            .expect("reading failed unexpectedly");
+        assert_eq!(expected_rest, read_contents);
+    }
+
+    #[test]
+    fn test_seeking_before_start_fails() {
+        let mut recorder = ReadRecorder::new(Cursor::new("hello world"));
+
+        recorder
+            .seek(SeekFrom::Current(-1))
+            .expect_err("seeking before the start should fail");
+    }
+
+    #[test]
+    fn test_can_replay_recording_spilled_to_disk() {
+        let mut recorder = ReadRecorder::new(Cursor::new("hello world"))
+            .memory_limit(4, OverflowBehavior::SpillToDisk);
+
+        recorder.start_recording();
+        recorder
+            .read_exact(&mut [0_u8; 8])
+            .expect("reading failed unexpectedly");
+        recorder.stop_recording();
+        assert!(
+            matches!(recorder.recorded_data, Recording::Disk(_)),
+            "recording was not spilled to disk"
+        );
+
+        recorder.rewind_to_start_of_recording();
+        let mut read_contents = String::new();
+        recorder
+            .read_to_string(&mut read_contents)
+            .expect("reading failed unexpectedly");
+        assert_eq!("hello world", read_contents);
+    }
+
+    #[test]
+    fn test_recording_past_memory_limit_fails_if_not_spilling() {
+        let mut recorder =
+            ReadRecorder::new(Cursor::new("hello world")).memory_limit(4, OverflowBehavior::Fail);
+
+        recorder.start_recording();
+        recorder
+            .read_exact(&mut [0_u8; 4])
+            .expect("reading failed unexpectedly");
+        let err = recorder
+            .read_exact(&mut [0_u8; 1])
+            .expect_err("recording past the limit should fail");
+
+        assert_eq!(ErrorKind::OutOfMemory, err.kind());
+    }
+
+    #[test]
+    fn test_can_read_lines_through_recording() {
+        let mut recorder = ReadRecorder::new(Cursor::new("hello\nworld\n"));
+
+        recorder.start_recording();
+        let mut first_line = String::new();
+        recorder
+            .read_line(&mut first_line)
+            .expect("reading failed unexpectedly");
+        recorder.stop_recording();
+        assert_eq!("hello\n", first_line);
+
+        recorder.rewind_to_start_of_recording();
+        let lines = recorder
+            .lines()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("reading failed unexpectedly");
+        assert_eq!(vec!["hello", "world"], lines);
+    }
+
+    #[test]
+    fn test_reads_data_buffered_by_buf_read() {
+        let mut recorder = ReadRecorder::new(Cursor::new("hello world"));
+
+        let buffered = recorder.fill_buf().expect("reading failed unexpectedly");
+        assert_eq!(b"hello world", buffered);
+        recorder.consume(6);
+
+        let mut read_contents = String::new();
+        recorder
+            .read_to_string(&mut read_contents)
+            .expect("reading failed unexpectedly");
+        assert_eq!("world", read_contents);
+    }
+
+    #[test]
+    fn test_seeking_gives_back_data_buffered_by_buf_read() {
+        let mut recorder = ReadRecorder::new(Cursor::new("hello world"));
+
+        recorder.fill_buf().expect("reading failed unexpectedly");
+        recorder.consume(6);
+        let pos = recorder
+            .stream_position()
+            .expect("seeking failed unexpectedly");
+
+        assert_eq!(6, pos);
+    }
+
     #[test]
     fn test_reading_past_recorded_portion_drops_recording() {
         const BYTES_TO_RECORD: usize = 3;
@@ -0,0 +1,175 @@
+use std::fs;
+use std::fs::{File, Metadata};
+use std::io::{ErrorKind, Read, Result, Seek, SeekFrom};
+use std::path::PathBuf;
+
+/// `ReopeningFile` reads the file at a given path, reopening it by that path if it is replaced, like `tail -F`.
+///
+/// Whenever the end of the current file is reached, the path is checked to see if it now names a different file (e.g.
+/// because the log it names was rotated), and if so, the new file is read from its start. If the current file was
+/// truncated, it is read again from its start. While no file exists at the path, the end of the input is signalled
+/// as usual, so wrapping a `ReopeningFile` in a [`FollowReader`](super::FollowReader) will wait for the file to be
+/// recreated.
+#[allow(clippy::module_name_repetitions)]
+pub struct ReopeningFile {
+    path: PathBuf,
+    file: File,
+    identity: Option<FileIdentity>,
+    position: u64,
+}
+
+impl ReopeningFile {
+    /// Open the file at the given path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be opened.
+    pub fn open<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        let file = File::open(&path)?;
+        let identity = file_identity(&file.metadata()?);
+
+        Ok(Self {
+            path,
+            file,
+            identity,
+            position: 0,
+        })
+    }
+
+    /// Reopen the file by its path if the path now names a different file, returning whether it was reopened.
+    fn reopen_if_replaced(&mut self) -> Result<bool> {
+        let identity = match fs::metadata(&self.path) {
+            Ok(metadata) => file_identity(&metadata),
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err),
+        };
+
+        if identity == self.identity {
+            return Ok(false);
+        }
+
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            // The file may have been removed again since it was checked
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err),
+        };
+
+        self.identity = file_identity(&file.metadata()?);
+        self.file = file;
+        self.position = 0;
+
+        Ok(true)
+    }
+
+    /// Move back to the start of the file if it has been truncated to before the current position, returning whether
+    /// it was.
+    fn rewind_if_truncated(&mut self) -> Result<bool> {
+        if self.file.metadata()?.len() >= self.position {
+            return Ok(false);
+        }
+
+        self.seek(SeekFrom::Start(0))?;
+
+        Ok(true)
+    }
+}
+
+impl Read for ReopeningFile {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let mut n = self.file.read(buf)?;
+        if n == 0
+            && !buf.is_empty()
+            && (self.reopen_if_replaced()? || self.rewind_if_truncated()?)
+        {
+            n = self.file.read(buf)?;
+        }
+
+        self.position += n as u64;
+
+        Ok(n)
+    }
+}
+
+impl Seek for ReopeningFile {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.position = self.file.seek(pos)?;
+
+        Ok(self.position)
+    }
+}
+
+/// Identifies a file independently of the path used to open it.
+#[cfg(unix)]
+#[derive(PartialEq, Eq)]
+struct FileIdentity {
+    device: u64,
+    inode: u64,
+}
+
+/// Identifies a file independently of the path used to open it. Where inodes are not available, the time the file was
+/// created is used instead.
+#[cfg(not(unix))]
+#[derive(PartialEq, Eq)]
+struct FileIdentity {
+    created: std::time::SystemTime,
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)]
+fn file_identity(metadata: &Metadata) -> Option<FileIdentity> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(FileIdentity {
+        device: metadata.dev(),
+        inode: metadata.ino(),
+    })
+}
+
+#[cfg(not(unix))]
+fn file_identity(metadata: &Metadata) -> Option<FileIdentity> {
+    metadata
+        .created()
+        .ok()
+        .map(|created| FileIdentity { created })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+    use test_case::test_case;
+
+    fn read_available(file: &mut ReopeningFile) -> String {
+        let mut read = String::new();
+        file.read_to_string(&mut read).expect("read failed");
+
+        read
+    }
+
+    #[test_case(true; "replaced")]
+    #[test_case(false; "truncated")]
+    fn test_reads_new_contents_from_start(replace: bool) {
+        let dir = env::temp_dir();
+        let path = dir.join(format!("hline-reopen-test-{}-{replace}", process::id()));
+        fs::write(&path, "hello world\n").expect("failed to create test file");
+
+        let mut file = ReopeningFile::open(&path).expect("failed to open test file");
+        assert_eq!("hello world\n", read_available(&mut file));
+
+        if replace {
+            let rotated_path = path.with_extension("1");
+            fs::rename(&path, &rotated_path).expect("failed to rotate test file");
+            fs::write(&path, "goodbye\n").expect("failed to replace test file");
+            fs::remove_file(rotated_path).expect("failed to clean up test file");
+        } else {
+            fs::write(&path, "goodbye\n").expect("failed to truncate test file");
+        }
+
+        let read = read_available(&mut file);
+        fs::remove_file(&path).expect("failed to clean up test file");
+        assert_eq!("goodbye\n", read);
+    }
+}
@@ -0,0 +1,140 @@
+use std::io::{Read, Result};
+
+/// The shortest run of printable characters that [`StringsReader`] will read, unless another is given. This matches
+/// the default of `strings(1)`.
+const DEFAULT_MIN_LEN: usize = 4;
+
+/// The number of bytes read from the wrapped [`Read`] at a time.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// `StringsReader` reads only the runs of printable ASCII characters in a [`Read`], each on its own line, like
+/// `strings(1)`. This allows the text within binary data, such as the messages in an executable, to be scanned.
+///
+/// Printable characters are those from space to `~`, along with tabs. Runs shorter than the minimum length (4 by
+/// default) are dropped, as short runs are usually binary data that happens to be printable. Each line of a text file
+/// is a run of its own, so text is read mostly as-is, less its short lines.
+///
+/// ```
+/// use hline::file::StringsReader;
+/// use std::io::Read;
+///
+/// let mut reader = StringsReader::new(b"\x7fELF\x02\x01hello world\0\x03ab\0".as_slice());
+/// let mut read = String::new();
+/// reader.read_to_string(&mut read).expect("read failed");
+///
+/// assert_eq!("hello world\n", read);
+/// ```
+#[allow(clippy::module_name_repetitions)]
+pub struct StringsReader<R: Read> {
+    reader: R,
+    min_len: usize,
+    run: Vec<u8>,
+    pending: Vec<u8>,
+    pending_pos: usize,
+    done: bool,
+}
+
+impl<R: Read> StringsReader<R> {
+    /// Make a new `StringsReader` that reads the runs of at least four printable characters in the given [`Read`].
+    pub fn new(reader: R) -> Self {
+        Self::with_min_len(reader, DEFAULT_MIN_LEN)
+    }
+
+    /// Make a new `StringsReader` that reads the runs of at least `min_len` printable characters in the given [`Read`].
+    pub fn with_min_len(reader: R, min_len: usize) -> Self {
+        Self {
+            reader,
+            min_len,
+            run: Vec::new(),
+            pending: Vec::new(),
+            pending_pos: 0,
+            done: false,
+        }
+    }
+
+    /// Read the next chunk of the wrapped [`Read`], moving any runs that it completes to the pending output.
+    fn fill_pending(&mut self) -> Result<()> {
+        let mut chunk = [0; CHUNK_SIZE];
+        let n = self.reader.read(&mut chunk)?;
+        if n == 0 {
+            self.done = true;
+            self.end_run();
+            return Ok(());
+        }
+
+        for &byte in &chunk[..n] {
+            if is_printable(byte) {
+                self.run.push(byte);
+            } else {
+                self.end_run();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// End the current run, moving it to the pending output if it is long enough.
+    fn end_run(&mut self) {
+        if self.run.len() >= self.min_len {
+            self.pending.extend_from_slice(&self.run);
+            self.pending.push(b'\n');
+        }
+
+        self.run.clear();
+    }
+}
+
+impl<R: Read> Read for StringsReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        while self.pending_pos == self.pending.len() {
+            if self.done || buf.is_empty() {
+                return Ok(0);
+            }
+
+            self.pending.clear();
+            self.pending_pos = 0;
+            self.fill_pending()?;
+        }
+
+        let pending = &self.pending[self.pending_pos..];
+        let n = pending.len().min(buf.len());
+        buf[..n].copy_from_slice(&pending[..n]);
+        self.pending_pos += n;
+
+        Ok(n)
+    }
+}
+
+fn is_printable(byte: u8) -> bool {
+    byte == b'\t' || (b' '..=b'~').contains(&byte)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(b"", ""; "empty input")]
+    #[test_case(b"hello\nworld\n", "hello\nworld\n"; "text")]
+    #[test_case(b"hello\nhi\nworld", "hello\nworld\n"; "short line and no trailing newline")]
+    #[test_case(b"\0\0abc\0abcd\xff\xfe\ttab run\0", "abc\nabcd\n\ttab run\n"; "binary data")]
+    #[test_case(b"caf\xc3\xa9 latte", "caf\n latte\n"; "non-ascii characters")]
+    fn test_reads_printable_runs(input: &[u8], expected: &str) {
+        let mut reader = StringsReader::with_min_len(input, 3);
+
+        let mut read = String::new();
+        reader.read_to_string(&mut read).expect("read failed");
+        assert_eq!(expected, read);
+    }
+
+    #[test]
+    fn test_reads_runs_across_chunks() {
+        let mut input = vec![b'a'; CHUNK_SIZE + 10];
+        input.push(b'\0');
+        let mut reader = StringsReader::new(input.as_slice());
+
+        let mut read = Vec::new();
+        reader.read_to_end(&mut read).expect("read failed");
+        assert_eq!(CHUNK_SIZE + 11, read.len());
+    }
+}
@@ -0,0 +1,321 @@
+use globset::GlobSet;
+use ignore::Ignores;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+mod ignore;
+mod types;
+
+pub use types::{FileTypeError, FileTypes};
+
+/// `Error` represents a failure to read part of the directory tree being walked.
+#[derive(Error, Debug)]
+#[error("{}: {source}", path.display())]
+pub struct Error {
+    /// The path that could not be read.
+    pub path: PathBuf,
+    /// The original i/o error that caused the failure.
+    pub source: io::Error,
+}
+
+/// `Walk` is an iterator over every regular file in a directory tree, in lexicographic order of their paths.
+///
+/// Symbolic links to files are included, but symbolic links to directories are not followed, so that the walk can
+/// never loop. If part of the tree cannot be read, an [`Error`] is produced for it, and the walk will carry on with
+/// the rest of the tree.
+pub struct Walk {
+    root: PathBuf,
+    // Paths that are yet to be visited, in reverse order so that the next path can be popped off the end.
+    pending: Vec<PendingPath>,
+    max_depth: Option<usize>,
+    include: Vec<GlobSet>,
+    exclude: Option<GlobSet>,
+    respect_ignore_files: bool,
+    skip_hidden: bool,
+}
+
+/// `PendingPath` is a path that is yet to be visited by a [`Walk`].
+struct PendingPath {
+    path: PathBuf,
+    // How many directories below the root the path is
+    depth: usize,
+    // The rules from the ignore files of the directories the path is within
+    ignores: Option<Arc<Ignores>>,
+}
+
+impl Walk {
+    /// Make a new `Walk` over the directory tree rooted at the given path. If the path is a file, only that file
+    /// will be produced.
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        let root = root.as_ref().to_path_buf();
+        let pending_root = PendingPath {
+            path: root.clone(),
+            depth: 0,
+            ignores: None,
+        };
+
+        Self {
+            pending: vec![pending_root],
+            root,
+            max_depth: None,
+            include: Vec::new(),
+            exclude: None,
+            respect_ignore_files: false,
+            skip_hidden: false,
+        }
+    }
+
+    /// Only descend the given number of directories below the root, so that a depth of 1 produces only the files
+    /// directly within the root.
+    #[must_use]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Only produce the files whose paths, relative to the root, match at least one of the given globs. All
+    /// directories are still descended into, so that files within them can match. If the root is itself a file, it is
+    /// always produced. If this is called more than once, files must match a glob from each of the sets.
+    #[must_use]
+    pub fn include(mut self, globs: GlobSet) -> Self {
+        self.include.push(globs);
+        self
+    }
+
+    /// Skip the files and directories whose paths, relative to the root, match any of the given globs. Nothing within
+    /// a skipped directory is visited. This takes precedence over [`Walk::include`].
+    #[must_use]
+    pub fn exclude(mut self, globs: GlobSet) -> Self {
+        self.exclude = Some(globs);
+        self
+    }
+
+    /// Skip the files and directories listed in `.gitignore` and `.ignore` files within the tree, using the same
+    /// format as git. The rules from an ignore file apply to everything within its directory, and take precedence over
+    /// those from the directories above it.
+    #[must_use]
+    pub fn respect_ignore_files(mut self) -> Self {
+        self.respect_ignore_files = true;
+        self
+    }
+
+    /// Skip hidden files and directories, whose names start with a dot. Ignore files are still respected if they are
+    /// hidden, as they always are.
+    #[must_use]
+    pub fn skip_hidden(mut self) -> Self {
+        self.skip_hidden = true;
+        self
+    }
+
+    /// Whether the file at the given path, which is at the given depth, should be produced by the walk.
+    fn is_included(&self, path: &Path, depth: usize) -> bool {
+        let relative_path = self.relative_path(path);
+        depth == 0
+            || self
+                .include
+                .iter()
+                .all(|include| include.is_match(relative_path))
+    }
+
+    /// Whether the file or directory at the given path, which is at the given depth, should be skipped by the walk.
+    fn is_excluded(&self, path: &Path, depth: usize) -> bool {
+        if depth == 0 {
+            return false;
+        }
+
+        let is_hidden = path
+            .file_name()
+            .is_some_and(|name| name.as_encoded_bytes().starts_with(b"."));
+        if self.skip_hidden && is_hidden {
+            return true;
+        }
+
+        self.exclude
+            .as_ref()
+            .is_some_and(|exclude| exclude.is_match(self.relative_path(path)))
+    }
+
+    /// Get the given path, relative to the root of the walk.
+    fn relative_path<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+
+    /// Queue the contents of the given directory to be visited.
+    fn push_dir_entries(&mut self, dir: PendingPath) -> Result<(), Error> {
+        let to_walk_error = |source| Error {
+            path: dir.path.clone(),
+            source,
+        };
+
+        let ignores = if self.respect_ignore_files {
+            Ignores::load(&dir.path, dir.ignores).map_err(to_walk_error)?
+        } else {
+            None
+        };
+
+        let mut entries = fs::read_dir(&dir.path)
+            .and_then(|read_dir| {
+                read_dir
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(to_walk_error)?;
+
+        // Sort in reverse, so that popping from the end of the stack visits paths in order.
+        entries.sort_unstable_by(|a, b| b.cmp(a));
+        self.pending
+            .extend(entries.into_iter().map(|entry| PendingPath {
+                path: entry,
+                depth: dir.depth + 1,
+                ignores: ignores.clone(),
+            }));
+
+        Ok(())
+    }
+}
+
+impl Iterator for Walk {
+    type Item = Result<PathBuf, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(pending) = self.pending.pop() {
+            if self.is_excluded(&pending.path, pending.depth) {
+                continue;
+            }
+
+            let file_type = match fs::symlink_metadata(&pending.path) {
+                Ok(metadata) => metadata.file_type(),
+                Err(err) => {
+                    return Some(Err(Error {
+                        path: pending.path,
+                        source: err,
+                    }))
+                }
+            };
+
+            let is_ignored = pending
+                .ignores
+                .as_ref()
+                .is_some_and(|ignores| ignores.is_ignored(&pending.path, file_type.is_dir()));
+            if is_ignored {
+                continue;
+            }
+
+            if file_type.is_dir() {
+                if self
+                    .max_depth
+                    .is_some_and(|max_depth| pending.depth >= max_depth)
+                {
+                    continue;
+                }
+
+                if let Err(err) = self.push_dir_entries(pending) {
+                    return Some(Err(err));
+                }
+            } else if (file_type.is_file() || (file_type.is_symlink() && pending.path.is_file()))
+                && self.is_included(&pending.path, pending.depth)
+            {
+                return Some(Ok(pending.path));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use globset::{Glob, GlobSetBuilder};
+    use std::env;
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use test_case::test_case;
+
+    // Each test walks its own directory, so that tests running in parallel don't interfere with each other
+    static NEXT_TEST_DIR_ID: AtomicUsize = AtomicUsize::new(0);
+
+    fn make_globs(globs: &[&str]) -> GlobSet {
+        let mut builder = GlobSetBuilder::new();
+        for &glob in globs {
+            builder.add(Glob::new(glob).expect("invalid glob"));
+        }
+
+        builder.build().expect("failed to build globs")
+    }
+
+    #[test_case(None, None, None, &["a.log", "b/e.log", "b/nested/d.log", "c.log"]; "unlimited depth")]
+    #[test_case(Some(1), None, None, &["a.log", "c.log"]; "only the root directory")]
+    #[test_case(Some(2), None, None, &["a.log", "b/e.log", "c.log"]; "one level of subdirectories")]
+    #[test_case(None, Some(&["b/**"]), None, &["b/e.log", "b/nested/d.log"]; "included directory")]
+    #[test_case(None, Some(&["*d.*", "c.log"]), None, &["b/nested/d.log", "c.log"]; "included file names")]
+    #[test_case(None, None, Some(&["b/nested"]), &["a.log", "b/e.log", "c.log"]; "excluded directory")]
+    #[test_case(None, None, Some(&["[ab].log", "*/e.*"]), &["b/nested/d.log", "c.log"]; "excluded file names")]
+    #[test_case(None, Some(&["b/**"]), Some(&["*/e.*"]), &["b/nested/d.log"]; "included and excluded")]
+    fn test_walks_files_in_order(
+        max_depth: Option<usize>,
+        include: Option<&[&str]>,
+        exclude: Option<&[&str]>,
+        expected_files: &[&str],
+    ) {
+        let id = NEXT_TEST_DIR_ID.fetch_add(1, Ordering::Relaxed);
+        let root = env::temp_dir().join(format!("hline-walk-test-{}-{id}", process::id()));
+        fs::create_dir_all(root.join("b/nested")).expect("failed to create test dirs");
+        fs::create_dir_all(root.join("empty")).expect("failed to create test dirs");
+        for file in ["c.log", "a.log", "b/nested/d.log", "b/e.log"] {
+            fs::write(root.join(file), "hello").expect("failed to create test file");
+        }
+
+        let mut walk = Walk::new(&root);
+        if let Some(max_depth) = max_depth {
+            walk = walk.max_depth(max_depth);
+        }
+        if let Some(include) = include {
+            walk = walk.include(make_globs(include));
+        }
+        if let Some(exclude) = exclude {
+            walk = walk.exclude(make_globs(exclude));
+        }
+
+        let walked = walk.collect::<Result<Vec<_>, _>>().expect("walk failed");
+        fs::remove_dir_all(&root).expect("failed to clean up test dir");
+
+        let expected: Vec<PathBuf> = expected_files.iter().map(|file| root.join(file)).collect();
+        assert_eq!(expected, walked);
+    }
+
+    #[test]
+    fn test_respects_ignore_files_and_hidden_files() {
+        let id = NEXT_TEST_DIR_ID.fetch_add(1, Ordering::Relaxed);
+        let root = env::temp_dir().join(format!("hline-walk-test-{}-{id}", process::id()));
+        fs::create_dir_all(root.join("build")).expect("failed to create test dirs");
+        fs::create_dir_all(root.join("src")).expect("failed to create test dirs");
+        for file in ["a.log", "build/b.log", "src/c.log", "src/d.log"] {
+            fs::write(root.join(file), "hello").expect("failed to create test file");
+        }
+        fs::write(root.join(".gitignore"), "build/\n*.log\n")
+            .expect("failed to create ignore file");
+        fs::write(root.join("src/.ignore"), "!c.log\n").expect("failed to create ignore file");
+
+        let walked = Walk::new(&root)
+            .respect_ignore_files()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("walk failed");
+        let walked_without_hidden = Walk::new(&root)
+            .respect_ignore_files()
+            .skip_hidden()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("walk failed");
+        fs::remove_dir_all(&root).expect("failed to clean up test dir");
+
+        assert_eq!(vec![root.join("src/c.log")], walked_without_hidden);
+        let expected: Vec<PathBuf> = [".gitignore", "src/.ignore", "src/c.log"]
+            .iter()
+            .map(|file| root.join(file))
+            .collect();
+        assert_eq!(expected, walked);
+    }
+}
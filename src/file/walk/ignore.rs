@@ -0,0 +1,170 @@
+use globset::{GlobBuilder, GlobMatcher};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The names of the files that list the paths to ignore within their directory, in increasing order of precedence.
+const IGNORE_FILE_NAMES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// `Ignores` holds the rules from the ignore files in a directory, along with those of the directories above it, which
+/// they take precedence over.
+pub(super) struct Ignores {
+    dir: PathBuf,
+    rules: Vec<Rule>,
+    parent: Option<Arc<Ignores>>,
+}
+
+/// `Rule` is a single pattern from an ignore file, in the format used by `.gitignore`.
+struct Rule {
+    glob: GlobMatcher,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl Ignores {
+    /// Load the rules from the ignore files in the given directory, on top of those of its parent. If there are no
+    /// ignore files in the directory, the parent's rules are returned as they are.
+    pub(super) fn load(
+        dir: &Path,
+        parent: Option<Arc<Ignores>>,
+    ) -> io::Result<Option<Arc<Ignores>>> {
+        let mut contents = String::new();
+        for name in IGNORE_FILE_NAMES {
+            match fs::read(dir.join(name)) {
+                Ok(file_contents) => {
+                    contents.push_str(&String::from_utf8_lossy(&file_contents));
+                    contents.push('\n');
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(Self::parse(dir, &contents, parent))
+    }
+
+    /// Parse the given contents of the ignore files in the given directory, on top of the rules of its parent.
+    fn parse(dir: &Path, contents: &str, parent: Option<Arc<Ignores>>) -> Option<Arc<Ignores>> {
+        let rules: Vec<Rule> = contents.lines().filter_map(Rule::parse).collect();
+        if rules.is_empty() {
+            return parent;
+        }
+
+        Some(Arc::new(Self {
+            dir: dir.to_path_buf(),
+            rules,
+            parent,
+        }))
+    }
+
+    /// Whether the given path, which must be within the directory of these rules, should be ignored.
+    pub(super) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignores = Some(self);
+        while let Some(current) = ignores {
+            if let Some(ignored) = current.matched(path, is_dir) {
+                return ignored;
+            }
+
+            ignores = current.parent.as_deref();
+        }
+
+        false
+    }
+
+    /// Check the given path against the rules from this directory alone. As in `.gitignore`, the last matching rule
+    /// decides whether the path is ignored. `None` is returned if no rule matches.
+    fn matched(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative_path = path.strip_prefix(&self.dir).ok()?;
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(relative_path, is_dir))
+            .map(|rule| !rule.negated)
+    }
+}
+
+impl Rule {
+    /// Parse a line of an ignore file. `None` is returned for blank lines, comments, and invalid patterns, which are
+    /// skipped, as git does.
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, line),
+        };
+        // A leading ! or # can be escaped to match it literally
+        let pattern = pattern
+            .strip_prefix('\\')
+            .filter(|unescaped| unescaped.starts_with(['!', '#']))
+            .unwrap_or(pattern);
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern),
+        };
+
+        // Patterns with a separator are relative to the ignore file's directory, and all others match at any depth
+        let glob = match pattern.strip_prefix('/') {
+            Some(anchored) => anchored.to_string(),
+            None if pattern.contains('/') => pattern.to_string(),
+            None => format!("**/{pattern}"),
+        };
+        if glob.is_empty() {
+            return None;
+        }
+
+        let glob = GlobBuilder::new(&glob)
+            .literal_separator(true)
+            .build()
+            .ok()?
+            .compile_matcher();
+
+        Some(Self {
+            glob,
+            negated,
+            dir_only,
+        })
+    }
+
+    /// Whether this rule matches the given path, relative to the directory of its ignore file.
+    fn matches(&self, relative_path: &Path, is_dir: bool) -> bool {
+        (is_dir || !self.dir_only) && self.glob.is_match(relative_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("*.log", "a.log", false, true; "glob")]
+    #[test_case("*.log", "sub/a.log", false, true; "glob in subdirectory")]
+    #[test_case("*.log", "a.txt", false, false; "glob that does not match")]
+    #[test_case("/a.log", "sub/a.log", false, false; "anchored pattern in subdirectory")]
+    #[test_case("sub/*.log", "sub/nested/a.log", false, false; "star does not match separators")]
+    #[test_case("build/", "build", true, true; "directory pattern")]
+    #[test_case("build/", "build", false, false; "directory pattern for file")]
+    #[test_case("*.log\n!keep.log", "keep.log", false, false; "negated pattern")]
+    #[test_case("!keep.log\n*.log", "keep.log", false, true; "last matching pattern wins")]
+    #[test_case("# a.log\n\\#b.log", "#b.log", false, true; "comment and escaped hash")]
+    fn test_is_ignored(contents: &str, path: &str, is_dir: bool, expected: bool) {
+        let dir = Path::new("root");
+        let ignores = Ignores::parse(dir, contents, None).expect("no rules were parsed");
+
+        assert_eq!(expected, ignores.is_ignored(&dir.join(path), is_dir));
+    }
+
+    #[test]
+    fn test_child_rules_take_precedence() {
+        let parent = Ignores::parse(Path::new("root"), "*.log", None);
+        let child = Ignores::parse(Path::new("root/sub"), "!keep.log", parent)
+            .expect("no rules were parsed");
+
+        assert!(!child.is_ignored(Path::new("root/sub/keep.log"), false));
+        assert!(child.is_ignored(Path::new("root/sub/other.log"), false));
+    }
+}
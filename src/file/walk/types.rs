@@ -0,0 +1,158 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// The file types that are known by default, along with the globs that match the names of files of each type.
+const DEFAULT_TYPES: &[(&str, &[&str])] = &[
+    ("c", &["*.c", "*.h"]),
+    (
+        "cpp",
+        &["*.cpp", "*.cc", "*.cxx", "*.hpp", "*.hh", "*.hxx", "*.h"],
+    ),
+    ("csv", &["*.csv", "*.tsv"]),
+    ("go", &["*.go"]),
+    ("html", &["*.html", "*.htm"]),
+    ("java", &["*.java"]),
+    ("js", &["*.js", "*.mjs", "*.cjs", "*.jsx"]),
+    ("json", &["*.json", "*.jsonl"]),
+    ("log", &["*.log", "*.log.[0-9]*"]),
+    ("markdown", &["*.md", "*.markdown"]),
+    ("py", &["*.py"]),
+    ("ruby", &["*.rb", "Gemfile", "Rakefile"]),
+    ("rust", &["*.rs"]),
+    ("sh", &["*.sh", "*.bash", "*.zsh"]),
+    ("toml", &["*.toml"]),
+    ("ts", &["*.ts", "*.tsx"]),
+    ("txt", &["*.txt"]),
+    ("xml", &["*.xml"]),
+    ("yaml", &["*.yaml", "*.yml"]),
+];
+
+/// `FileTypeError` represents a failure to define or look up a file type.
+#[derive(Error, Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum FileTypeError {
+    /// No file type has the given name.
+    #[error("unknown file type {0}")]
+    UnknownType(String),
+    /// The given definition of a file type was not of the form `name:glob`.
+    #[error("invalid file type definition {0}, which must be of the form name:glob")]
+    InvalidDefinition(String),
+    /// A glob for a file type was not valid.
+    #[error(transparent)]
+    InvalidGlob(#[from] globset::Error),
+}
+
+/// `FileTypes` is a set of named file types, each of which is matched by globs for the names of its files, such as
+/// `rust` for `*.rs`. The default set holds a small number of common types.
+#[derive(Clone, Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct FileTypes {
+    globs_by_type: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for FileTypes {
+    fn default() -> Self {
+        let globs_by_type = DEFAULT_TYPES
+            .iter()
+            .map(|&(name, globs)| {
+                let globs = globs.iter().map(ToString::to_string).collect();
+                (name.to_string(), globs)
+            })
+            .collect();
+
+        Self { globs_by_type }
+    }
+}
+
+impl FileTypes {
+    /// Add a glob to a file type from a definition of the form `name:glob` (e.g. `web:*.html`), making the type if it
+    /// does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the definition is not of that form, or if its glob is invalid.
+    pub fn add(&mut self, definition: &str) -> Result<(), FileTypeError> {
+        let (name, glob) = definition
+            .split_once(':')
+            .filter(|(name, glob)| !name.is_empty() && !glob.is_empty())
+            .ok_or_else(|| FileTypeError::InvalidDefinition(definition.to_string()))?;
+        Glob::new(glob)?;
+
+        self.globs_by_type
+            .entry(name.to_string())
+            .or_default()
+            .push(glob.to_string());
+
+        Ok(())
+    }
+
+    /// Make a set of globs that matches the paths of files of any of the given types, for use with
+    /// [`Walk::include`](super::Walk::include).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the types is not known.
+    pub fn globs<S: AsRef<str>>(&self, names: &[S]) -> Result<GlobSet, FileTypeError> {
+        let mut builder = GlobSetBuilder::new();
+        for name in names {
+            let name = name.as_ref();
+            let globs = self
+                .globs_by_type
+                .get(name)
+                .ok_or_else(|| FileTypeError::UnknownType(name.to_string()))?;
+
+            // The globs are for the names of files, so must match them in any directory
+            for glob in globs {
+                builder.add(Glob::new(&format!("**/{glob}"))?);
+            }
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(&["rust"], "src/main.rs", true; "type in subdirectory")]
+    #[test_case(&["rust"], "main.rs", true; "type in root")]
+    #[test_case(&["rust"], "main.py", false; "different type")]
+    #[test_case(&["rust", "py"], "main.py", true; "any of several types")]
+    #[test_case(&["ruby"], "app/Gemfile", true; "exact file name")]
+    #[test_case(&["log"], "logs/app.log.1", true; "rotated log")]
+    fn test_globs_match_types(names: &[&str], path: &str, expected: bool) {
+        let globs = FileTypes::default()
+            .globs(names)
+            .expect("failed to make globs");
+
+        assert_eq!(expected, globs.is_match(path));
+    }
+
+    #[test]
+    fn test_added_type_is_matched() {
+        let mut types = FileTypes::default();
+        types.add("web:*.html").expect("failed to add type");
+        types.add("web:*.css").expect("failed to add type");
+
+        let globs = types.globs(&["web"]).expect("failed to make globs");
+        assert!(globs.is_match("static/style.css"));
+        assert!(globs.is_match("index.html"));
+    }
+
+    #[test_case("web"; "missing glob")]
+    #[test_case(":*.html"; "missing name")]
+    #[test_case("web:["; "invalid glob")]
+    fn test_invalid_definition_is_rejected(definition: &str) {
+        assert!(FileTypes::default().add(definition).is_err());
+    }
+
+    #[test]
+    fn test_unknown_type_is_rejected() {
+        let res = FileTypes::default().globs(&["not-a-type"]);
+
+        assert!(matches!(res, Err(FileTypeError::UnknownType(name)) if name == "not-a-type"));
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often [`FileWatcher`] will check its file for changes by default.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// `FileWatcher` waits for the file at a given path to change, by periodically checking its modification time and
+/// size.
+///
+/// The file is looked up by its path each time it is checked, so a file that is replaced (e.g. by an editor that saves
+/// by writing a new file and renaming it into place) is still seen as a change. While no file exists at the path, it
+/// is not considered to have changed.
+#[allow(clippy::module_name_repetitions)]
+pub struct FileWatcher {
+    path: PathBuf,
+    poll_interval: Duration,
+    last_seen: Option<FileVersion>,
+}
+
+/// The properties of a file that are compared to tell whether it has changed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct FileVersion {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileWatcher {
+    /// Make a new `FileWatcher` that watches the file at the given path, starting from its current state.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self::with_poll_interval(path, DEFAULT_POLL_INTERVAL)
+    }
+
+    /// Make a new `FileWatcher` that watches the file at the given path, starting from its current state, and checking
+    /// it for changes every `poll_interval`.
+    pub fn with_poll_interval<P: Into<PathBuf>>(path: P, poll_interval: Duration) -> Self {
+        let path = path.into();
+        let last_seen = current_version(&path);
+
+        Self {
+            path,
+            poll_interval,
+            last_seen,
+        }
+    }
+
+    /// Block until the file differs from how it was when it was last seen, either when this `FileWatcher` was made or
+    /// when this was last called.
+    pub fn wait_for_change(&mut self) {
+        loop {
+            let version = current_version(&self.path);
+            if version.is_some() && version != self.last_seen {
+                self.last_seen = version;
+                return;
+            }
+
+            thread::sleep(self.poll_interval);
+        }
+    }
+}
+
+/// Get the current version of the file at the given path, or `None` if it cannot be inspected (e.g. if it does not
+/// exist).
+fn current_version(path: &Path) -> Option<FileVersion> {
+    let metadata = fs::metadata(path).ok()?;
+
+    Some(FileVersion {
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::process;
+
+    #[test]
+    fn test_waits_for_file_to_grow() {
+        let path = env::temp_dir().join(format!("hline-watch-test-{}", process::id()));
+        fs::write(&path, "hello\n").expect("failed to create test file");
+
+        let mut watcher = FileWatcher::with_poll_interval(&path, Duration::ZERO);
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(b"world\n"))
+            .expect("failed to append to test file");
+
+        watcher.wait_for_change();
+        fs::remove_file(&path).expect("failed to clean up test file");
+    }
+}
@@ -0,0 +1,54 @@
+//! `invert` provides utilities to highlight the lines that do _not_ match a pattern.
+use grep::matcher::{Match, Matcher, NoCaptures};
+
+/// `InvertedMatcher` wraps another [`Matcher`] so that it matches every line the wrapped [`Matcher`] does not, and
+/// none of the lines that it does. A matching line is matched in its entirety.
+///
+/// Note that this matcher operates on a line-by-line basis; it must not be used with a multi-line searcher.
+#[allow(clippy::module_name_repetitions)]
+pub struct InvertedMatcher<M: Matcher> {
+    matcher: M,
+}
+
+impl<M: Matcher> InvertedMatcher<M> {
+    /// Make a new `InvertedMatcher` that inverts the given [`Matcher`]
+    pub fn new(matcher: M) -> Self {
+        Self { matcher }
+    }
+}
+
+impl<M: Matcher> Matcher for InvertedMatcher<M> {
+    // There is nothing to capture in a line that didn't match.
+    type Captures = NoCaptures;
+    type Error = M::Error;
+
+    fn find_at(&self, haystack: &[u8], at: usize) -> Result<Option<Match>, Self::Error> {
+        if at > haystack.len() || self.matcher.is_match(haystack)? {
+            return Ok(None);
+        }
+
+        Ok(Some(Match::new(at, haystack.len())))
+    }
+
+    fn new_captures(&self) -> Result<Self::Captures, Self::Error> {
+        Ok(NoCaptures::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grep::regex::RegexMatcher;
+    use test_case::test_case;
+
+    #[test_case(b"hello world", None; "matching line")]
+    #[test_case(b"goodbye", Some(Match::new(0, 7)); "non-matching line")]
+    #[test_case(b"", Some(Match::new(0, 0)); "empty line")]
+    fn test_inverts_matches(line: &[u8], expected: Option<Match>) {
+        let matcher =
+            InvertedMatcher::new(RegexMatcher::new("hello").expect("regexp doesn't compile"));
+        let found = matcher.find(line).expect("failed to match");
+
+        assert_eq!(expected, found);
+    }
+}
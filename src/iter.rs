@@ -0,0 +1,206 @@
+//! `iter` provides a way to consume the results of a scan lazily, as an [`Iterator`], rather than having them pushed to
+//! a [`Printer`](`crate::print::Printer`) or [`Sink`](`grep::searcher::Sink`).
+use crate::sink::{self, CallbackSink, OwnedLineEvent};
+use crate::{Error, Position};
+use grep::matcher::Matcher;
+use grep::regex::RegexMatcher;
+use grep::searcher::{Searcher, SearcherBuilder};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+
+/// The number of bytes of the input that are read before they are scanned. Lines are never split between chunks, so a
+/// chunk may be larger than this if it ends with a long line.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// `ScanLines` is an [`Iterator`] over the lines of a `Read`, yielding an [`OwnedLineEvent`] for each of them, noting
+/// whether it matched and where. The input is read and scanned a chunk of lines at a time, as the events are consumed,
+/// so only as much of it is read as is needed.
+///
+/// If reading the input fails, the events for the lines read before the failure are yielded, followed by the error.
+/// Nothing is yielded after an error.
+#[allow(clippy::module_name_repetitions)]
+pub struct ScanLines<R: Read, M: Matcher = RegexMatcher> {
+    reader: BufReader<R>,
+    matcher: M,
+    searcher: Searcher,
+    chunk: Vec<u8>,
+    events: VecDeque<OwnedLineEvent>,
+    error: Option<Error>,
+    line_number: u64,
+    byte_offset: u64,
+    done: bool,
+}
+
+impl<R: Read, M: Matcher> ScanLines<R, M> {
+    /// Make a new `ScanLines` that scans the given `Read` with the given [`Matcher`].
+    pub fn new(reader: R, matcher: M) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            matcher,
+            searcher: SearcherBuilder::new().passthru(true).build(),
+            chunk: Vec::new(),
+            events: VecDeque::new(),
+            error: None,
+            line_number: 0,
+            byte_offset: 0,
+            done: false,
+        }
+    }
+
+    /// Read the next chunk of lines from the input, and scan it for the events it holds.
+    fn scan_chunk(&mut self) {
+        self.chunk.clear();
+        while self.chunk.len() < CHUNK_SIZE {
+            match self.reader.read_until(b'\n', &mut self.chunk) {
+                Ok(0) => {
+                    self.done = true;
+                    break;
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    self.done = true;
+                    self.error = Some(Error::ReadFailure {
+                        offset: self.byte_offset + self.chunk.len() as u64,
+                        source: err,
+                    });
+                    break;
+                }
+            }
+        }
+
+        let (line_number, byte_offset) = (self.line_number, self.byte_offset);
+        let events = &mut self.events;
+        let num_events_before = events.len();
+        let sink = CallbackSink::with_matcher(&self.matcher, |event| {
+            let mut event = OwnedLineEvent::from(event);
+            // Each chunk is scanned on its own, so its lines are numbered from the start of the chunk
+            event.line_number += line_number;
+            event.byte_offset += byte_offset;
+            events.push_back(event);
+        });
+
+        let mut sink = sink::PositionTrackingSink::new(sink::BinaryQuittingSink::new(sink));
+        let search_res = self
+            .searcher
+            .search_slice(&self.matcher, &self.chunk, &mut sink)
+            .map_err(|err| {
+                let position = sink.position().map(|position| Position {
+                    line_number: position.line_number.map(|n| n + line_number),
+                    byte_offset: position.byte_offset + byte_offset,
+                });
+
+                Error::from_sink_error(err, byte_offset, position)
+            });
+        if let Err(err) = search_res {
+            self.done = true;
+            // An error from the search happened before any read failure could, as it was found in what was read
+            self.error = Some(err);
+        }
+
+        self.line_number += (self.events.len() - num_events_before) as u64;
+        self.byte_offset += self.chunk.len() as u64;
+    }
+}
+
+impl<R: Read, M: Matcher> Iterator for ScanLines<R, M> {
+    type Item = Result<OwnedLineEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Some(Ok(event));
+            } else if let Some(err) = self.error.take() {
+                return Some(Err(err));
+            } else if self.done {
+                return None;
+            }
+
+            self.scan_chunk();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    /// `FailingReader` reads the given data, and then fails.
+    struct FailingReader<'a> {
+        data: &'a [u8],
+    }
+
+    impl Read for FailingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.data.is_empty() {
+                return Err(io::Error::other("read failed"));
+            }
+
+            self.data.read(buf)
+        }
+    }
+
+    #[test]
+    fn test_yields_every_line() {
+        let matcher = RegexMatcher::new("o").expect("regexp doesn't compile");
+        let events = ScanLines::new(b"The quick\r\nbrown fox\njumped".as_slice(), matcher)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("scan failed");
+
+        assert_eq!(
+            vec![
+                ("The quick".to_string(), false, 1, 0, vec![]),
+                ("brown fox".to_string(), true, 2, 11, vec![2..3, 7..8]),
+                ("jumped".to_string(), false, 3, 21, vec![]),
+            ],
+            events
+                .into_iter()
+                .map(|event| (
+                    event.as_event().text().to_string(),
+                    event.matched,
+                    event.line_number,
+                    event.byte_offset,
+                    event.spans
+                ))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_numbers_lines_across_chunks() {
+        let line = "a line that is long enough to fill several chunks with fox\n";
+        let input = line.repeat(CHUNK_SIZE * 3 / line.len());
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let events = ScanLines::new(input.as_bytes(), matcher)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("scan failed");
+
+        assert_eq!(CHUNK_SIZE * 3 / line.len(), events.len());
+        for (i, event) in events.into_iter().enumerate() {
+            assert_eq!(i as u64 + 1, event.line_number);
+            assert_eq!((i * line.len()) as u64, event.byte_offset);
+            assert!(event.matched);
+        }
+    }
+
+    #[test]
+    fn test_yields_read_failure_after_lines_read() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mut scan_lines = ScanLines::new(
+            FailingReader {
+                data: b"brown fox\njumped\n",
+            },
+            matcher,
+        );
+
+        let event = scan_lines.next().expect("no event").expect("scan failed");
+        assert_eq!(b"brown fox", event.line.as_slice());
+        let event = scan_lines.next().expect("no event").expect("scan failed");
+        assert_eq!(b"jumped", event.line.as_slice());
+        match scan_lines.next() {
+            Some(Err(Error::ReadFailure { offset: 17, .. })) => {}
+            other => panic!("expected a read failure, got {other:?}"),
+        }
+        assert!(scan_lines.next().is_none());
+    }
+}
@@ -1,22 +1,49 @@
 #![warn(clippy::all, clippy::pedantic)]
+use file::ReadCounter;
+use grep::matcher::Matcher;
 use grep::regex;
 use grep::regex::RegexMatcher;
-use grep::searcher::SearcherBuilder;
-use print::{Printer, StdoutPrinter};
+use grep::searcher::{Searcher, SearcherBuilder, Sink};
+use print::{LockedStdoutPrinter, Printer};
+use std::fmt;
+use std::fmt::Display;
 use std::io;
-use std::io::Read;
+use std::io::{Read, Write};
+use std::time::Duration;
 use thiserror::Error;
 
+mod ansi;
+pub mod color;
+mod columns;
 pub mod file;
+pub mod invert;
+pub mod iter;
 mod lines;
+pub mod options;
+#[cfg(feature = "lua")]
+pub mod plugin;
+pub mod prefixed;
 pub mod print;
-mod sink;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod rules;
+pub mod sink;
+pub mod style;
+pub mod theme;
+#[cfg(feature = "terminal")]
+pub mod tui;
+
+pub use sink::JsonStats;
 
 #[cfg(test)]
 mod testutil;
 
 /// `Error` represents the possible errors that can occur during the search process.
+///
+/// More variants may be added in the future, and their fields may change, so the accessors such as
+/// [`Error::input_name`] and [`Error::position`] should be preferred over matching on them.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Parsing the given regular expression failed.
     #[error("Regular expression engine failed: {0}")]
@@ -25,24 +52,142 @@ pub enum Error {
         regex::Error,
     ),
     /// The search process encountered a fatal error. This is likely an i/o error, but it is not necessarily.
-    #[error("Search process failed: {0}")]
-    SearchError(
+    #[error("Search process failed{}: {message}", at_position(.position.as_ref()))]
+    SearchError {
         /// An error message provided by the underlying grep library.
-        String,
-    ),
+        message: String,
+        /// The position of the line being handled when the error occurred, if there was one.
+        position: Option<Position>,
+    },
     /// Printing to the given printer failed due to an i/o error. The original error is wrapped in the variant
-    #[error("Printing results failed: {0}")]
-    PrintFailure(
+    #[error("Printing results failed{}: {source}", at_position(.position.as_ref()))]
+    PrintFailure {
         /// The original i/o error that caused the print failure.
-        io::Error,
+        source: io::Error,
+        /// The position of the line being printed when the error occurred, if there was one.
+        position: Option<Position>,
+    },
+    /// Reading from the given [`Read`] failed due to an i/o error.
+    #[error("Reading input failed at byte {offset}: {source}")]
+    ReadFailure {
+        /// The number of bytes that were successfully read from the input before the failure.
+        offset: u64,
+        /// The original i/o error that caused the read failure.
+        source: io::Error,
+    },
+    /// The plugin that lines were passed through failed.
+    #[cfg(feature = "lua")]
+    // The plugin's errors already say that they came from the plugin
+    #[error("{0}")]
+    PluginFailure(
+        /// The original error produced by the plugin.
+        plugin::Error,
     ),
+    /// Binary data was found in the input, so scanning stopped before it. This is only reported when scanning with
+    /// [`ScanOptions::quit_on_binary`](`options::ScanOptions::quit_on_binary`).
+    #[error("Binary data found at byte {offset}; stopped scanning")]
+    BinaryData {
+        /// The byte offset in the input at which the binary data was found.
+        offset: u64,
+    },
+    /// Scanning the named input failed. This is only reported when scanning with
+    /// [`ScanOptions::input_name`](`options::ScanOptions::input_name`), so that the inputs of a scan of several can be
+    /// told apart.
+    #[error("{name}: {source}")]
+    Input {
+        /// The name of the input, such as its path.
+        name: String,
+        /// The error that scanning the input failed with.
+        source: Box<Error>,
+    },
 }
 
-impl From<sink::Error> for Error {
-    fn from(err: sink::Error) -> Self {
+/// `Position` is a place in the input being scanned, such as where an [`Error`] occurred.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Position {
+    /// The number of the line, starting from 1, if line numbers were being counted.
+    pub line_number: Option<u64>,
+    /// The offset of the start of the line from the start of the input, in bytes.
+    pub byte_offset: u64,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line_number {
+            Some(line_number) => write!(f, "line {line_number} (byte {})", self.byte_offset),
+            None => write!(f, "byte {}", self.byte_offset),
+        }
+    }
+}
+
+/// Describe where an error happened, if it is known, to follow its description.
+fn at_position(position: Option<&Position>) -> String {
+    position.map_or_else(String::new, |position| format!(" at {position}"))
+}
+
+impl Error {
+    /// Convert the given [`sink::Error`] into an [`Error`], given the number of bytes that had been read from the input
+    /// at the time it occurred, and the position of the line that was being handled, if any.
+    fn from_sink_error(err: sink::Error, bytes_read: u64, position: Option<Position>) -> Self {
         match err {
-            sink::Error::SearchError(msg) => Error::SearchError(msg),
-            sink::Error::PrintFailed(io_err) => Error::PrintFailure(io_err),
+            sink::Error::SearchError(message) => Error::SearchError { message, position },
+            sink::Error::PrintFailed(source) => Error::PrintFailure { source, position },
+            sink::Error::ReadFailed(io_err) => Error::ReadFailure {
+                offset: bytes_read,
+                source: io_err,
+            },
+            #[cfg(feature = "lua")]
+            sink::Error::PluginFailed(plugin_err) => Error::PluginFailure(plugin_err),
+            sink::Error::BinaryData(offset) => Error::BinaryData { offset },
+        }
+    }
+
+    /// Attach the name of the input being scanned to this error, as an [`Error::Input`].
+    #[must_use]
+    pub fn in_input(self, name: &str) -> Self {
+        Error::Input {
+            name: name.to_string(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Get the name of the input that was being scanned when this error occurred, if it was given.
+    #[must_use]
+    pub fn input_name(&self) -> Option<&str> {
+        match self {
+            Error::Input { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Get the position in the input at which this error occurred, if it is known. Failures to read, and binary data,
+    /// are only known by their byte offset.
+    #[must_use]
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Error::RegexError(_) => None,
+            Error::SearchError { position, .. } | Error::PrintFailure { position, .. } => *position,
+            Error::ReadFailure { offset, .. } | Error::BinaryData { offset } => Some(Position {
+                line_number: None,
+                byte_offset: *offset,
+            }),
+            #[cfg(feature = "lua")]
+            Error::PluginFailure(_) => None,
+            Error::Input { source, .. } => source.position(),
+        }
+    }
+
+    /// Get the i/o error that caused this error, if there was one, such as when reading the input or printing the
+    /// results failed.
+    #[must_use]
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            Error::PrintFailure { source, .. } | Error::ReadFailure { source, .. } => Some(source),
+            Error::Input { source, .. } => source.io_error(),
+            #[cfg(feature = "lua")]
+            Error::PluginFailure(_) => None,
+            Error::RegexError(_) | Error::SearchError { .. } | Error::BinaryData { .. } => None,
         }
     }
 }
@@ -61,7 +206,7 @@ impl From<regex::Error> for Error {
 ///
 /// See [`scan_pattern_to_printer`]
 pub fn scan_pattern<R: Read>(reader: R, pattern: &str) -> Result<(), Error> {
-    scan_pattern_to_printer(reader, pattern, StdoutPrinter::new())
+    scan_pattern_to_printer(reader, pattern, LockedStdoutPrinter::new())
 }
 
 /// `scan_pattern_to_printer` will print a `Read`'s contents to the given `Printer`, while also scanning its contents
@@ -75,8 +220,7 @@ pub fn scan_pattern<R: Read>(reader: R, pattern: &str) -> Result<(), Error> {
 /// There are four general error cases
 /// - An invalid regular expression
 /// - An error produced by the underlying grep library during the search
-/// - I/O errors in scanning from the [`Read`] (these will be manifested as search errors, as the search process is what
-///   performs the reading).
+/// - I/O errors in scanning from the [`Read`]. These will report the byte offset at which reading failed.
 /// - A failure to print to the given printer
 ///
 /// See [enum@Error] for more details.
@@ -86,19 +230,409 @@ pub fn scan_pattern_to_printer<R: Read, P: Printer>(
     printer: P,
 ) -> Result<(), Error> {
     let matcher = RegexMatcher::new(pattern)?;
-    let mut searcher = SearcherBuilder::new().passthru(true).build();
-    let context_sink = sink::ContextPrintingSink::new(printer);
+    scan_matcher_to_printer(reader, matcher, printer)
+}
+
+/// `scan_matcher_to_printer` is identical to [`scan_pattern_to_printer`], except that lines are matched with the given
+/// [`Matcher`], rather than a regular expression. This allows for customizing how lines are matched, such as with a
+/// [`PrefixedMatcher`](`prefixed::PrefixedMatcher`).
+///
+/// Note that the searcher used will always be line-oriented.
+///
+/// # Errors
+///
+/// See [`scan_pattern_to_printer`], with the exception that there will not be a regular expression error.
+pub fn scan_matcher_to_printer<R: Read, M: Matcher, P: Printer>(
+    reader: R,
+    matcher: M,
+    printer: P,
+) -> Result<(), Error> {
+    scan_matcher_to_sink(reader, matcher, sink::ContextPrintingSink::new(printer))
+}
+
+/// `scan_pattern_to_sink` will scan a `Read`'s contents for a regular expression, giving the search results to the
+/// provided [`Sink`]. Like [`scan_matcher_to_sink`], every line of the input is given to the [`Sink`], so it may be
+/// one of your own, or one that forwards to a printing [`Sink`] too, such as [`TeeSink`](`sink::TeeSink`).
+///
+/// # Errors
+///
+/// See [`scan_pattern_to_printer`]. Any errors produced by the given [`Sink`] are also returned.
+pub fn scan_pattern_to_sink<R: Read, S: Sink<Error = sink::Error>>(
+    reader: R,
+    pattern: &str,
+    sink: S,
+) -> Result<(), Error> {
+    let matcher = RegexMatcher::new(pattern)?;
+    scan_matcher_to_sink(reader, matcher, sink)
+}
+
+/// `scan_matcher_to_sink` is identical to [`scan_matcher_to_printer`], except that the search results are given to the
+/// provided [`Sink`]. This allows for extending the printing behavior, by wrapping a
+/// [`ContextPrintingSink`](`sink::ContextPrintingSink`) in another [`Sink`] that forwards to it, such as
+/// [`MatchCountingSink`](`sink::MatchCountingSink`). Any number of sinks may be chained in this way.
+///
+/// The searcher used will have passthru enabled, so the given [`Sink`] will receive every line of the input, with
+/// non-matching lines provided as context.
+///
+/// # Errors
+///
+/// See [`scan_matcher_to_printer`]. Any errors produced by the given [`Sink`] are also returned.
+pub fn scan_matcher_to_sink<R: Read, M: Matcher, S: Sink<Error = sink::Error>>(
+    reader: R,
+    matcher: M,
+    sink: S,
+) -> Result<(), Error> {
+    let searcher = SearcherBuilder::new().passthru(true).build();
+    search_reader(searcher, reader, matcher, sink)
+}
+
+/// Search the given slice with the given [`Searcher`], converting any error into an [`Error`].
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(len = slice.len()), err))]
+pub(crate) fn search_slice<M: Matcher, S: Sink<Error = sink::Error>>(
+    mut searcher: Searcher,
+    slice: &[u8],
+    matcher: M,
+    sink: S,
+) -> Result<(), Error> {
+    let mut sink = sink::PositionTrackingSink::new(sink::BinaryQuittingSink::new(sink));
+    // Nothing is read from a slice, so there's no offset for a read failure to have happened at
+    searcher
+        .search_slice(matcher, slice, &mut sink)
+        .map_err(|err| Error::from_sink_error(err, 0, sink.position()))
+}
+
+/// Search the given `Read` with the given [`Searcher`], converting any error into an [`Error`].
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, err)
+)]
+pub(crate) fn search_reader<R: Read, M: Matcher, S: Sink<Error = sink::Error>>(
+    mut searcher: Searcher,
+    reader: R,
+    matcher: M,
+    sink: S,
+) -> Result<(), Error> {
+    let mut counted_reader = ReadCounter::new(reader);
+    let mut sink = sink::PositionTrackingSink::new(sink::BinaryQuittingSink::new(sink));
+
+    searcher
+        .search_reader(matcher, &mut counted_reader, &mut sink)
+        .map_err(|err| Error::from_sink_error(err, counted_reader.bytes_read(), sink.position()))?;
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        bytes_read = counted_reader.bytes_read(),
+        "finished reading input"
+    );
 
-    searcher.search_reader(matcher, reader, context_sink)?;
     Ok(())
 }
 
+/// `scan_pattern_with_callback` will scan a `Read`'s contents for a regular expression, calling the given function
+/// with a [`LineEvent`](`sink::LineEvent`) for every line of the input. Each event holds the line's contents, whether it
+/// matched, its line number, its byte offset, and the spans of any matches within it. This allows the results to be
+/// consumed programmatically, rather than printed, such as by editors and indexers.
+///
+/// ```
+/// let mut matching_lines = Vec::new();
+/// hline::scan_pattern_with_callback("hello\nworld\n".as_bytes(), "world", |event| {
+///     if event.matched {
+///         matching_lines.push(event.line_number);
+///     }
+/// })
+/// .expect("scan failed");
+///
+/// assert_eq!(vec![2], matching_lines);
+/// ```
+///
+/// # Errors
+///
+/// See [`scan_pattern_to_printer`], with the exception that there will be no print failures.
+pub fn scan_pattern_with_callback<R: Read, F: FnMut(&sink::LineEvent)>(
+    reader: R,
+    pattern: &str,
+    callback: F,
+) -> Result<(), Error> {
+    let matcher = RegexMatcher::new(pattern)?;
+    scan_matcher_with_callback(reader, matcher, callback)
+}
+
+/// `scan_matcher_with_callback` is identical to [`scan_pattern_with_callback`], except that lines are matched with the
+/// given [`Matcher`], rather than a regular expression.
+///
+/// # Errors
+///
+/// See [`scan_pattern_with_callback`], with the exception that there will not be a regular expression error.
+pub fn scan_matcher_with_callback<R: Read, M: Matcher, F: FnMut(&sink::LineEvent)>(
+    reader: R,
+    matcher: M,
+    callback: F,
+) -> Result<(), Error> {
+    let sink = sink::CallbackSink::with_matcher(&matcher, callback);
+    scan_matcher_to_sink(reader, &matcher, sink)
+}
+
+/// `scan_lines` will scan a `Read`'s contents for a regular expression, returning an [`Iterator`] that yields an
+/// [`OwnedLineEvent`](`sink::OwnedLineEvent`) for every line of the input, like those given by
+/// [`scan_pattern_with_callback`]. The input is only read as the events are consumed, so the results can be processed
+/// with ordinary iterator adapters, and scanning stops early if they are no longer needed.
+///
+/// ```
+/// let matching_lines = hline::scan_lines("hello\nworld\n".as_bytes(), "world")
+///     .expect("pattern is invalid")
+///     .filter_map(Result::ok)
+///     .filter(|event| event.matched)
+///     .map(|event| event.line_number)
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(vec![2], matching_lines);
+/// ```
+///
+/// # Errors
+///
+/// An error is returned if the pattern is not a valid regular expression. Otherwise, the iterator will yield any errors
+/// that happen while scanning, as described by [`scan_pattern_to_printer`], with the exception that there will be no
+/// print failures. Nothing is yielded after an error.
+pub fn scan_lines<R: Read>(reader: R, pattern: &str) -> Result<iter::ScanLines<R>, Error> {
+    let matcher = RegexMatcher::new(pattern)?;
+    Ok(scan_matcher_lines(reader, matcher))
+}
+
+/// `scan_matcher_lines` is identical to [`scan_lines`], except that lines are matched with the given [`Matcher`],
+/// rather than a regular expression. As such, it cannot fail until the iterator is consumed.
+pub fn scan_matcher_lines<R: Read, M: Matcher>(reader: R, matcher: M) -> iter::ScanLines<R, M> {
+    iter::ScanLines::new(reader, matcher)
+}
+
+/// `scan_rules_to_printer` will print a `Read`'s contents to the given `Printer`, handling each line as directed by the
+/// first of the given [`Rules`](`rules::Rules`) that matches it. Lines that match no rule are printed as-is.
+/// [`ScanStats`](`sink::ScanStats`) about the scan are returned, where the matching lines are those that matched a rule.
+///
+/// # Errors
+///
+/// See [`scan_pattern_to_printer`], with the exception that there will not be a regular expression error; these are
+/// reported when the rules are made.
+pub fn scan_rules_to_printer<R: Read, P: Printer>(
+    reader: R,
+    rules: &rules::Rules,
+    printer: P,
+) -> Result<sink::ScanStats, Error> {
+    let mut sink = sink::StatsSink::new(sink::RulesSink::new(rules, printer));
+    scan_matcher_to_sink(reader, rules, &mut sink)?;
+
+    Ok(sink.stats())
+}
+
+/// `scan_pattern_with_plugin_to_printer` is identical to [`scan_pattern_to_printer`], except that each line is passed
+/// through the given [`LuaPlugin`](`plugin::LuaPlugin`) before it is printed, which may replace it, change its
+/// highlighting, or drop it altogether. [`ScanStats`](`sink::ScanStats`) about the scan are returned.
+///
+/// # Errors
+///
+/// See [`scan_pattern_to_printer`]. Errors raised by the plugin are also returned.
+#[cfg(feature = "lua")]
+pub fn scan_pattern_with_plugin_to_printer<R: Read, P: Printer>(
+    reader: R,
+    pattern: &str,
+    plugin: &plugin::LuaPlugin,
+    printer: P,
+) -> Result<sink::ScanStats, Error> {
+    let matcher = RegexMatcher::new(pattern)?;
+    scan_matcher_with_plugin_to_printer(reader, matcher, plugin, printer)
+}
+
+/// `scan_matcher_with_plugin_to_printer` is identical to [`scan_pattern_with_plugin_to_printer`], except that lines are
+/// matched with the given [`Matcher`], rather than a regular expression.
+///
+/// # Errors
+///
+/// See [`scan_pattern_with_plugin_to_printer`], with the exception that there will not be a regular expression error.
+#[cfg(feature = "lua")]
+pub fn scan_matcher_with_plugin_to_printer<R: Read, M: Matcher, P: Printer>(
+    reader: R,
+    matcher: M,
+    plugin: &plugin::LuaPlugin,
+    printer: P,
+) -> Result<sink::ScanStats, Error> {
+    let mut sink = sink::StatsSink::new(sink::PluginSink::new(&matcher, plugin, printer));
+    scan_matcher_to_sink(reader, &matcher, &mut sink)?;
+
+    Ok(sink.stats())
+}
+
+/// `scan_pattern_to_csv` will scan a `Read`'s contents for a regular expression, and write each matching line to the
+/// given [`Write`] as a row of CSV. Lines that do not match are not written.
+///
+/// Each row has the columns `file,line_number,byte_offset,matched_text,full_line`, preceded by a header row.
+/// `source_name` is used to fill in the `file` column, and `matched_text` holds the first match on the line.
+/// Fields are quoted as per RFC 4180.
+///
+/// # Errors
+///
+/// See [`scan_pattern_to_printer`]. Failing to write to the given [`Write`] is reported as a print failure.
+pub fn scan_pattern_to_csv<R: Read, W: Write>(
+    reader: R,
+    pattern: &str,
+    source_name: &str,
+    writer: W,
+) -> Result<(), Error> {
+    let matcher = RegexMatcher::new(pattern)?;
+    scan_matcher_to_csv(reader, matcher, source_name, writer)
+}
+
+/// `write_csv_header` writes the header row of the CSV produced by [`scan_pattern_to_csv`] to the given [`Write`].
+/// This is useful along with [`scan_matcher_to_csv_rows`], to write the rows for multiple inputs under one header.
+///
+/// # Errors
+///
+/// Returns [`Error::PrintFailure`] if writing to the given [`Write`] fails.
+pub fn write_csv_header<W: Write>(mut writer: W) -> Result<(), Error> {
+    writer
+        .write_all(sink::CSV_HEADER.as_bytes())
+        .map_err(|source| Error::PrintFailure {
+            source,
+            position: None,
+        })
+}
+
+/// `scan_matcher_to_csv` is identical to [`scan_pattern_to_csv`], except that lines are matched with the given
+/// [`Matcher`], rather than a regular expression.
+///
+/// # Errors
+///
+/// See [`scan_pattern_to_csv`], with the exception that there will not be a regular expression error.
+pub fn scan_matcher_to_csv<R: Read, M: Matcher, W: Write>(
+    reader: R,
+    matcher: M,
+    source_name: &str,
+    mut writer: W,
+) -> Result<(), Error> {
+    write_csv_header(&mut writer)?;
+    scan_matcher_to_csv_rows(reader, matcher, source_name, writer)
+}
+
+/// `scan_matcher_to_csv_rows` is identical to [`scan_matcher_to_csv`], except that the header row is not written.
+///
+/// # Errors
+///
+/// See [`scan_matcher_to_csv`].
+pub fn scan_matcher_to_csv_rows<R: Read, M: Matcher, W: Write>(
+    reader: R,
+    matcher: M,
+    source_name: &str,
+    writer: W,
+) -> Result<(), Error> {
+    let searcher = SearcherBuilder::new().line_number(true).build();
+    let csv_sink = sink::CsvSink::new(&matcher, source_name, writer);
+
+    search_reader(searcher, reader, &matcher, csv_sink)
+}
+
+/// `scan_pattern_to_vimgrep` will scan a `Read`'s contents for a regular expression, and write a line of the form
+/// `file:line:column:text` to the given [`Write`] for each match, as `vim`'s `:vimgrep` does. This allows the results to
+/// be loaded into the quickfix list of an editor. Lines that do not match are not written.
+///
+/// `source_name` is used to fill in `file`, and `column` is the byte offset of the match within its line, starting
+/// from 1. `text` is the whole line, without its line terminator.
+///
+/// # Errors
+///
+/// See [`scan_pattern_to_printer`]. Failing to write to the given [`Write`] is reported as a print failure.
+pub fn scan_pattern_to_vimgrep<R: Read, W: Write>(
+    reader: R,
+    pattern: &str,
+    source_name: &str,
+    writer: W,
+) -> Result<(), Error> {
+    let matcher = RegexMatcher::new(pattern)?;
+    scan_matcher_to_vimgrep(reader, matcher, source_name, writer)
+}
+
+/// `scan_matcher_to_vimgrep` is identical to [`scan_pattern_to_vimgrep`], except that lines are matched with the given
+/// [`Matcher`], rather than a regular expression.
+///
+/// # Errors
+///
+/// See [`scan_pattern_to_vimgrep`], with the exception that there will not be a regular expression error.
+pub fn scan_matcher_to_vimgrep<R: Read, M: Matcher, W: Write>(
+    reader: R,
+    matcher: M,
+    source_name: &str,
+    writer: W,
+) -> Result<(), Error> {
+    let searcher = SearcherBuilder::new().line_number(true).build();
+    let vimgrep_sink = sink::VimgrepSink::new(&matcher, source_name, writer);
+
+    search_reader(searcher, reader, &matcher, vimgrep_sink)
+}
+
+/// `scan_pattern_to_json` will scan a `Read`'s contents for a regular expression, and write every line to the given
+/// [`Write`] as a JSON object, each on its own line, in the format of ripgrep's `--json` output. This allows the
+/// results to be consumed by other tools, including those that already read ripgrep's output.
+///
+/// A `begin` message is written first, followed by a `match` or `context` message for each line, and an `end` message
+/// holding the statistics of the scan, which are also returned. `source_name` is used as the `path` of each message.
+/// To finish the output as ripgrep does, write a `summary` message with [`write_json_summary`] once every input has
+/// been scanned.
+///
+/// # Errors
+///
+/// See [`scan_pattern_to_printer`]. Failing to write to the given [`Write`] is reported as a print failure.
+pub fn scan_pattern_to_json<R: Read, W: Write>(
+    reader: R,
+    pattern: &str,
+    source_name: &str,
+    writer: W,
+) -> Result<JsonStats, Error> {
+    let matcher = RegexMatcher::new(pattern)?;
+    scan_matcher_to_json(reader, matcher, source_name, writer)
+}
+
+/// `scan_matcher_to_json` is identical to [`scan_pattern_to_json`], except that lines are matched with the given
+/// [`Matcher`], rather than a regular expression.
+///
+/// # Errors
+///
+/// See [`scan_pattern_to_json`], with the exception that there will not be a regular expression error.
+pub fn scan_matcher_to_json<R: Read, M: Matcher, W: Write>(
+    reader: R,
+    matcher: M,
+    source_name: &str,
+    writer: W,
+) -> Result<JsonStats, Error> {
+    let searcher = SearcherBuilder::new()
+        .passthru(true)
+        .line_number(true)
+        .build();
+    let mut json_sink = sink::JsonSink::new(&matcher, source_name, writer);
+    search_reader(searcher, reader, &matcher, &mut json_sink)?;
+
+    Ok(json_sink.stats())
+}
+
+/// `write_json_summary` writes the `summary` message that ends ripgrep's `--json` output to the given [`Write`], with
+/// the statistics of every scan made with [`scan_pattern_to_json`] added together, and the total time taken.
+///
+/// # Errors
+///
+/// Returns [`Error::PrintFailure`] if writing to the given [`Write`] fails.
+pub fn write_json_summary<W: Write>(
+    writer: W,
+    stats: JsonStats,
+    elapsed_total: Duration,
+) -> Result<(), Error> {
+    sink::write_json_summary(writer, stats, elapsed_total).map_err(|source| Error::PrintFailure {
+        source,
+        position: None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::testutil;
     use std::io;
-    use std::io::Cursor;
+    use std::io::{Cursor, Read};
     use test_case::test_case;
     use testutil::mock_print::MockPrinter;
 
@@ -126,7 +660,7 @@ mod tests {
             &mock_printer,
         );
         if let Err(err) = res {
-            panic!("failed to search: {}", err)
+            panic!("failed to search: {err}")
         }
 
         let colored_messages = mock_printer.colored_messages.borrow();
@@ -163,7 +697,7 @@ mod tests {
         // but it does make sure the functionality works as expected
         let res = scan_pattern_to_printer(&mut lipsum_reader, "(?i)INTEGRAL", &mock_printer);
         if let Err(err) = res {
-            panic!("failed to search: {}", err)
+            panic!("failed to search: {err}")
         }
 
         let colored_messages = mock_printer.colored_messages.borrow();
@@ -192,6 +726,52 @@ mod tests {
         testutil::assert_slices_eq!(&uncolored_messages, &expected_uncolored_messages);
     }
 
+    // A reader that fails once all of its data has been read
+    struct FailingReader<R: Read>(R);
+
+    impl<R: Read> Read for FailingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.read(buf)? {
+                0 => Err(io::Error::other("disk on fire")),
+                n => Ok(n),
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_failure_reports_offset() {
+        let mock_printer = MockPrinter::default();
+        let failing_reader = FailingReader(Cursor::new(SEARCH_TEXT));
+        let res = scan_pattern_to_printer(failing_reader, "computable", &mock_printer);
+
+        match res {
+            Err(Error::ReadFailure { offset, .. }) => assert_eq!(offset, SEARCH_TEXT.len() as u64),
+            other => panic!("expected read failure, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_accessors_see_through_input_name() {
+        let err = Error::ReadFailure {
+            offset: 5,
+            source: io::Error::other("disk on fire"),
+        }
+        .in_input("app.log");
+
+        assert_eq!(Some("app.log"), err.input_name());
+        assert_eq!(
+            Some(Position {
+                line_number: None,
+                byte_offset: 5
+            }),
+            err.position()
+        );
+        assert_eq!(
+            "disk on fire",
+            err.io_error().expect("no i/o error").to_string()
+        );
+    }
+
     #[test_case(".", 0, 1; "failure on first match will only attempt to print that match")]
     #[test_case("hello I am alan turing", 1, 0; "never matching will only attempt to print the first line")]
     fn test_does_not_attempt_to_print_after_broken_pipe_error(
@@ -206,7 +786,7 @@ mod tests {
         let mut lipsum_reader = Cursor::new(SEARCH_TEXT);
         let res = scan_pattern_to_printer(&mut lipsum_reader, pattern, &mock_printer);
 
-        assert!(!res.is_err(), "failed to search: {:?}", res.unwrap_err());
+        assert!(res.is_ok(), "failed to search: {:?}", res.unwrap_err());
         assert_eq!(
             num_colored_messages,
             mock_printer.colored_messages.borrow().len()
@@ -1,14 +1,14 @@
-//! `lines` provides utilities for processing lines of strings
+//! `lines` provides utilities for processing lines of text
 
-/// `line_split` is an extremely similar iterator to [`str::lines`], but with one key difference: it provides the line
-/// character type it split on (the second element in the returned tuple). This way, one can reconstruct the original
-/// string when joining. If the line was not terminated by a newline (i.e. when it's at the end of a file), the second
+/// `line_split` is an extremely similar iterator to [`str::lines`], but over bytes, and with one key difference: it
+/// provides the line character type it split on (the second element in the returned tuple). This way, one can
+/// reconstruct the original bytes when joining. If the line was not terminated by a newline (i.e. when it's at the end of a file), the second
 /// tuple element will be None.
-pub(crate) fn line_split<'a>(s: &'a str) -> impl Iterator<Item = (&str, Option<&str>)> + 'a {
+pub(crate) fn line_split(s: &[u8]) -> impl Iterator<Item = (&[u8], Option<&[u8]>)> + '_ {
     // We could probably make this more efficient, but it would involve mostly re-implementing `split`.
     // I did some poking around, and this method is generally called for split_components.len() <= 2, so I'm not
     // too worried
-    let split_components: Vec<&str> = s.split('\n').collect();
+    let split_components: Vec<&[u8]> = s.split(|&b| b == b'\n').collect();
     let num_split_components = split_components.len();
 
     split_components
@@ -22,18 +22,36 @@ pub(crate) fn line_split<'a>(s: &'a str) -> impl Iterator<Item = (&str, Option<&
             } else if component.is_empty() {
                 // If there's an empty component that _isn't_ the last component, it's going to be followed by a newline
                 // (an \r\n terminated line will be non-empty).
-                return (component, Some("\n"));
+                return (component, Some(b"\n".as_slice()));
             }
 
             let len = component.len();
-            if component.as_bytes()[len - 1] == b'\r' {
-                (&component[0..len - 1], Some("\r\n"))
+            if component[len - 1] == b'\r' {
+                (&component[0..len - 1], Some(b"\r\n".as_slice()))
             } else {
-                (component, Some("\n"))
+                (component, Some(b"\n".as_slice()))
             }
         })
 }
 
+/// `without_terminator` strips the line terminator (either `\n` or `\r\n`) from the end of the given line, if it has
+/// one.
+pub(crate) fn without_terminator(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// `without_line_terminator` strips the given line terminator from the end of the given line, if it has one. If the
+/// terminator is `\n`, a preceding `\r` is stripped as well, as with [`without_terminator`].
+pub(crate) fn without_line_terminator(line: &[u8], line_terminator: u8) -> &[u8] {
+    if line_terminator == b'\n' {
+        return without_terminator(line);
+    }
+
+    line.strip_suffix(&[line_terminator]).unwrap_or(line)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,7 +94,20 @@ mod tests {
         "carriage return alone isn't significant"
     )]
     fn test_splits_on_newlines(s: &str, expected: &[(&str, Option<&str>)]) {
-        let collected: Vec<(&str, Option<&str>)> = line_split(s).collect();
+        let collected: Vec<(&str, Option<&str>)> = line_split(s.as_bytes())
+            .map(|(component, newline)| {
+                let as_str = |bytes| std::str::from_utf8(bytes).expect("not utf-8");
+                (as_str(component), newline.map(as_str))
+            })
+            .collect();
         testutil::assert_slices_eq!(&expected, &collected);
     }
+
+    #[test_case(b"hello\r\n", b'\n', b"hello"; "crlf")]
+    #[test_case(b"hello\0", b'\0', b"hello"; "nul")]
+    #[test_case(b"hello\r\0", b'\0', b"hello\r"; "carriage return is kept with other terminators")]
+    #[test_case(b"hello", b'\0', b"hello"; "unterminated")]
+    fn test_strips_line_terminator(line: &[u8], line_terminator: u8, expected: &[u8]) {
+        assert_eq!(expected, without_line_terminator(line, line_terminator));
+    }
 }
@@ -1,37 +1,73 @@
 #![warn(clippy::all, clippy::pedantic)]
-use clap::{crate_name, crate_version, App, AppSettings, Arg, ArgMatches};
-use hline::file;
-use hline::file::ReadRecorder;
-use std::env;
+use cli::app::setup_arg_parser;
+use cli::args::{Args, Count, Follow, Matching, OutputFormat, PassedFile};
+use cli::notify::Notifier;
+use cli::output::{filename_terminator, scan_opened_file, Matcher, Plugin, Scan};
+use cli::run_stats::{LineCountingReader, RunStats};
+use hline::color::{Fg, LightRed, LightYellow};
+use hline::file::{
+    ChainReader, DecodeReader, DecompressReader, FileWatcher, FollowReader, ReopeningFile,
+    StringsReader, Utf16,
+};
+use hline::print;
+use hline::print::{PlainPrinter, Printer, StderrPrinter, StdoutPrinter};
+use hline::rules;
+use hline::rules::{Action, Rule, Rules};
+use hline::style::{Bold, Reset, Style};
+use hline::tui::Pager;
+use hline::JsonStats;
+use std::cell::Cell;
 use std::fmt::Display;
-use std::fs::File;
+use std::fs;
+use std::fs::{File, OpenOptions};
 use std::io;
-use std::io::{Read, Seek, Stdin};
+use std::io::{BufRead, BufReader, IsTerminal, Read, Seek, Stdin, Write};
+use std::path::Path;
 use std::process;
-use termion::color::{Fg, LightRed, Reset};
+use std::sync::Arc;
+use std::time::Instant;
 
-const FILENAME_ARG_NAME: &str = "filename";
-const PATTERN_ARG_NAME: &str = "pattern";
-const CASE_INSENSITIVE_ARG_NAME: &str = "case-insensitive";
-const OK_IF_BINARY_ARG_NAME: &str = "ok-if-binary";
+mod cli;
+
+/// The escape sequence printed with `--watch` to clear the terminal and move the cursor to its top-left corner before
+/// each rescan.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// The exit code used when binary data is found in the input.
+const BINARY_FILE_EXIT_CODE: i32 = 5;
+
+/// The exit code used when stdout is closed before everything is printed, if the program cannot be terminated by
+/// SIGPIPE instead. This is the code that shells report for programs terminated by SIGPIPE.
+const BROKEN_PIPE_EXIT_CODE: i32 = 128 + 13;
+
+/// The exit code used when the program is interrupted with Ctrl-C. This is the code that shells report for programs
+/// terminated by SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 128 + 2;
+
+/// The exit code used for all failures in `--lessopen` mode. `less` will fall back to displaying the original file
+/// if the filter fails, so there is no value in distinguishing between failures.
+const LESSOPEN_FAILURE_EXIT_CODE: i32 = 1;
 
 /// `OpenedFile` represents some kind of file that was opened for further handling by `hl`
 enum OpenedFile {
-    Stdin(ReadRecorder<Stdin>),
+    Stdin(DecodeReader<BufReader<Stdin>>),
     File(File),
+    // Files that must be transcoded, or that cannot seek (e.g. FIFOs), are read through a decoder like stdin
+    DecodedFile(DecodeReader<BufReader<File>>),
+    Followed(FollowReader<File>),
+    FollowedByName(FollowReader<ReopeningFile>),
+    DecompressedStdin(DecompressReader<BufReader<Stdin>>),
+    DecompressedFile(DecompressReader<BufReader<File>>),
+    // Files are counted through this when their stats are reported on SIGUSR1
+    LineCounted(LineCountingReader<Box<OpenedFile>>),
+    Concatenated(ChainReader<OpenedFile>),
+    Strings(StringsReader<Box<OpenedFile>>),
 }
 
-/// `PassedFile` represents some kind of file that will be passed in an argument
-enum PassedFile {
-    Stdin,
-    Path(String),
-}
-
-/// `Args` represents arguments passed to the program
-struct Args {
-    pattern: String,
-    file: PassedFile,
-    ok_if_binary_file: bool,
+/// `Failure` represents a failure that should be reported to the user, along with the code to exit with
+struct Failure {
+    message: String,
+    exit_code: i32,
 }
 
 impl Read for OpenedFile {
@@ -40,171 +76,620 @@ impl Read for OpenedFile {
             // TODO: If more variants are ever added this could probably be a macro
             Self::Stdin(read) => read.read(buf),
             Self::File(read) => read.read(buf),
+            Self::DecodedFile(read) => read.read(buf),
+            Self::Followed(read) => read.read(buf),
+            Self::FollowedByName(read) => read.read(buf),
+            Self::DecompressedStdin(read) => read.read(buf),
+            Self::DecompressedFile(read) => read.read(buf),
+            Self::LineCounted(read) => read.read(buf),
+            Self::Concatenated(read) => read.read(buf),
+            Self::Strings(read) => read.read(buf),
         }
     }
 }
 
-impl From<ArgMatches<'_>> for Args {
-    fn from(args: ArgMatches) -> Self {
-        let case_insensitive = args.is_present(CASE_INSENSITIVE_ARG_NAME);
-        let ok_if_binary_file = args.is_present(OK_IF_BINARY_ARG_NAME);
-        let pattern = args
-            .value_of(PATTERN_ARG_NAME)
-            .map(|pat| {
-                if case_insensitive {
-                    make_pattern_case_insensitive(pat)
-                } else {
-                    pat.to_string()
-                }
+fn main() {
+    enable_ansi_colors();
+    handle_interrupts();
+    let started_at = Instant::now();
+    let args = Args::try_from(setup_arg_parser().get_matches()).unwrap_or_else(|err| err.exit());
+    let matcher = match &args.matching {
+        Matching::Pattern(pattern) => Matcher::Pattern(pattern.clone()),
+        Matching::Patterns(patterns) => {
+            // The patterns come from the arguments, so no file is to blame if they fail to compile
+            Matcher::Rules(make_pattern_rules(patterns).unwrap_or_else(|err| {
+                fail(&args, &format!("while compiling the -e patterns: {err}"), 2)
+            }))
+        }
+        Matching::RulesFile(path) => Matcher::Rules(load_rules(path).unwrap_or_else(|err| {
+            fail(
+                &args,
+                &format!("while loading rules from {}: {err}", path.display()),
+                2,
+            )
+        })),
+    };
+
+    // --interactive can't be given with rules, so this is always the case when it is given
+    if let (true, Matcher::Pattern(pattern)) = (args.interactive, &matcher) {
+        page_file(&args, pattern);
+        return;
+    }
+
+    let plugin = args.plugin_file.as_ref().map(|path| {
+        load_plugin(path).unwrap_or_else(|err| {
+            fail(
+                &args,
+                &format!("while loading the plugin {}: {err}", path.display()),
+                2,
+            )
+        })
+    });
+
+    let matches_file = match args.format {
+        OutputFormat::Highlight => args.matches_to.as_ref().map(|path| {
+            open_matches_file(path).unwrap_or_else(|err| {
+                fail(
+                    &args,
+                    &format!("while opening {}: {err}", path.display()),
+                    2,
+                )
             })
-            .expect("pattern arg not found, despite parser reporting it was present");
+        }),
+        OutputFormat::Csv | OutputFormat::Json | OutputFormat::Vimgrep => None,
+    };
 
-        let file = args
-            .value_of(FILENAME_ARG_NAME)
-            .map_or(PassedFile::Stdin, |filename| {
-                PassedFile::Path(filename.to_string())
-            });
+    if let OutputFormat::Csv = args.format {
+        print_csv_header(&args);
+    }
 
-        Args {
-            pattern,
-            file,
-            ok_if_binary_file,
+    let notifier = match (&args.notify_url, &matcher) {
+        (Some(url), Matcher::Pattern(pattern)) => Some(Notifier::new(url.clone(), pattern)),
+        // --notify-url can only be used with a single pattern
+        _ => None,
+    };
+    let run_stats = args.signal_stats.then(|| start_run_stats(&args));
+    let json_stats = Cell::new(JsonStats::default());
+    let scan = Scan::new(&args, &args.file, &matcher, &json_stats)
+        .plugin(plugin.as_ref())
+        .matches_file(matches_file.as_ref())
+        .notifier(notifier.as_ref())
+        .run_stats(run_stats.as_ref());
+
+    let exit_code = match &args.file {
+        // Directories can't be followed, watched, or shown in less, so are left to fail to open in those cases
+        PassedFile::Path(path)
+            if path.is_dir() && args.follow.is_none() && !args.watch && !args.lessopen =>
+        {
+            scan_directory(&scan, path)
+        }
+        PassedFile::Path(path) if args.watch => {
+            watch_file(&scan, path);
+            None
         }
+        PassedFile::Concatenated(files) if args.reset_line_numbers || args.with_filename => {
+            scan_files(&scan, files)
+        }
+        file => {
+            let scan_result = open_file_for_scan(&args, file)
+                .and_then(|opened_file| scan_opened_file(opened_file, &scan));
+            if let Err(failure) = scan_result {
+                finish_scan(&args, notifier, json_stats.get(), started_at);
+                fail(&args, &failure.message, failure.exit_code);
+            }
+
+            None
+        }
+    };
+
+    finish_scan(&args, notifier, json_stats.get(), started_at);
+    if let Some(exit_code) = exit_code {
+        process::exit(exit_code);
     }
 }
 
-fn main() {
-    let parsed_args = setup_arg_parser().get_matches();
-    let args_parse_result = Args::try_from(parsed_args);
+/// Finish the output of the scan, whether or not it succeeded, by waiting for every match to be sent by the notifier,
+/// and writing the summary that ends JSON output.
+fn finish_scan(
+    args: &Args,
+    notifier: Option<Notifier>,
+    json_stats: JsonStats,
+    started_at: Instant,
+) {
+    if let Some(notifier) = notifier {
+        notifier.finish();
+    }
 
-    let args = args_parse_result.unwrap();
-    let open_file_result = open_file(args.file);
-    if let Err(err) = open_file_result {
-        print_error(&format!("Failed to open input file: {}", err));
-        process::exit(2);
+    if let OutputFormat::Json = args.format {
+        match hline::write_json_summary(io::stdout(), json_stats, started_at.elapsed()) {
+            Err(hline::Error::PrintFailure { source: err, .. })
+                if err.kind() == io::ErrorKind::BrokenPipe =>
+            {
+                exit_for_broken_pipe()
+            }
+            Err(err) => fail(args, &format!("while printing: {err}"), 3),
+            Ok(()) => {}
+        }
     }
+}
 
-    let mut opened_file = open_file_result.unwrap();
-    if !args.ok_if_binary_file {
-        handle_potentially_binary_file(&mut opened_file);
+/// Scan every file in the given directory, and its subdirectories if scanning recursively, printing a heading before
+/// each one. Failures to scan a file are reported, but do not stop the remaining files from being scanned. Files are
+/// skipped from the point that binary data is found in them, and files larger than `--max-filesize` are skipped
+/// entirely, with a warning.
+///
+/// Returns the code of the last failure, if there was one, which the program should exit with.
+fn scan_directory(scan: &Scan, dir: &Path) -> Option<i32> {
+    let args = scan.args;
+    let mut exit_code = None;
+    let mut scanned_any = false;
+    let mut walk = args.walk_options.walk(dir);
+    if !args.recursive {
+        walk = walk.max_depth(1);
     }
 
-    let scan_result = hline::scan_pattern(opened_file, &args.pattern);
-    if let Err(err) = scan_result {
-        // the lib crate provides the context for the errors in their error messages
-        print_error(&err);
-        process::exit(3);
+    for walk_result in walk {
+        let file = match walk_result {
+            Ok(path) => PassedFile::Path(path),
+            Err(err) => {
+                print_error(&format!("while reading {err}"));
+                exit_code = Some(2);
+                continue;
+            }
+        };
+
+        if let Some(size) = args.walk_options.oversized_file_size(&file) {
+            print_warning(&format!(
+                "skipping {file}, as its size of {size} bytes is larger than --max-filesize"
+            ));
+            continue;
+        }
+
+        let opened_file = match open_file_for_scan(args, &file) {
+            Ok(opened_file) => opened_file,
+            Err(failure) => {
+                print_error(&failure.message);
+                exit_code = Some(failure.exit_code);
+                continue;
+            }
+        };
+
+        if should_print_headings(args) {
+            match print_heading(&file, args, scanned_any) {
+                Ok(()) => {}
+                Err(print::Error::BrokenPipe(_)) => exit_for_broken_pipe(),
+                Err(err) => fail(args, &format!("while printing: {err}"), 3),
+            }
+        }
+
+        scanned_any = true;
+        let file_scan = scan.for_file(&file);
+        match scan_opened_file(opened_file, &file_scan) {
+            Ok(()) => {}
+            Err(failure) if failure.exit_code == BINARY_FILE_EXIT_CODE => {
+                print_warning(&format!(
+                    "skipping the rest of {file}, as it has binary data"
+                ));
+            }
+            Err(failure) => {
+                print_error(&failure.message);
+                exit_code = Some(failure.exit_code);
+            }
+        }
     }
+
+    exit_code
 }
 
-fn print_error<T: Display + ?Sized>(error_msg: &T) {
-    eprintln!(
-        "{color}error:{reset} {err}",
-        color = Fg(LightRed),
-        reset = Fg(Reset),
-        err = error_msg
-    );
-}
-
-/// Setup the argument parser for the program with all possible flags
-fn setup_arg_parser() -> App<'static, 'static> {
-    App::new(crate_name!())
-        .version(crate_version!())
-        .about("Highlights lines that match the given regular expression")
-        .setting(AppSettings::DisableVersion)
-        .arg(
-            Arg::with_name("pattern")
-                .takes_value(true)
-                .required(true)
-                .allow_hyphen_values(true)
-                .help(concat!(
-                    "The regular expression to search for. Note that this is not anchored, and if ",
-                    "anchoring is desired, should be done manually with ^ or $."
-                )),
-        )
-        .arg(
-            Arg::with_name(FILENAME_ARG_NAME)
-                .takes_value(true)
-                .help("The file to scan. If not specified, reads from stdin"),
-        )
-        .arg(
-            Arg::with_name(CASE_INSENSITIVE_ARG_NAME)
-                .short("-i")
-                .long("--ignore-case")
-                .help("Ignore case when performing matching. If not specified, the matching is case-sensitive."),
-        )
-        .arg(
-            Arg::with_name(OK_IF_BINARY_ARG_NAME)
-                .short("-b")
-                .help("Treat the given input file as text, even if it may be a binary file"),
-        )
+/// Scan each of the given files in turn, so that line numbers start over for each of them. Failures to scan a file are
+/// reported, but do not stop the remaining files from being scanned.
+///
+/// Returns the code of the last failure, if there was one, which the program should exit with.
+fn scan_files(scan: &Scan, files: &[PassedFile]) -> Option<i32> {
+    let mut exit_code = None;
+    for file in files {
+        let scan = scan.for_file(file);
+        let scan_result = open_file_for_scan(scan.args, file)
+            .and_then(|opened_file| scan_opened_file(opened_file, &scan));
+        if let Err(failure) = scan_result {
+            print_error(&failure.message);
+            exit_code = Some(failure.exit_code);
+        }
+    }
+
+    exit_code
 }
 
-/// Open the file that was passed to the command line
-fn open_file(file: PassedFile) -> Result<OpenedFile, io::Error> {
-    match file {
-        PassedFile::Stdin => {
-            let stdin = io::stdin();
-            let recorded_stdin = ReadRecorder::new(stdin);
-            Ok(OpenedFile::Stdin(recorded_stdin))
+/// Scan the given file, and then scan it again each time it changes, until the program is interrupted. Before each
+/// rescan, the screen is cleared if printing to a terminal, or a heading naming the file is printed otherwise. Failures
+/// to scan the file are reported, but do not stop it from being watched.
+fn watch_file(scan: &Scan, path: &Path) {
+    let args = scan.args;
+    let mut watcher = FileWatcher::new(path);
+    let mut rescanning = false;
+    loop {
+        if rescanning {
+            match print_rescan_separator(scan.file, args) {
+                Ok(()) => {}
+                Err(print::Error::BrokenPipe(_)) => exit_for_broken_pipe(),
+                Err(err) => fail(args, &format!("while printing: {err}"), 3),
+            }
         }
-        PassedFile::Path(path) => {
-            let file = File::open(path)?;
-            assert_is_not_directory(&file)?;
-            Ok(OpenedFile::File(file))
+
+        let scan_result = open_file_for_scan(args, scan.file)
+            .and_then(|opened_file| scan_opened_file(opened_file, scan));
+        if let Err(failure) = scan_result {
+            print_error(&failure.message);
         }
+
+        watcher.wait_for_change();
+        rescanning = true;
     }
 }
 
-fn assert_is_not_directory(file: &File) -> Result<(), io::Error> {
-    let metadata = file.metadata()?;
-    if metadata.is_dir() {
-        Err(io::Error::new(
-            // io::ErrorKind::IsADirectory is unstable at the time of writing :(
-            io::ErrorKind::Other,
-            "is a directory",
-        ))
+/// Separate the output of a rescan of the given file from the output of the previous scan, by clearing the screen
+/// if printing to a terminal, or by printing a heading naming the file otherwise.
+fn print_rescan_separator(file: &PassedFile, args: &Args) -> Result<(), print::Error> {
+    if io::stdout().is_terminal() {
+        StdoutPrinter::new().print(CLEAR_SCREEN)
+    } else if should_print_headings(args) {
+        print_heading(file, args, true)
     } else {
         Ok(())
     }
 }
 
-fn make_pattern_case_insensitive(pattern: &str) -> String {
-    format!("(?i){}", pattern)
+/// Whether headings naming each file should be printed when scanning a directory. Decorations, CSV, JSON, and lines
+/// prefixed with their filename already name the file that output came from.
+fn should_print_headings(args: &Args) -> bool {
+    matches!(args.format, OutputFormat::Highlight)
+        && args.decorations.is_none()
+        && !args.with_filename
+        && !matches!(args.count, Count::Only)
+}
+
+/// Print a heading naming the given file in the theme's header color, separated from the output of any previous file if
+/// there was one.
+fn print_heading(file: &PassedFile, args: &Args, separate: bool) -> Result<(), print::Error> {
+    if args.color {
+        print_heading_to(StdoutPrinter::new(), file, args, separate)
+    } else {
+        print_heading_to(
+            PlainPrinter::new(StdoutPrinter::new()),
+            file,
+            args,
+            separate,
+        )
+    }
 }
 
-/// Check if the given file is a binary file, and if it is, exit gracefully
-fn handle_potentially_binary_file(opened_file: &mut OpenedFile) {
-    let is_binary_file = match should_treat_as_binary_file(opened_file) {
-        Err(err) => {
-            // This could probably be done nicer with a macro but I don't care about a small allocation like this
-            // when we're immediately about to quit anyway
-            print_error(&format!("failed to peek file: {}", err));
-            process::exit(4);
+fn print_heading_to<P: Printer>(
+    printer: P,
+    file: &PassedFile,
+    args: &Args,
+    separate: bool,
+) -> Result<(), print::Error> {
+    if separate {
+        printer.print("\n")?;
+    }
+
+    printer.styled_print((Bold, Fg(args.theme.header)), file.to_string())?;
+    printer.print(filename_terminator(args, "\n"))
+}
+
+/// Scan the passed file into a full-screen pager, in which the results can be scrolled through and searched. Lines are
+/// matched against the given pattern as configured by the arguments, but are not printed to stdout.
+fn page_file(args: &Args, pattern: &str) {
+    let mut pager = Pager::new(args.theme);
+    let mut options = args.scan_options.clone();
+    options.input_name(Some(&args.file.to_string()));
+    let scan_result = open_file_for_scan(args, &args.file).and_then(|opened_file| {
+        options
+            .scan_pattern_with_callback(opened_file, pattern, |event| pager.push(event))
+            .map_err(|err| scan_failure(&err))
+    });
+    if let Err(failure) = scan_result {
+        fail(args, &failure.message, failure.exit_code);
+    }
+
+    if let Err(err) = pager.run() {
+        fail(args, &format!("while showing results: {err}"), 3);
+    }
+}
+
+/// Make the [`Failure`] for the given error from scanning a file.
+fn scan_failure(err: &hline::Error) -> Failure {
+    let source = match err {
+        hline::Error::Input { source, .. } => source,
+        _ => err,
+    };
+
+    Failure {
+        message: describe_scan_error(err),
+        exit_code: match source {
+            hline::Error::BinaryData { .. } => BINARY_FILE_EXIT_CODE,
+            _ => 3,
+        },
+    }
+}
+
+/// Start counting the lines seen and matched, printing them whenever SIGUSR1 is received.
+fn start_run_stats(args: &Args) -> Arc<RunStats> {
+    let stats = Arc::new(RunStats::start());
+    if let Err(err) = cli::run_stats::report_on_signal(Arc::clone(&stats)) {
+        fail(args, &format!("while listening for SIGUSR1: {err}"), 2);
+    }
+
+    stats
+}
+
+/// Print the header row of the CSV output to stdout, which must come before the rows of every file that is scanned.
+fn print_csv_header(args: &Args) {
+    match hline::write_csv_header(io::stdout()) {
+        Err(hline::Error::PrintFailure { source: err, .. })
+            if err.kind() == io::ErrorKind::BrokenPipe =>
+        {
+            exit_for_broken_pipe()
         }
-        Ok(val) => val,
+        Err(err) => fail(args, &format!("while printing: {err}"), 3),
+        Ok(()) => {}
+    }
+}
+
+/// Report the given error and exit with the given code. In `--lessopen` mode, nothing is reported (as it would only
+/// clutter `less`' display), and all failures exit with the same code.
+fn fail<T: Display + ?Sized>(args: &Args, error_msg: &T, exit_code: i32) -> ! {
+    if args.lessopen {
+        process::exit(LESSOPEN_FAILURE_EXIT_CODE);
+    }
+
+    print_error(error_msg);
+    process::exit(exit_code);
+}
+
+/// Exit as if terminated by SIGPIPE, as is conventional for programs whose output is closed before they are done (e.g.
+/// by `head`). Rust ignores SIGPIPE so that writing to a closed pipe fails instead, so its default action must be
+/// restored before it is raised.
+#[cfg(unix)]
+fn exit_for_broken_pipe() -> ! {
+    // SAFETY: this program installs no handlers of its own for SIGPIPE, and is exiting anyway
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+        libc::raise(libc::SIGPIPE);
+    }
+
+    process::exit(BROKEN_PIPE_EXIT_CODE);
+}
+
+/// Other platforms have no SIGPIPE, so exit with the code shells would report for it.
+#[cfg(not(unix))]
+fn exit_for_broken_pipe() -> ! {
+    process::exit(BROKEN_PIPE_EXIT_CODE);
+}
+
+/// Describe an error that occurred while scanning a file, including the file and the operation that failed. The file is
+/// named by the error itself, as it is given to the scan options for each file.
+fn describe_scan_error(err: &hline::Error) -> String {
+    let hline::Error::Input { name, source } = err else {
+        return format!("while scanning: {err}");
     };
 
-    if is_binary_file {
-        print_error("Input file may be a binary file. Pass -b to ignore this and scan anyway.");
-        process::exit(5);
+    match source.as_ref() {
+        hline::Error::ReadFailure { offset, source } => {
+            format!("while reading {name} at byte {offset}: {source}")
+        }
+        hline::Error::PrintFailure { .. } => {
+            format!("while printing the contents of {name}: {source}")
+        }
+        hline::Error::BinaryData { offset } => {
+            format!(
+                "{name} has binary data at byte {offset}, so scanning stopped. Pass -b to scan it anyway, or \
+                 --strings to scan the text within it."
+            )
+        }
+        // the lib crate provides the remaining context for the errors in their error messages
+        _ => format!("while scanning {name}: {source}"),
+    }
+}
+
+fn print_warning<T: Display + ?Sized>(warning_msg: &T) {
+    let printer = StderrPrinter::new();
+    // As with errors, there is nowhere left to report anything if stderr has gone away
+    let _ = printer
+        .colored_print(Fg(LightYellow), "warning:")
+        .and_then(|()| printer.print(format!(" {warning_msg}\n")));
+}
+
+/// Allow the colors we print to be displayed by the Windows console, which will otherwise print their escape sequences
+/// verbatim. If this fails (e.g. because output is not a console), there is nothing to display colors on anyway.
+#[cfg(windows)]
+fn enable_ansi_colors() {
+    use winapi_util::console::Console;
+
+    for mut console in [Console::stdout(), Console::stderr()].into_iter().flatten() {
+        let _ = console.set_virtual_terminal_processing(true);
+    }
+}
+
+/// Other platforms' terminals display colors natively.
+#[cfg(not(windows))]
+fn enable_ansi_colors() {}
+
+/// Install a handler for Ctrl-C that ends any colors or styles that were being printed when the program was
+/// interrupted, so that the terminal is not left in them, before exiting. Output to a terminal is flushed after every
+/// line, so the reset follows everything printed so far. If the handler cannot be installed, Ctrl-C will still stop
+/// the program, only without resetting the terminal.
+fn handle_interrupts() {
+    let _ = ctrlc::set_handler(|| {
+        if io::stdout().is_terminal() {
+            // There's nothing more that can be done if this fails, as the program is exiting anyway
+            let _ = write_to_stdout_unlocked(Reset.to_string().as_bytes());
+        }
+
+        process::exit(INTERRUPTED_EXIT_CODE);
+    });
+}
+
+/// Write the given bytes to stdout without locking it, as the thread that was interrupted may be holding the lock. This
+/// is done through a duplicate of stdout's file descriptor, rather than `io::stdout()`.
+#[cfg(not(windows))]
+fn write_to_stdout_unlocked(bytes: &[u8]) -> io::Result<()> {
+    use std::os::fd::AsFd;
+
+    let mut stdout = File::from(io::stdout().as_fd().try_clone_to_owned()?);
+    stdout.write_all(bytes)
+}
+
+/// Write the given bytes to stdout without locking it, as the thread that was interrupted may be holding the lock. This
+/// is done through a duplicate of stdout's handle, rather than `io::stdout()`.
+#[cfg(windows)]
+fn write_to_stdout_unlocked(bytes: &[u8]) -> io::Result<()> {
+    use std::os::windows::io::AsHandle;
+
+    let mut stdout = File::from(io::stdout().as_handle().try_clone_to_owned()?);
+    stdout.write_all(bytes)
+}
+
+fn print_error<T: Display + ?Sized>(error_msg: &T) {
+    let printer = StderrPrinter::new();
+    // If stderr has gone away (e.g. due to a broken pipe), there is nowhere left to report anything, so there's no
+    // choice but to carry on.
+    let _ = printer
+        .colored_print(Fg(LightRed), "error:")
+        .and_then(|()| printer.print(format!(" {error_msg}\n")));
+}
+
+/// Make rules that highlight each of the given patterns with their style
+fn make_pattern_rules(patterns: &[(String, Style)]) -> Result<Rules, rules::Error> {
+    let rules = patterns
+        .iter()
+        .map(|(pattern, style)| Rule::with_style(pattern, *style, Action::Highlight))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Rules::new(rules))
+}
+
+/// Load the rules file at the given path
+fn load_rules(path: &Path) -> Result<Rules, Box<dyn std::error::Error>> {
+    let rules_toml = fs::read_to_string(path)?;
+    let rules = Rules::from_toml(&rules_toml)?;
+
+    Ok(rules)
+}
+
+/// Load the Lua plugin at the given path
+#[cfg(feature = "lua")]
+fn load_plugin(path: &Path) -> Result<Plugin, Box<dyn std::error::Error>> {
+    let source = fs::read_to_string(path)?;
+    let plugin = Plugin::load(&source, &path.display().to_string())?;
+
+    Ok(plugin)
+}
+
+#[cfg(not(feature = "lua"))]
+fn load_plugin(_path: &Path) -> Result<Plugin, &'static str> {
+    Err("plugins are not supported, as hl was built without the lua feature")
+}
+
+/// Open the given file, and check that it is suitable for scanning.
+fn open_file_for_scan(args: &Args, file: &PassedFile) -> Result<OpenedFile, Failure> {
+    // Each of the concatenated files is opened on its own, so that failures name the file at fault
+    if let PassedFile::Concatenated(files) = file {
+        let opened_files = files
+            .iter()
+            .map(|file| open_file_for_scan(args, file))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        return Ok(OpenedFile::Concatenated(ChainReader::new(opened_files)));
     }
+
+    let opened_file = open_file(file, args).map_err(|err| Failure {
+        message: format!("while opening {file}: {err}"),
+        exit_code: 2,
+    })?;
+
+    if args.strings {
+        return Ok(OpenedFile::Strings(StringsReader::new(Box::new(
+            opened_file,
+        ))));
+    }
+
+    Ok(opened_file)
 }
 
-// Check if a given file is a binary file (or not possible to be easily checked)
-fn should_treat_as_binary_file(opened_file: &mut OpenedFile) -> Result<bool, io::Error> {
-    match opened_file {
-        OpenedFile::Stdin(stdin) => {
-            stdin.start_recording();
-            let is_likely_binary = file::utf8::is_file_likely_binary(stdin)?;
-            stdin.stop_recording();
-            stdin.rewind_to_start_of_recording();
-            Ok(is_likely_binary)
+/// Open the file that was passed to the command line, following it for new data or decompressing it if requested
+fn open_file(file: &PassedFile, args: &Args) -> Result<OpenedFile, io::Error> {
+    match file {
+        PassedFile::Concatenated(_) => {
+            unreachable!("concatenated files must be opened with open_file_for_scan")
+        }
+        PassedFile::Stdin if args.decompress => {
+            let decompressed = DecompressReader::new(BufReader::new(io::stdin()))?;
+            Ok(OpenedFile::DecompressedStdin(decompressed))
         }
-        OpenedFile::File(file) => {
-            let is_likely_binary = file::utf8::is_file_likely_binary(file)?;
-            file.rewind()?;
-            Ok(is_likely_binary)
+        PassedFile::Stdin => {
+            let decoded = decode(BufReader::new(io::stdin()), args)?;
+            Ok(OpenedFile::Stdin(decoded))
         }
+        PassedFile::Path(path) => {
+            let mut file = File::open(path)?;
+            assert_is_not_directory(&file)?;
+            if args.decompress {
+                let decompressed = DecompressReader::new(BufReader::new(file))?;
+                Ok(OpenedFile::DecompressedFile(decompressed))
+            } else if !is_regular_file(&file)? {
+                // There's no need to follow pipes, as reading them already waits for more data to be written
+                let decoded = decode(BufReader::new(file), args)?;
+                Ok(OpenedFile::DecodedFile(decoded))
+            } else if let Some(Follow::Name) = args.follow {
+                let reopening = ReopeningFile::open(path)?;
+                Ok(OpenedFile::FollowedByName(FollowReader::new(reopening)))
+            } else if let Some(Follow::Descriptor) = args.follow {
+                Ok(OpenedFile::Followed(FollowReader::new(file)))
+            } else if args.encoding.is_some() || is_utf16_file(&mut file)? {
+                let decoded = decode(BufReader::new(file), args)?;
+                Ok(OpenedFile::DecodedFile(decoded))
+            } else {
+                Ok(OpenedFile::File(file))
+            }
+        }
+    }
+}
+
+/// Transcode the given input from the encoding given in the arguments, or from UTF-16 if it is detected
+fn decode<R: BufRead>(read: R, args: &Args) -> Result<DecodeReader<R>, io::Error> {
+    match args.encoding {
+        Some(encoding) => Ok(DecodeReader::with_encoding(read, encoding)),
+        None => DecodeReader::new(read),
+    }
+}
+
+/// Check if the given file begins with a UTF-16 byte order mark, rewinding it to the start afterwards
+fn is_utf16_file(file: &mut File) -> Result<bool, io::Error> {
+    let mut start = Vec::new();
+    file.take(2).read_to_end(&mut start)?;
+    file.rewind()?;
+
+    Ok(Utf16::detect(&start).is_some())
+}
+
+/// Open the file that matching lines will be copied to, for appending
+fn open_matches_file(path: &Path) -> Result<File, io::Error> {
+    OpenOptions::new().append(true).create(true).open(path)
+}
+
+/// Check if the given file is a regular file, which can seek. Others, such as FIFOs and the pipes made by process
+/// substitution (e.g. `<(cmd)`), can only be read through once.
+fn is_regular_file(file: &File) -> Result<bool, io::Error> {
+    Ok(file.metadata()?.is_file())
+}
+
+fn assert_is_not_directory(file: &File) -> Result<(), io::Error> {
+    let metadata = file.metadata()?;
+    if metadata.is_dir() {
+        // io::ErrorKind::IsADirectory is unstable at the time of writing :(
+        Err(io::Error::other("is a directory"))
+    } else {
+        Ok(())
     }
 }
@@ -0,0 +1,917 @@
+//! `options` provides [`ScanOptions`], which configures how input is matched and printed in a single place.
+use crate::color::AnsiValue;
+use crate::invert::InvertedMatcher;
+#[cfg(feature = "lua")]
+use crate::plugin::LuaPlugin;
+use crate::prefixed::PrefixedMatcher;
+use crate::print::Printer;
+use crate::rules::Rules;
+use crate::sink::{self, ContextPrintingSink, JsonStats, LineEvent, ScanStats, StatsSink};
+use crate::style::Style;
+use crate::theme::Theme;
+use crate::Error;
+use grep::matcher::{LineTerminator, Matcher};
+use grep::regex::{RegexMatcher, RegexMatcherBuilder};
+use grep::searcher::{BinaryDetection, Searcher, SearcherBuilder, Sink};
+use regex::{Regex, RegexBuilder};
+use std::io;
+use std::io::{Read, Write};
+
+/// `ScanOptions` configures how lines are matched and highlighted, for use in place of the functions in the crate root
+/// when more control is needed. Each option is set with a builder method, and defaults to the behavior of
+/// [`scan_pattern_to_printer`](`crate::scan_pattern_to_printer`).
+///
+/// ```no_run
+/// use hline::options::ScanOptions;
+/// use hline::print::StdoutPrinter;
+/// use hline::theme::Theme;
+/// use std::io;
+///
+/// ScanOptions::new()
+///     .case_insensitive(true)
+///     .theme(Theme::HIGH_CONTRAST)
+///     .scan_pattern_to_printer(io::stdin(), "error", StdoutPrinter::new())
+///     .expect("scan failed");
+/// ```
+#[derive(Clone, Debug, Default)]
+#[allow(clippy::module_name_repetitions, clippy::struct_excessive_bools)]
+pub struct ScanOptions {
+    case_insensitive: bool,
+    fixed_strings: bool,
+    invert: bool,
+    multi_line: bool,
+    only_matching: bool,
+    before_context: usize,
+    after_context: usize,
+    capture_groups: bool,
+    dim: Option<String>,
+    line_number: bool,
+    byte_offset: bool,
+    markers: bool,
+    prefixed: bool,
+    theme: Theme,
+    alternate_color: Option<AnsiValue>,
+    max_count: Option<u64>,
+    stop_after: Option<u64>,
+    max_columns: Option<usize>,
+    replacement: Option<String>,
+    line_terminator: LineTerminator,
+    crlf: bool,
+    quit_on_binary: bool,
+    input_name: Option<String>,
+}
+
+/// `Haystack` is the data being scanned, which is searched most efficiently when it is already in memory.
+enum Haystack<'a, R: Read> {
+    Reader(R),
+    Slice(&'a [u8]),
+}
+
+impl<R: Read> Haystack<'_, R> {
+    fn search<M: Matcher, S: Sink<Error = sink::Error>>(
+        self,
+        searcher: Searcher,
+        matcher: M,
+        sink: S,
+    ) -> Result<(), Error> {
+        match self {
+            Self::Reader(reader) => crate::search_reader(searcher, reader, matcher, sink),
+            Self::Slice(slice) => crate::search_slice(searcher, slice, matcher, sink),
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Make a new `ScanOptions` with every option set to its default.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ignore case when matching the pattern. Disabled by default.
+    pub fn case_insensitive(&mut self, case_insensitive: bool) -> &mut Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Match the pattern as a literal string, rather than a regular expression. Disabled by default.
+    pub fn fixed_strings(&mut self, fixed_strings: bool) -> &mut Self {
+        self.fixed_strings = fixed_strings;
+        self
+    }
+
+    /// Highlight the lines that do not match the pattern, rather than those that do. Disabled by default.
+    pub fn invert(&mut self, invert: bool) -> &mut Self {
+        self.invert = invert;
+        self
+    }
+
+    /// Allow the pattern to match across lines, such as with `\n`, highlighting every line that a match spans. `^` and
+    /// `$` still match at the start and end of each line. The whole input is read before any of it is printed, unless
+    /// it is a slice. Disabled by default. This does not affect CSV or JSON output.
+    pub fn multi_line(&mut self, multi_line: bool) -> &mut Self {
+        self.multi_line = multi_line;
+        self
+    }
+
+    /// Print every line of the input, highlighting the lines that match. Enabled by default. Disabling this is the same
+    /// as enabling [`only_matching`](`ScanOptions::only_matching`).
+    pub fn passthru(&mut self, passthru: bool) -> &mut Self {
+        self.only_matching = !passthru;
+        self
+    }
+
+    /// Print only the lines that match, rather than every line of the input, like `grep`. Once
+    /// [`max_count`](`ScanOptions::max_count`) lines have matched, the scan stops. Disabled by default. This does not
+    /// affect CSV or JSON output.
+    ///
+    /// Lines that are not printed are not counted in the [`ScanStats`] that are returned, so
+    /// [`lines_scanned`](`ScanStats::lines_scanned`) will only count the printed lines.
+    pub fn only_matching(&mut self, only_matching: bool) -> &mut Self {
+        self.only_matching = only_matching;
+        self
+    }
+
+    /// When only printing matching lines, also print this many lines before each of them, as context. Groups of lines
+    /// that are not contiguous are separated by a `--` line. Defaults to 0. Has no effect unless
+    /// [`only_matching`](`ScanOptions::only_matching`) is enabled.
+    pub fn before_context(&mut self, before_context: usize) -> &mut Self {
+        self.before_context = before_context;
+        self
+    }
+
+    /// Identical to [`before_context`](`ScanOptions::before_context`), except that the lines after each matching line
+    /// are printed.
+    pub fn after_context(&mut self, after_context: usize) -> &mut Self {
+        self.after_context = after_context;
+        self
+    }
+
+    /// Highlight each capture group of the pattern within matching lines in its own color, as
+    /// [`ContextPrintingSink::capture_groups`] does. Disabled by default. This has no effect when the lines that do not
+    /// match are highlighted, the pattern is matched as a literal string, or the input is prefixed, and does not affect
+    /// CSV or JSON output.
+    pub fn capture_groups(&mut self, capture_groups: bool) -> &mut Self {
+        self.capture_groups = capture_groups;
+        self
+    }
+
+    /// Print lines that do not match the pattern, but do match `dim_pattern`, dimmed, so that noisy lines recede into
+    /// the background. `dim_pattern` is matched with the same options as the pattern. Defaults to `None`, which dims
+    /// no lines.
+    pub fn dim(&mut self, dim_pattern: Option<&str>) -> &mut Self {
+        self.dim = dim_pattern.map(str::to_string);
+        self
+    }
+
+    /// Prefix each printed line with its line number, as [`ContextPrintingSink::line_number`] does. If the byte offset
+    /// is printed too, it follows the line number. Disabled by default. This does not affect CSV or JSON output, which
+    /// always include the line number of each line.
+    pub fn line_number(&mut self, line_number: bool) -> &mut Self {
+        self.line_number = line_number;
+        self
+    }
+
+    /// Prefix each printed line with the byte offset of its start within the input, as
+    /// [`ContextPrintingSink::byte_offset`] does. Disabled by default. This does not affect CSV or JSON output; CSV rows
+    /// always include the byte offset of their line.
+    pub fn byte_offset(&mut self, byte_offset: bool) -> &mut Self {
+        self.byte_offset = byte_offset;
+        self
+    }
+
+    /// After each matching line, print a line with `^` markers under each match within it, as
+    /// [`ContextPrintingSink::markers`] does. Disabled by default. This has no effect when the lines that do not match
+    /// are highlighted, and does not affect CSV or JSON output.
+    pub fn markers(&mut self, markers: bool) -> &mut Self {
+        self.markers = markers;
+        self
+    }
+
+    /// Treat the input as lines prefixed with their source, only matching the pattern against the remainder of each
+    /// line. See [`PrefixedMatcher`] for the prefixes that are understood. Disabled by default.
+    pub fn prefixed(&mut self, prefixed: bool) -> &mut Self {
+        self.prefixed = prefixed;
+        self
+    }
+
+    /// The [`Theme`] to style the output with. Defaults to [`Theme::DEFAULT`].
+    pub fn theme(&mut self, theme: Theme) -> &mut Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Highlight matches that directly follow another match in the input in `color`, switching back and forth between
+    /// it and the theme's color, as [`ContextPrintingSink::alternate`] does, so that adjacent matches can be told apart.
+    /// Defaults to `None`, which highlights every match alike. This does not affect CSV or JSON output.
+    pub fn alternate_color(&mut self, color: Option<AnsiValue>) -> &mut Self {
+        self.alternate_color = color;
+        self
+    }
+
+    /// Only highlight the first `max_count` matching lines, passing any after them through as if they did not match.
+    /// Defaults to `None`, which highlights every matching line. This does not affect CSV or JSON output.
+    pub fn max_count(&mut self, max_count: Option<u64>) -> &mut Self {
+        self.max_count = max_count;
+        self
+    }
+
+    /// Stop the scan entirely once `stop_after` matching lines have been printed, as
+    /// [`ContextPrintingSink::stop_after`] does, rather than passing the rest of the input through. Defaults to `None`,
+    /// which scans the input to its end. This does not affect CSV or JSON output.
+    pub fn stop_after(&mut self, stop_after: Option<u64>) -> &mut Self {
+        self.stop_after = stop_after;
+        self
+    }
+
+    /// Truncate printed lines that are wider than `max_columns` columns, as [`ContextPrintingSink::max_columns`] does,
+    /// keeping the first match within each line visible where it fits. Defaults to `None`, which prints lines in full.
+    /// This does not affect CSV or JSON output.
+    pub fn max_columns(&mut self, max_columns: Option<usize>) -> &mut Self {
+        self.max_columns = max_columns;
+        self
+    }
+
+    /// In matching lines, replace each match of the pattern with `template`, in which `$1` or `${name}` refers to the
+    /// text matched by a capture group, as [`ContextPrintingSink::replace`] does. Defaults to `None`, which prints
+    /// matching lines as-is. This has no effect when the lines that do not match are highlighted, and does not affect
+    /// CSV or JSON output.
+    pub fn replace(&mut self, template: Option<&str>) -> &mut Self {
+        self.replacement = template.map(str::to_string);
+        self
+    }
+
+    /// The byte that separates each line (or record) of the input, such as `b'\0'` for the output of `find -print0`.
+    /// The terminator is never highlighted. Defaults to `b'\n'`, in which case lines may also end in `\r\n`.
+    pub fn line_terminator(&mut self, line_terminator: u8) -> &mut Self {
+        self.line_terminator = LineTerminator::byte(line_terminator);
+        self
+    }
+
+    /// Treat lines as terminated by `\r\n`, so that `$` in the pattern matches before the `\r`, rather than only before
+    /// a bare `\n`. This takes precedence over [`ScanOptions::line_terminator`]. Disabled by default.
+    pub fn crlf(&mut self, crlf: bool) -> &mut Self {
+        self.crlf = crlf;
+        self
+    }
+
+    /// Stop scanning once binary data (a NUL byte) is found in the input, failing with
+    /// [`Error::BinaryData`](`crate::Error::BinaryData`). Lines before the binary data may be printed before it is
+    /// found. Disabled by default.
+    pub fn quit_on_binary(&mut self, quit_on_binary: bool) -> &mut Self {
+        self.quit_on_binary = quit_on_binary;
+        self
+    }
+
+    /// Name the input being scanned, such as with its path, so that any error from scanning it is reported as an
+    /// [`Error::Input`](`crate::Error::Input`) carrying this name. This tells apart the errors from scanning several
+    /// inputs with the same options. Unset by default.
+    pub fn input_name(&mut self, input_name: Option<&str>) -> &mut Self {
+        self.input_name = input_name.map(ToString::to_string);
+        self
+    }
+
+    /// Print a `Read`'s contents to the given [`Printer`], highlighting the lines that match the given pattern, as
+    /// configured by these options. [`ScanStats`] about the scan are returned.
+    ///
+    /// # Errors
+    ///
+    /// See [`scan_pattern_to_printer`](`crate::scan_pattern_to_printer`).
+    pub fn scan_pattern_to_printer<R: Read, P: Printer>(
+        &self,
+        reader: R,
+        pattern: &str,
+        printer: P,
+    ) -> Result<ScanStats, Error> {
+        self.scan_haystack_to_printer(Haystack::Reader(reader), pattern, printer)
+    }
+
+    /// Identical to [`ScanOptions::scan_pattern_to_printer`], except that the data is given as a slice, such as a memory
+    /// map of a file. This is faster than scanning a `Read`, as the data does not need to be copied before it is
+    /// searched.
+    ///
+    /// # Errors
+    ///
+    /// See [`scan_pattern_to_printer`](`crate::scan_pattern_to_printer`).
+    pub fn scan_slice_to_printer<P: Printer>(
+        &self,
+        slice: &[u8],
+        pattern: &str,
+        printer: P,
+    ) -> Result<ScanStats, Error> {
+        self.scan_haystack_to_printer(Haystack::<io::Empty>::Slice(slice), pattern, printer)
+    }
+
+    /// Write the lines of a `Read` that match the given pattern, as configured by these options, to the given
+    /// [`Write`] as rows of CSV. Like [`scan_matcher_to_csv_rows`](`crate::scan_matcher_to_csv_rows`), the header row
+    /// is not written. The theme is not used, as CSV has no colors.
+    ///
+    /// # Errors
+    ///
+    /// See [`scan_pattern_to_csv`](`crate::scan_pattern_to_csv`).
+    pub fn scan_pattern_to_csv_rows<R: Read, W: Write>(
+        &self,
+        reader: R,
+        pattern: &str,
+        source_name: &str,
+        writer: W,
+    ) -> Result<(), Error> {
+        let matcher = self.build_matcher(pattern)?;
+        if self.prefixed {
+            self.scan_matcher_to_csv_rows(
+                reader,
+                PrefixedMatcher::new(matcher),
+                source_name,
+                writer,
+            )
+        } else {
+            self.scan_matcher_to_csv_rows(reader, matcher, source_name, writer)
+        }
+    }
+
+    /// Write a line of the form `file:line:column:text` for each match of the given pattern within a `Read`, as
+    /// configured by these options, to the given [`Write`]. See
+    /// [`scan_pattern_to_vimgrep`](`crate::scan_pattern_to_vimgrep`) for the meaning of each part. The theme is not
+    /// used, as the lines have no colors.
+    ///
+    /// # Errors
+    ///
+    /// See [`scan_pattern_to_vimgrep`](`crate::scan_pattern_to_vimgrep`).
+    pub fn scan_pattern_to_vimgrep<R: Read, W: Write>(
+        &self,
+        reader: R,
+        pattern: &str,
+        source_name: &str,
+        writer: W,
+    ) -> Result<(), Error> {
+        let matcher = self.build_matcher(pattern)?;
+        if self.prefixed {
+            self.scan_matcher_to_vimgrep(reader, PrefixedMatcher::new(matcher), source_name, writer)
+        } else {
+            self.scan_matcher_to_vimgrep(reader, matcher, source_name, writer)
+        }
+    }
+
+    /// Write every line of a `Read` to the given [`Write`] as a JSON object, noting which lines match the given pattern,
+    /// as configured by these options, and return the statistics of the scan. See
+    /// [`scan_pattern_to_json`](`crate::scan_pattern_to_json`) for the format of the output. The theme is not used, as
+    /// JSON has no colors.
+    ///
+    /// # Errors
+    ///
+    /// See [`scan_pattern_to_json`](`crate::scan_pattern_to_json`).
+    pub fn scan_pattern_to_json<R: Read, W: Write>(
+        &self,
+        reader: R,
+        pattern: &str,
+        source_name: &str,
+        writer: W,
+    ) -> Result<JsonStats, Error> {
+        let matcher = self.build_matcher(pattern)?;
+        if self.prefixed {
+            self.scan_matcher_to_json(reader, PrefixedMatcher::new(matcher), source_name, writer)
+        } else {
+            self.scan_matcher_to_json(reader, matcher, source_name, writer)
+        }
+    }
+
+    /// Print a `Read`'s contents to the given [`Printer`], passing each line through the given plugin, and noting which
+    /// lines match the given pattern, as configured by these options. The theme is not used, as the plugin decides how
+    /// lines are highlighted. [`ScanStats`] about the scan are returned.
+    ///
+    /// # Errors
+    ///
+    /// See [`scan_pattern_with_plugin_to_printer`](`crate::scan_pattern_with_plugin_to_printer`).
+    #[cfg(feature = "lua")]
+    pub fn scan_pattern_with_plugin_to_printer<R: Read, P: Printer>(
+        &self,
+        reader: R,
+        pattern: &str,
+        plugin: &LuaPlugin,
+        printer: P,
+    ) -> Result<ScanStats, Error> {
+        let matcher = self.build_matcher(pattern)?;
+        if self.prefixed {
+            self.scan_matcher_with_plugin_to_printer(
+                reader,
+                PrefixedMatcher::new(matcher),
+                plugin,
+                printer,
+            )
+        } else {
+            self.scan_matcher_with_plugin_to_printer(reader, matcher, plugin, printer)
+        }
+    }
+
+    /// Call the given function with a [`LineEvent`] for every line of a `Read`, noting which lines match the given
+    /// pattern, as configured by these options. Events for matching lines hold the spans of the matches within them,
+    /// unless the lines that do not match are the ones highlighted. As nothing is printed, only the matching, line
+    /// terminator, and binary data options are used.
+    ///
+    /// # Errors
+    ///
+    /// See [`scan_pattern_with_callback`](`crate::scan_pattern_with_callback`).
+    pub fn scan_pattern_with_callback<R: Read, F: FnMut(&LineEvent)>(
+        &self,
+        reader: R,
+        pattern: &str,
+        callback: F,
+    ) -> Result<(), Error> {
+        let matcher = self.build_matcher(pattern)?;
+        if self.prefixed {
+            self.scan_matcher_with_callback(reader, PrefixedMatcher::new(matcher), callback)
+        } else {
+            self.scan_matcher_with_callback(reader, matcher, callback)
+        }
+    }
+
+    /// Print a `Read`'s contents to the given [`Printer`], handling each line as directed by the first of the given
+    /// [`Rules`] that matches it, as [`scan_rules_to_printer`](`crate::scan_rules_to_printer`) does. The rules decide
+    /// how lines are matched and highlighted, so only the line terminator and binary data options are used.
+    ///
+    /// # Errors
+    ///
+    /// See [`scan_rules_to_printer`](`crate::scan_rules_to_printer`).
+    pub fn scan_rules_to_printer<R: Read, P: Printer>(
+        &self,
+        reader: R,
+        rules: &Rules,
+        printer: P,
+    ) -> Result<ScanStats, Error> {
+        let mut sink = StatsSink::new(sink::RulesSink::new(rules, printer));
+        let searcher = self.searcher_builder().passthru(true).build();
+        self.search_reader(searcher, reader, rules, &mut sink)?;
+
+        Ok(sink.stats())
+    }
+
+    /// Search the given `Read` with the given [`Searcher`], naming the input in any error, as configured.
+    fn search_reader<R: Read, M: Matcher, S: Sink<Error = sink::Error>>(
+        &self,
+        searcher: Searcher,
+        reader: R,
+        matcher: M,
+        sink: S,
+    ) -> Result<(), Error> {
+        crate::search_reader(searcher, reader, matcher, sink).map_err(|err| self.name_error(err))
+    }
+
+    /// Attach the name of the input to the given error, if one was given.
+    fn name_error(&self, err: Error) -> Error {
+        match &self.input_name {
+            Some(input_name) => err.in_input(input_name),
+            None => err,
+        }
+    }
+
+    fn build_matcher(&self, pattern: &str) -> Result<RegexMatcher, Error> {
+        let pattern = if self.fixed_strings {
+            regex::escape(pattern)
+        } else {
+            pattern.to_string()
+        };
+
+        let matcher = RegexMatcherBuilder::new()
+            .case_insensitive(self.case_insensitive)
+            .crlf(self.crlf)
+            .multi_line(self.multi_line)
+            .build(&pattern)?;
+
+        Ok(matcher)
+    }
+
+    /// Build the regex that capture groups are found with, if they are to be highlighted.
+    fn build_capture_groups_regex(&self, pattern: &str) -> Option<Regex> {
+        // Prefixed lines are matched after their prefix, which the regex would not know to skip
+        if !self.capture_groups || self.invert || self.fixed_strings || self.prefixed {
+            return None;
+        }
+
+        // The matcher has already accepted the pattern, so this will only fail if the matcher allows something that
+        // regex does not, in which case the lines are highlighted whole
+        RegexBuilder::new(pattern)
+            .case_insensitive(self.case_insensitive)
+            .build()
+            .ok()
+    }
+
+    fn searcher_builder(&self) -> SearcherBuilder {
+        let mut builder = SearcherBuilder::new();
+        if self.crlf {
+            builder.line_terminator(LineTerminator::crlf());
+        } else {
+            builder.line_terminator(self.line_terminator);
+        }
+        if self.quit_on_binary {
+            builder.binary_detection(BinaryDetection::quit(b'\0'));
+        }
+
+        builder
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, haystack, printer), err)
+    )]
+    fn scan_haystack_to_printer<R: Read, P: Printer>(
+        &self,
+        haystack: Haystack<R>,
+        pattern: &str,
+        printer: P,
+    ) -> Result<ScanStats, Error> {
+        let matcher = self.build_matcher(pattern)?;
+        let dim_matcher = self
+            .dim
+            .as_deref()
+            .map(|dim_pattern| self.build_matcher(dim_pattern))
+            .transpose()?;
+        let alternate_highlight = self.alternate_color.map(|color| Style {
+            fg: Some(color),
+            ..self.theme.match_highlight()
+        });
+        let sink = ContextPrintingSink::with_theme(printer, &self.theme)
+            .alternate(alternate_highlight)
+            .max_count(self.max_count)
+            .stop_after(self.stop_after)
+            .passthru(!self.only_matching)
+            .capture_groups(self.build_capture_groups_regex(pattern))
+            .dim(dim_matcher)
+            .line_number(self.line_number)
+            .byte_offset(self.byte_offset)
+            .markers(Some(matcher.clone()).filter(|_| self.markers && !self.invert))
+            .max_columns(
+                self.max_columns,
+                Some(matcher.clone()).filter(|_| !self.invert),
+            )
+            .replace(
+                self.replacement
+                    .as_ref()
+                    .filter(|_| !self.invert)
+                    .map(|template| (matcher.clone(), template.as_bytes().to_vec())),
+            );
+        if self.prefixed {
+            self.scan_matcher_to_sink(haystack, PrefixedMatcher::new(matcher), sink)
+        } else {
+            self.scan_matcher_to_sink(haystack, matcher, sink)
+        }
+    }
+
+    fn scan_matcher_to_sink<R: Read, M: Matcher, P: Printer>(
+        &self,
+        haystack: Haystack<R>,
+        matcher: M,
+        sink: ContextPrintingSink<P, Style>,
+    ) -> Result<ScanStats, Error> {
+        let mut sink = StatsSink::new(sink);
+        let mut builder = self.searcher_builder();
+        builder.multi_line(self.multi_line);
+        if self.only_matching {
+            builder
+                .before_context(self.before_context)
+                .after_context(self.after_context);
+        } else {
+            builder.passthru(true);
+        }
+
+        let searcher = builder.build();
+        if self.invert {
+            haystack
+                .search(searcher, InvertedMatcher::new(matcher), &mut sink)
+                .map_err(|err| self.name_error(err))?;
+        } else {
+            haystack
+                .search(searcher, matcher, &mut sink)
+                .map_err(|err| self.name_error(err))?;
+        }
+
+        Ok(sink.stats())
+    }
+
+    fn scan_matcher_with_callback<R: Read, M: Matcher, F: FnMut(&LineEvent)>(
+        &self,
+        reader: R,
+        matcher: M,
+        callback: F,
+    ) -> Result<(), Error> {
+        let searcher = self
+            .searcher_builder()
+            .passthru(true)
+            .line_number(true)
+            .build();
+        if self.invert {
+            // The inverted matcher matches whole lines, so it has no spans worth reporting
+            let matcher = InvertedMatcher::new(matcher);
+            self.search_reader(searcher, reader, matcher, sink::CallbackSink::new(callback))
+        } else {
+            let callback_sink = sink::CallbackSink::with_matcher(&matcher, callback);
+            self.search_reader(searcher, reader, &matcher, callback_sink)
+        }
+    }
+
+    fn scan_matcher_to_csv_rows<R: Read, M: Matcher, W: Write>(
+        &self,
+        reader: R,
+        matcher: M,
+        source_name: &str,
+        writer: W,
+    ) -> Result<(), Error> {
+        let searcher = self.searcher_builder().line_number(true).build();
+        if self.invert {
+            let matcher = InvertedMatcher::new(matcher);
+            let csv_sink = sink::CsvSink::new(&matcher, source_name, writer);
+            self.search_reader(searcher, reader, &matcher, csv_sink)
+        } else {
+            let csv_sink = sink::CsvSink::new(&matcher, source_name, writer);
+            self.search_reader(searcher, reader, &matcher, csv_sink)
+        }
+    }
+
+    fn scan_matcher_to_vimgrep<R: Read, M: Matcher, W: Write>(
+        &self,
+        reader: R,
+        matcher: M,
+        source_name: &str,
+        writer: W,
+    ) -> Result<(), Error> {
+        let searcher = self.searcher_builder().line_number(true).build();
+        if self.invert {
+            let matcher = InvertedMatcher::new(matcher);
+            let vimgrep_sink = sink::VimgrepSink::new(&matcher, source_name, writer);
+            self.search_reader(searcher, reader, &matcher, vimgrep_sink)
+        } else {
+            let vimgrep_sink = sink::VimgrepSink::new(&matcher, source_name, writer);
+            self.search_reader(searcher, reader, &matcher, vimgrep_sink)
+        }
+    }
+
+    fn scan_matcher_to_json<R: Read, M: Matcher, W: Write>(
+        &self,
+        reader: R,
+        matcher: M,
+        source_name: &str,
+        writer: W,
+    ) -> Result<JsonStats, Error> {
+        let searcher = self.searcher_builder().passthru(true).build();
+        if self.invert {
+            let matcher = InvertedMatcher::new(matcher);
+            let mut json_sink = sink::JsonSink::new(&matcher, source_name, writer);
+            self.search_reader(searcher, reader, &matcher, &mut json_sink)?;
+            Ok(json_sink.stats())
+        } else {
+            let mut json_sink = sink::JsonSink::new(&matcher, source_name, writer);
+            self.search_reader(searcher, reader, &matcher, &mut json_sink)?;
+            Ok(json_sink.stats())
+        }
+    }
+
+    #[cfg(feature = "lua")]
+    fn scan_matcher_with_plugin_to_printer<R: Read, M: Matcher, P: Printer>(
+        &self,
+        reader: R,
+        matcher: M,
+        plugin: &LuaPlugin,
+        printer: P,
+    ) -> Result<ScanStats, Error> {
+        let searcher = self.searcher_builder().passthru(true).build();
+        if self.invert {
+            let matcher = InvertedMatcher::new(matcher);
+            let mut sink = StatsSink::new(sink::PluginSink::new(&matcher, plugin, printer));
+            self.search_reader(searcher, reader, &matcher, &mut sink)?;
+            Ok(sink.stats())
+        } else {
+            let mut sink = StatsSink::new(sink::PluginSink::new(&matcher, plugin, printer));
+            self.search_reader(searcher, reader, &matcher, &mut sink)?;
+            Ok(sink.stats())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{AnsiValue, Fg, Reset};
+    use crate::print;
+    use crate::print::WritePrinter;
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+    use test_case::test_case;
+
+    const SEARCH_TEXT: &str = "ERROR: a.b failed\nerror: axb failed\nINFO: ok\n";
+
+    #[test_case(&ScanOptions::new(), "ERROR", &["ERROR: a.b failed\n"]; "defaults")]
+    #[test_case(
+        ScanOptions::new().case_insensitive(true),
+        "error",
+        &["ERROR: a.b failed\n", "error: axb failed\n"];
+        "case insensitive"
+    )]
+    #[test_case(
+        ScanOptions::new().fixed_strings(true),
+        "a.b",
+        &["ERROR: a.b failed\n"];
+        "fixed strings"
+    )]
+    #[test_case(
+        ScanOptions::new().invert(true),
+        "failed",
+        &["INFO: ok\n"];
+        "inverted"
+    )]
+    fn test_highlights_as_configured(options: &ScanOptions, pattern: &str, expected: &[&str]) {
+        let mock_printer = MockPrinter::default();
+        let stats = options
+            .scan_pattern_to_printer(SEARCH_TEXT.as_bytes(), pattern, &mock_printer)
+            .expect("scan failed");
+
+        assert_eq!(expected.len() as u64, stats.lines_matched);
+        let expected = expected.iter().map(ToString::to_string).collect::<Vec<_>>();
+        testutil::assert_slices_eq!(&expected, &mock_printer.colored_messages.borrow());
+    }
+
+    #[test]
+    fn test_matches_across_lines() {
+        let mock_printer = MockPrinter::default();
+        let stats = ScanOptions::new()
+            .multi_line(true)
+            .scan_pattern_to_printer(SEARCH_TEXT.as_bytes(), r"failed\nerror", &mock_printer)
+            .expect("scan failed");
+
+        assert_eq!(2, stats.lines_matched);
+        testutil::assert_slices_eq!(
+            &["ERROR: a.b failed\nerror: axb failed\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+
+    #[test_case(false, &["INFO: ok\n"]; "with passthru")]
+    #[test_case(true, &[]; "only matching")]
+    fn test_only_matching_drops_context(only_matching: bool, expected_uncolored: &[&str]) {
+        let mock_printer = MockPrinter::default();
+        ScanOptions::new()
+            .only_matching(only_matching)
+            .scan_pattern_to_printer(SEARCH_TEXT.as_bytes(), "failed", &mock_printer)
+            .expect("scan failed");
+
+        let expected_uncolored = expected_uncolored
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        testutil::assert_slices_eq!(
+            &expected_uncolored,
+            &mock_printer.uncolored_messages.borrow()
+        );
+        assert_eq!(2, mock_printer.colored_messages.borrow().len());
+    }
+
+    #[test_case(false, &["ERROR: a.b failed\n", "error: axb failed\n"]; "with passthru")]
+    #[test_case(true, &["error: axb failed\n"]; "only matching")]
+    fn test_prints_context_before_matches(only_matching: bool, expected_uncolored: &[&str]) {
+        let mock_printer = MockPrinter::default();
+        ScanOptions::new()
+            .only_matching(only_matching)
+            .before_context(1)
+            .scan_pattern_to_printer(SEARCH_TEXT.as_bytes(), "INFO", &mock_printer)
+            .expect("scan failed");
+
+        let expected_uncolored = expected_uncolored
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        testutil::assert_slices_eq!(
+            &expected_uncolored,
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_highlights_capture_groups() {
+        let printer = WritePrinter::new(Vec::new());
+        ScanOptions::new()
+            .capture_groups(true)
+            .only_matching(true)
+            .scan_pattern_to_printer(SEARCH_TEXT.as_bytes(), "ERROR: (a.b)", &printer)
+            .expect("scan failed");
+
+        let expected = format!(
+            "{0}ERROR: {1}{2}a.b{1}{0} failed{1}\n",
+            Fg(AnsiValue(9)),
+            Fg(Reset),
+            Fg(AnsiValue(1))
+        );
+        assert_eq!(
+            expected,
+            String::from_utf8(printer.into_inner()).expect("not utf-8")
+        );
+    }
+
+    #[test]
+    fn test_reports_invalid_dim_pattern() {
+        let res = ScanOptions::new().dim(Some("(")).scan_pattern_to_printer(
+            SEARCH_TEXT.as_bytes(),
+            "ERROR",
+            MockPrinter::default(),
+        );
+
+        assert!(matches!(res, Err(Error::RegexError(_))));
+    }
+
+    #[test_case(None, "Printing results failed at line 2 (byte 18): boom"; "unnamed")]
+    #[test_case(Some("app.log"), "app.log: Printing results failed at line 2 (byte 18): boom"; "named")]
+    fn test_reports_where_printing_failed(input_name: Option<&str>, expected: &str) {
+        let mut mock_printer = MockPrinter::default();
+        mock_printer.fail_next(print::Error::Other(io::Error::other("boom")));
+        let res = ScanOptions::new()
+            .input_name(input_name)
+            .only_matching(true)
+            .scan_pattern_to_printer(SEARCH_TEXT.as_bytes(), "axb", &mock_printer);
+
+        assert_eq!(expected, res.expect_err("scan succeeded").to_string());
+    }
+
+    #[test_case(&ScanOptions::new(), &[(false, 0), (true, 1), (false, 0)]; "defaults")]
+    #[test_case(ScanOptions::new().case_insensitive(true), &[(true, 1), (true, 1), (false, 0)]; "case insensitive")]
+    #[test_case(ScanOptions::new().invert(true), &[(true, 0), (false, 0), (true, 0)]; "inverted")]
+    fn test_calls_back_as_configured(options: &ScanOptions, expected: &[(bool, usize)]) {
+        let mut events = Vec::new();
+        options
+            .scan_pattern_with_callback(SEARCH_TEXT.as_bytes(), "error", |event| {
+                events.push((event.matched, event.spans.len()));
+            })
+            .expect("scan failed");
+
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn test_scans_slice_like_reader() {
+        let options = ScanOptions::new();
+        let reader_printer = MockPrinter::default();
+        let slice_printer = MockPrinter::default();
+        let reader_stats = options
+            .scan_pattern_to_printer(SEARCH_TEXT.as_bytes(), "failed", &reader_printer)
+            .expect("scan failed");
+        let slice_stats = options
+            .scan_slice_to_printer(SEARCH_TEXT.as_bytes(), "failed", &slice_printer)
+            .expect("scan failed");
+
+        assert_eq!(reader_stats.lines_matched, slice_stats.lines_matched);
+        testutil::assert_slices_eq!(
+            &reader_printer.colored_messages.borrow(),
+            &slice_printer.colored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &reader_printer.uncolored_messages.borrow(),
+            &slice_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test_case(false, 0; "disabled")]
+    #[test_case(true, 2; "enabled")]
+    fn test_crlf_matches_end_before_carriage_return(crlf: bool, expected_matches: u64) {
+        let stats = ScanOptions::new()
+            .crlf(crlf)
+            .scan_pattern_to_printer(
+                "ERROR: failed\r\nINFO: ok\r\nerror: failed\r\n".as_bytes(),
+                "failed$",
+                MockPrinter::default(),
+            )
+            .expect("scan failed");
+
+        assert_eq!(expected_matches, stats.lines_matched);
+    }
+
+    #[test_case(false; "reader")]
+    #[test_case(true; "slice")]
+    fn test_quits_on_binary_data_past_start_of_input(slice: bool) {
+        let mut input = "hello\n".repeat(100);
+        let binary_offset = input.len() as u64;
+        input.push_str("\0world\n");
+
+        let mut options = ScanOptions::new();
+        options.quit_on_binary(true);
+        let res = if slice {
+            options.scan_slice_to_printer(input.as_bytes(), "world", MockPrinter::default())
+        } else {
+            options.scan_pattern_to_printer(input.as_bytes(), "world", MockPrinter::default())
+        };
+
+        assert!(matches!(res, Err(Error::BinaryData { offset }) if offset == binary_offset));
+    }
+
+    #[test]
+    fn test_does_not_detect_binary_data_unless_quitting() {
+        let mut options = ScanOptions::new();
+        options.quit_on_binary(false);
+        let searcher = options.searcher_builder().build();
+        assert_eq!(None, searcher.binary_detection().quit_byte());
+
+        let stats = options
+            .scan_pattern_to_printer(
+                b"hello\n\0world\n".as_slice(),
+                "world",
+                MockPrinter::default(),
+            )
+            .expect("scan failed");
+        assert_eq!(1, stats.lines_matched);
+    }
+}
@@ -0,0 +1,287 @@
+//! `plugin` allows each line to be inspected, and transformed, by a Lua script before it is printed. This is only
+//! available when built with the `lua` feature.
+//!
+//! A plugin must define a global `on_line` function, which is called with each line (without its line terminator) and
+//! a table describing it:
+//!
+//! ```lua
+//! function on_line(line, info)
+//!     -- info.line_number is the number of the line, starting at 1.
+//!     -- info.matched is whether the line matched the pattern.
+//!     -- info.matches holds a { first = ..., last = ... } table for each match, giving the 1-based, inclusive byte
+//!     -- positions of the match, so that string.sub(line, match.first, match.last) is the matched text.
+//!     if line:find("password") then
+//!         return { line = line:gsub("password=%S+", "password=***"), color = "yellow" }
+//!     end
+//! end
+//! ```
+//!
+//! `on_line` may return
+//! - `nil` (or nothing), to print the line as it would have been printed without the plugin;
+//! - `false`, to not print the line at all;
+//! - a string, to print in place of the line;
+//! - a table with any of the fields `line`, a string to print in place of the line, `color`, a color to highlight the
+//!   line in (as for [`rules`](`crate::rules`)), even if it did not match, and `drop`, which has the same effect as
+//!   returning `false` if it is true.
+use crate::color::{AnsiValue, ParseColorError};
+use mlua::{Function, Lua, RegistryKey, Table, Value};
+use std::ops::Range;
+use thiserror::Error;
+
+/// The name of the function that each plugin must define.
+const HOOK_NAME: &str = "on_line";
+
+/// `Error` represents the possible errors that can occur when loading or running a plugin.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The plugin could not be loaded, such as due to a syntax error.
+    #[error("Loading the plugin failed: {0}")]
+    LoadFailed(
+        /// The original error produced by Lua.
+        mlua::Error,
+    ),
+    /// The plugin did not define an `on_line` function.
+    #[error("The plugin does not define an {HOOK_NAME} function")]
+    MissingHook,
+    /// The plugin raised an error while handling a line.
+    #[error("The plugin failed: {0}")]
+    RunFailed(
+        /// The original error produced by Lua.
+        mlua::Error,
+    ),
+    /// The plugin returned something other than one of the values described in the [module documentation](`self`).
+    #[error("The plugin returned an invalid result: {0}")]
+    InvalidResult(
+        /// A description of what was wrong with the result.
+        String,
+    ),
+    /// The plugin returned a color that was not valid.
+    #[error("The plugin returned an invalid color: {0}")]
+    InvalidColor(
+        /// The original error produced while parsing the color.
+        ParseColorError,
+    ),
+}
+
+/// `Verdict` represents what a plugin decided should be done with a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// Print the line as it would have been printed without the plugin.
+    Keep,
+    /// Do not print the line at all.
+    Drop,
+    /// Print the line with the given changes.
+    Transform {
+        /// The text to print in place of the line, if it should be replaced.
+        line: Option<String>,
+        /// The color to highlight the line in, if it should be highlighted differently.
+        color: Option<AnsiValue>,
+    },
+}
+
+/// `LineInfo` is the information about a line that is given to a plugin alongside it.
+#[derive(Debug, Clone, Copy)]
+pub struct LineInfo<'a> {
+    /// The number of the line within the input, starting at 1, if it is known.
+    pub line_number: Option<u64>,
+    /// Whether the line matched the pattern being searched for.
+    pub matched: bool,
+    /// The byte ranges of each match within the line.
+    pub matches: &'a [Range<usize>],
+}
+
+/// `LuaPlugin` is a loaded Lua plugin. See the [module documentation](`self`) for what a plugin must provide.
+pub struct LuaPlugin {
+    lua: Lua,
+    on_line: RegistryKey,
+}
+
+impl LuaPlugin {
+    /// Load a plugin from the given Lua source. `name` is used to identify the plugin in Lua's error messages, and is
+    /// typically the path it was loaded from.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::LoadFailed`] if the source could not be run, or [`Error::MissingHook`] if it does not define
+    /// an `on_line` function.
+    pub fn load(source: &str, name: &str) -> Result<Self, Error> {
+        let lua = Lua::new();
+        // The @ tells Lua that this is the name of a file, so that it is shown as-is in error messages
+        lua.load(source)
+            .set_name(format!("@{name}"))
+            .exec()
+            .map_err(Error::LoadFailed)?;
+
+        let on_line = match lua.globals().get(HOOK_NAME).map_err(Error::LoadFailed)? {
+            Value::Function(on_line) => lua
+                .create_registry_value(on_line)
+                .map_err(Error::LoadFailed)?,
+            _ => return Err(Error::MissingHook),
+        };
+
+        Ok(Self { lua, on_line })
+    }
+
+    /// Pass the given line, which should not include its line terminator, to the plugin, and get what it decided
+    /// should be done with it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::RunFailed`] if the plugin raised an error, or [`Error::InvalidResult`] or
+    /// [`Error::InvalidColor`] if it returned something that could not be understood.
+    pub fn handle_line(&self, line: &[u8], info: LineInfo) -> Result<Verdict, Error> {
+        let on_line: Function = self
+            .lua
+            .registry_value(&self.on_line)
+            .map_err(Error::RunFailed)?;
+        let line = self.lua.create_string(line).map_err(Error::RunFailed)?;
+        let info = self.make_info_table(info).map_err(Error::RunFailed)?;
+
+        match on_line.call((line, info)).map_err(Error::RunFailed)? {
+            Value::Nil | Value::Boolean(true) => Ok(Verdict::Keep),
+            Value::Boolean(false) => Ok(Verdict::Drop),
+            Value::String(line) => Ok(Verdict::Transform {
+                line: Some(String::from_utf8_lossy(line.as_bytes()).into_owned()),
+                color: None,
+            }),
+            Value::Table(verdict) => verdict_from_table(&verdict),
+            other => Err(Error::InvalidResult(format!(
+                "expected nil, false, a string, or a table, but got a {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    fn make_info_table(&self, info: LineInfo) -> mlua::Result<Table<'_>> {
+        let matches = self.lua.create_table()?;
+        for range in info.matches {
+            let lua_match = self.lua.create_table()?;
+            // Lua's strings are indexed from 1, and their ranges include their end
+            lua_match.set("first", range.start + 1)?;
+            lua_match.set("last", range.end)?;
+            matches.push(lua_match)?;
+        }
+
+        let table = self.lua.create_table()?;
+        table.set("line_number", info.line_number)?;
+        table.set("matched", info.matched)?;
+        table.set("matches", matches)?;
+
+        Ok(table)
+    }
+}
+
+/// Get the verdict described by the table a plugin returned.
+fn verdict_from_table(table: &Table) -> Result<Verdict, Error> {
+    let invalid_field = |err: mlua::Error| Error::InvalidResult(err.to_string());
+    let drop: Option<bool> = table.get("drop").map_err(invalid_field)?;
+    if drop == Some(true) {
+        return Ok(Verdict::Drop);
+    }
+
+    let line: Option<mlua::String> = table.get("line").map_err(invalid_field)?;
+    let color: Option<mlua::String> = table.get("color").map_err(invalid_field)?;
+    let color = color
+        .map(|color| color.to_string_lossy().parse())
+        .transpose()
+        .map_err(Error::InvalidColor)?;
+
+    Ok(Verdict::Transform {
+        line: line.map(|line| String::from_utf8_lossy(line.as_bytes()).into_owned()),
+        color,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const NO_INFO: LineInfo = LineInfo {
+        line_number: Some(1),
+        matched: false,
+        matches: &[],
+    };
+
+    #[test_case("function on_line(line, info) end", &Verdict::Keep; "nothing")]
+    #[test_case("function on_line(line, info) return false end", &Verdict::Drop; "false")]
+    #[test_case("function on_line(line, info) return { drop = true } end", &Verdict::Drop; "drop field")]
+    #[test_case(
+        "function on_line(line, info) return line:upper() end",
+        &Verdict::Transform { line: Some("HELLO".to_string()), color: None };
+        "string"
+    )]
+    #[test_case(
+        "function on_line(line, info) return { color = \"green\" } end",
+        &Verdict::Transform { line: None, color: Some(AnsiValue(2)) };
+        "color field"
+    )]
+    fn test_handles_line(source: &str, expected: &Verdict) {
+        let plugin = LuaPlugin::load(source, "test").expect("failed to load plugin");
+        let verdict = plugin
+            .handle_line(b"hello", NO_INFO)
+            .expect("failed to handle line");
+
+        assert_eq!(expected, &verdict);
+    }
+
+    #[test]
+    fn test_passes_line_info() {
+        let source = r#"
+            function on_line(line, info)
+                local second = info.matches[2]
+                return string.format(
+                    "%d %s %s",
+                    info.line_number,
+                    tostring(info.matched),
+                    line:sub(second.first, second.last)
+                )
+            end
+        "#;
+        let plugin = LuaPlugin::load(source, "test").expect("failed to load plugin");
+        let info = LineInfo {
+            line_number: Some(3),
+            matched: true,
+            matches: &[0..5, 6..11],
+        };
+        let verdict = plugin
+            .handle_line(b"hello world", info)
+            .expect("failed to handle line");
+
+        assert_eq!(
+            Verdict::Transform {
+                line: Some("3 true world".to_string()),
+                color: None
+            },
+            verdict
+        );
+    }
+
+    #[test_case("function on_line(", "Loading the plugin failed"; "syntax error")]
+    #[test_case("function on_each_line() end", "The plugin does not define"; "no hook")]
+    fn test_invalid_plugin(source: &str, expected_msg_prefix: &str) {
+        let err = LuaPlugin::load(source, "test")
+            .err()
+            .expect("plugin should not have loaded");
+
+        assert!(
+            err.to_string().starts_with(expected_msg_prefix),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test_case("function on_line() error(\"oh no\") end", "The plugin failed"; "error")]
+    #[test_case("function on_line() return 5 end", "The plugin returned an invalid result"; "number")]
+    #[test_case("function on_line() return { color = \"reddish\" } end", "The plugin returned an invalid color"; "bad color")]
+    fn test_failing_plugin(source: &str, expected_msg_prefix: &str) {
+        let plugin = LuaPlugin::load(source, "test").expect("failed to load plugin");
+        let err = plugin
+            .handle_line(b"hello", NO_INFO)
+            .expect_err("plugin should have failed");
+
+        assert!(
+            err.to_string().starts_with(expected_msg_prefix),
+            "unexpected error: {err}"
+        );
+    }
+}
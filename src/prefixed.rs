@@ -0,0 +1,113 @@
+//! `prefixed` provides utilities to handle streams where every line starts with the name of its source, such as the
+//! output of `kubectl logs --prefix` (`[pod/web-1/app] message`) or `docker compose logs` (`web-1  | message`).
+use grep::matcher::{Match, Matcher, NoCaptures};
+
+/// `PrefixedMatcher` wraps another [`Matcher`] so that it only matches against the message portion of a line, ignoring
+/// its source prefix. Patterns anchored with `^` will therefore be anchored to the start of the message.
+///
+/// Note that this matcher operates on a line-by-line basis; it must not be used with a multi-line searcher.
+#[allow(clippy::module_name_repetitions)]
+pub struct PrefixedMatcher<M: Matcher> {
+    matcher: M,
+}
+
+impl<M: Matcher> PrefixedMatcher<M> {
+    /// Make a new `PrefixedMatcher` that wraps the given [`Matcher`]
+    pub fn new(matcher: M) -> Self {
+        Self { matcher }
+    }
+}
+
+impl<M: Matcher> Matcher for PrefixedMatcher<M> {
+    // Captures would be reported relative to the message, rather than the line. We don't need them, so we simply
+    // don't support them.
+    type Captures = NoCaptures;
+    type Error = M::Error;
+
+    fn find_at(&self, haystack: &[u8], at: usize) -> Result<Option<Match>, Self::Error> {
+        let prefix_len = prefix_len(haystack);
+        let message_at = at.saturating_sub(prefix_len);
+        let found = self.matcher.find_at(&haystack[prefix_len..], message_at)?;
+
+        Ok(found.map(|found_match| found_match.offset(prefix_len)))
+    }
+
+    fn new_captures(&self) -> Result<Self::Captures, Self::Error> {
+        Ok(NoCaptures::new())
+    }
+}
+
+/// `prefix_len` gets the length of the source prefix at the start of the given line, including any separator following
+/// it. If the line does not have a prefix, zero is returned.
+pub(crate) fn prefix_len(line: &[u8]) -> usize {
+    bracketed_prefix_len(line)
+        .or_else(|| piped_prefix_len(line))
+        .unwrap_or(0)
+}
+
+/// `bracketed_prefix_len` gets the length of a prefix of the form `[source] `, as produced by kubectl.
+fn bracketed_prefix_len(line: &[u8]) -> Option<usize> {
+    if line.first() != Some(&b'[') {
+        return None;
+    }
+
+    let closing_idx = line.iter().position(|&b| b == b']')?;
+    let separator_len = usize::from(line.get(closing_idx + 1) == Some(&b' '));
+
+    Some(closing_idx + 1 + separator_len)
+}
+
+/// `piped_prefix_len` gets the length of a prefix of the form `source  | `, as produced by docker compose.
+fn piped_prefix_len(line: &[u8]) -> Option<usize> {
+    let source_len = line
+        .iter()
+        .take_while(|&&b| !b.is_ascii_whitespace() && b != b'|')
+        .count();
+    if source_len == 0 {
+        return None;
+    }
+
+    let padding_len = line[source_len..]
+        .iter()
+        .take_while(|&&b| b == b' ')
+        .count();
+    let pipe_idx = source_len + padding_len;
+    if line.get(pipe_idx) != Some(&b'|') {
+        return None;
+    }
+
+    let separator_len = usize::from(line.get(pipe_idx + 1) == Some(&b' '));
+
+    Some(pipe_idx + 1 + separator_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grep::regex::RegexMatcher;
+    use test_case::test_case;
+
+    #[test_case(b"[pod/web-1/app] hello", 16; "kubectl prefix")]
+    #[test_case(b"[pod/web-1/app]hello", 15; "kubectl prefix without space")]
+    #[test_case(b"web-1  | hello", 9; "compose prefix")]
+    #[test_case(b"web-1|hello", 6; "compose prefix without padding")]
+    #[test_case(b"hello world", 0; "no prefix")]
+    #[test_case(b"hello world | foo", 0; "pipe later in the line")]
+    #[test_case(b"[unclosed hello", 0; "unclosed bracket")]
+    #[test_case(b"", 0; "empty line")]
+    fn test_prefix_len(line: &[u8], expected: usize) {
+        assert_eq!(expected, prefix_len(line));
+    }
+
+    #[test_case("web", b"web-1  | hello", None; "does not match prefix")]
+    #[test_case("hello", b"web-1  | hello", Some(Match::new(9, 14)); "matches message")]
+    #[test_case("^hello", b"[pod/web-1/app] hello", Some(Match::new(16, 21)); "anchors to start of message")]
+    #[test_case("hello", b"hello world", Some(Match::new(0, 5)); "lines without prefixes match normally")]
+    fn test_matches_only_message(pattern: &str, line: &[u8], expected: Option<Match>) {
+        let matcher =
+            PrefixedMatcher::new(RegexMatcher::new(pattern).expect("regexp doesn't compile"));
+        let found = matcher.find(line).expect("failed to match");
+
+        assert_eq!(expected, found);
+    }
+}
@@ -1,12 +1,40 @@
 //! `print` provides utilities to facilitate printing out search results.
+use crate::ansi;
 use crate::lines;
-use std::fmt;
+use crate::style::{End, Highlight, Start};
+use std::cell::RefCell;
 use std::io;
-use std::io::Write;
+use std::io::{StdoutLock, Write};
+use std::ops::Range;
 use std::result;
-use termion::color::{Color, Fg, Reset};
 use thiserror::Error;
 
+mod buffered;
+mod decorated;
+mod escaped;
+mod filename;
+mod plain;
+mod prefixed;
+mod resetting;
+mod squeezing;
+mod tail;
+mod tee;
+mod wrapping;
+
+pub use buffered::{BufferedPrinter, FlushPolicy};
+pub use decorated::{DecoratedPrinter, Gutter};
+#[cfg(feature = "terminal")]
+pub(crate) use escaped::push_caret_notation;
+pub use escaped::EscapingPrinter;
+pub use filename::FilenamePrinter;
+pub use plain::PlainPrinter;
+pub use prefixed::PrefixedPrinter;
+pub use resetting::ResettingPrinter;
+pub use squeezing::SqueezingPrinter;
+pub use tail::TailHeaderPrinter;
+pub use tee::MatchTeePrinter;
+pub use wrapping::WrappingPrinter;
+
 pub(crate) type Result = result::Result<(), Error>;
 
 /// Error is a simple wrapper for [`io::Error`] that differentiates between certain error kinds as part of the type.
@@ -33,7 +61,10 @@ impl From<io::Error> for Error {
     }
 }
 
-/// `Printer` represents an object that can perform some kind of printing, such as by the print! macro
+/// `Printer` represents an object that can perform some kind of printing, such as by the print! macro.
+///
+/// Messages are given as bytes, or anything that can be viewed as bytes, such as a `&str` or a `String`. They are
+/// printed as they are given, so input that is not valid UTF-8 can be printed without being altered.
 pub trait Printer {
     /// Print the given message.
     ///
@@ -41,65 +72,218 @@ pub trait Printer {
     /// In the event of any i/o error, an error is returned. The type [enum@Error] gives implementors the freedom to
     /// specify whether or not this error was due to some kind of broken pipe error, which callers may choose to execute
     /// specific behavior. The docs of [enum@Error] specify more information about this.
-    fn print<S: fmt::Display>(&self, msg: S) -> Result;
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result;
 
-    /// Print the given message with the given foreground color.
+    /// Print the given message with the given [`Highlight`], such as a foreground color (e.g. `Fg(LightRed)`). Any
+    /// colors already present in the message are removed, so that they do not interfere with the given highlight.
     ///
     /// # Errors
     /// In the event of any i/o error, an error is returned. The type [enum@Error] gives implementors the freedom to
     /// specify whether or not this error was due to some kind of broken pipe error, which callers may choose to
     /// execute specific behavior. The docs of [enum@Error] specify more information about this.
-    fn colored_print<S: fmt::Display, C: Color>(&self, color: Fg<C>, msg: S) -> Result {
-        let msg_string = msg.to_string();
-        let colored_msg: String = lines::line_split(&msg_string)
-            .map(|(component, joining_newline)| {
-                if component.is_empty() {
-                    return joining_newline.unwrap_or_default().to_string();
-                }
-
-                format!(
-                    "{color}{component}{reset}{joining_newline}",
-                    color = color,
-                    reset = Fg(Reset),
-                    component = component,
-                    joining_newline = joining_newline.unwrap_or_default()
-                )
-            })
-            .collect();
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        let mut colored_msg = Vec::with_capacity(msg.as_ref().len());
+        colorize(&mut colored_msg, &highlight, msg.as_ref());
 
         self.print(colored_msg)
     }
+
+    /// Print the given line with the given [`Highlight`], as [`colored_print`](`Printer::colored_print`) does, except
+    /// that each of the given spans of the line is highlighted with its own [`Highlight`] in its place, such as to
+    /// color the capture groups of a match. Spans are byte ranges of `line`, and must be in order and not overlap.
+    /// `line` must be a single line, which may end in a newline.
+    ///
+    /// # Errors
+    /// In the event of any i/o error, an error is returned. The type [enum@Error] gives implementors the freedom to
+    /// specify whether or not this error was due to some kind of broken pipe error, which callers may choose to
+    /// execute specific behavior. The docs of [enum@Error] specify more information about this.
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        spans: &[(Range<usize>, G)],
+    ) -> Result {
+        let mut colored_line = Vec::with_capacity(line.len());
+        let mut pos = 0;
+        for (span, span_highlight) in spans {
+            colorize(&mut colored_line, &highlight, &line[pos..span.start]);
+            colorize(&mut colored_line, span_highlight, &line[span.clone()]);
+            pos = span.end;
+        }
+
+        colorize(&mut colored_line, &highlight, &line[pos..]);
+
+        self.print(colored_line)
+    }
+
+    /// Print the given message with the given [`Highlight`], such as to color lines that did not match. Unlike
+    /// [`colored_print`](`Printer::colored_print`), any colors already present in the message are kept, and the message
+    /// is not treated as a match by printers that handle matches specially, such as [`MatchTeePrinter`].
+    ///
+    /// # Errors
+    /// In the event of any i/o error, an error is returned. The type [enum@Error] gives implementors the freedom to
+    /// specify whether or not this error was due to some kind of broken pipe error, which callers may choose to
+    /// execute specific behavior. The docs of [enum@Error] specify more information about this.
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        let msg = msg.as_ref();
+        let mut styled_msg = Vec::with_capacity(msg.len());
+        for (component, joining_newline) in lines::line_split(msg) {
+            if !component.is_empty() {
+                wrap_in_highlight(&mut styled_msg, &highlight, component);
+            }
+
+            styled_msg.extend_from_slice(joining_newline.unwrap_or_default());
+        }
+
+        self.print(styled_msg)
+    }
+}
+
+impl<P: Printer> Printer for &P {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        (*self).print(msg)
+    }
+
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        (*self).colored_print(highlight, msg)
+    }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        spans: &[(Range<usize>, G)],
+    ) -> Result {
+        (*self).colored_print_spans(highlight, line, spans)
+    }
+
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        (*self).styled_print(highlight, msg)
+    }
+}
+
+/// Append each line of the given message to `colored_msg`, wrapped in the given [`Highlight`], removing any colors
+/// already present in it.
+fn colorize<H: Highlight>(colored_msg: &mut Vec<u8>, highlight: &H, msg: &[u8]) {
+    for (component, joining_newline) in lines::line_split(msg) {
+        let component = ansi::strip_sgr_sequences(component);
+        if !component.is_empty() {
+            wrap_in_highlight(colored_msg, highlight, &component);
+        }
+
+        colored_msg.extend_from_slice(joining_newline.unwrap_or_default());
+    }
+}
+
+/// Append the given bytes to `buf`, wrapped in the escape sequences that apply and undo the given [`Highlight`].
+fn wrap_in_highlight<H: Highlight>(buf: &mut Vec<u8>, highlight: &H, bytes: &[u8]) {
+    // Writing to a Vec cannot fail
+    write!(buf, "{}", Start(highlight)).unwrap();
+    buf.extend_from_slice(bytes);
+    write!(buf, "{}", End(highlight)).unwrap();
 }
 
 /// `StdoutPrinter` is, quite simply, a printer that will print to stdout.
+#[derive(Default)]
 pub struct StdoutPrinter;
 
 impl StdoutPrinter {
     #[must_use]
     pub fn new() -> Self {
-        Self::default()
+        Self
+    }
+}
+
+impl Printer for StdoutPrinter {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(len = msg.as_ref().len())))]
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        Ok(io::stdout().write_all(msg.as_ref())?)
+    }
+}
+
+/// `LockedStdoutPrinter` is a printer that will print to stdout, holding its lock for as long as the printer lives.
+/// [`StdoutPrinter`] locks stdout again for every message, which adds up when printing large inputs, so this is best
+/// used for the duration of a scan. Any other threads that print to stdout will be blocked until it is dropped.
+#[allow(clippy::module_name_repetitions)]
+pub struct LockedStdoutPrinter {
+    stdout: RefCell<StdoutLock<'static>>,
+}
+
+impl LockedStdoutPrinter {
+    /// Make a new `LockedStdoutPrinter`, locking stdout until it is dropped.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            stdout: RefCell::new(io::stdout().lock()),
+        }
     }
 }
 
-impl Default for StdoutPrinter {
+impl Default for LockedStdoutPrinter {
     fn default() -> Self {
-        Self {}
+        Self::new()
     }
 }
 
-impl Printer for StdoutPrinter {
-    fn print<S: fmt::Display>(&self, msg: S) -> Result {
-        let mut stdout = io::stdout();
-        Ok(write!(stdout, "{}", msg)?)
+impl Printer for LockedStdoutPrinter {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(len = msg.as_ref().len())))]
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        Ok(self.stdout.borrow_mut().write_all(msg.as_ref())?)
+    }
+}
+
+/// `StderrPrinter` is a printer that will print to stderr. This is useful for reporting diagnostics, as its errors
+/// can be handled in the same way as any other [`Printer`], rather than panicking like `eprint!`.
+#[derive(Default)]
+pub struct StderrPrinter;
+
+impl StderrPrinter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Printer for StderrPrinter {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(len = msg.as_ref().len())))]
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        Ok(io::stderr().write_all(msg.as_ref())?)
+    }
+}
+
+/// `WritePrinter` is a printer that will print to any [`Write`], such as a file, a socket, or an in-memory buffer.
+#[allow(clippy::module_name_repetitions)]
+pub struct WritePrinter<W: Write> {
+    writer: RefCell<W>,
+}
+
+impl<W: Write> WritePrinter<W> {
+    /// Make a new `WritePrinter` that prints to the given [`Write`].
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: RefCell::new(writer),
+        }
+    }
+
+    /// Get the wrapped [`Write`] back.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+}
+
+impl<W: Write> Printer for WritePrinter<W> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(len = msg.as_ref().len())))]
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        Ok(self.writer.borrow_mut().write_all(msg.as_ref())?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::color::{Bg, Fg, Magenta, Reset, Yellow};
     use crate::testutil;
     use crate::testutil::mock_print::BarebonesMockPrinter;
-    use termion::color::Magenta;
     use test_case::test_case;
 
     #[test_case(
@@ -131,11 +315,21 @@ mod tests {
         format!("{0}foo{1}\n{0}bar{1}\n", Fg(Magenta), Fg(Reset));
         "puts reset char before newlines"
     )]
+    #[test_case(
+        "foo\r\nbar\r\n".to_string(),
+        format!("{0}foo{1}\r\n{0}bar{1}\r\n", Fg(Magenta), Fg(Reset));
+        "puts reset char before carriage returns"
+    )]
     #[test_case(
         "hello\n\n\nworld".to_string(),
         format!("{0}hello{1}\n\n\n{0}world{1}", Fg(Magenta), Fg(Reset));
         "empty strings don't need colorization"
     )]
+    #[test_case(
+        "\x1b[32m+hello\x1b[m\n".to_string(),
+        format!("{0}+hello{1}\n", Fg(Magenta), Fg(Reset));
+        "existing colors are replaced"
+    )]
     fn test_resets_colors_properly(message: String, expected: String) {
         // We're using a mock here specifically so we can test the default implementation of colored_print
         let printer = BarebonesMockPrinter::default();
@@ -144,4 +338,60 @@ mod tests {
 
         testutil::assert_slices_eq!(&[expected], &printer.messages.borrow());
     }
+
+    #[test]
+    fn test_write_printer_prints_to_writer() {
+        let printer = WritePrinter::new(Vec::new());
+        printer.print("hello ").expect("print failed");
+        printer
+            .colored_print(Fg(Magenta), "world\n")
+            .expect("print failed");
+
+        let expected = format!("hello {}world{}\n", Fg(Magenta), Fg(Reset));
+        assert_eq!(
+            expected,
+            String::from_utf8(printer.into_inner()).expect("not utf-8")
+        );
+    }
+
+    #[test]
+    fn test_colors_spans_separately() {
+        let printer = BarebonesMockPrinter::default();
+        let res = printer.colored_print_spans(Fg(Magenta), b"foo bar baz\n", &[(4..7, Fg(Yellow))]);
+        assert!(res.is_ok(), "{}", res.unwrap_err());
+
+        let expected = format!(
+            "{0}foo {1}{2}bar{1}{0} baz{1}\n",
+            Fg(Magenta),
+            Fg(Reset),
+            Fg(Yellow)
+        );
+        testutil::assert_slices_eq!(&[expected], &printer.messages.borrow());
+    }
+
+    #[test]
+    fn test_styled_print_keeps_existing_colors() {
+        let printer = BarebonesMockPrinter::default();
+        let res = printer.styled_print(Fg(Magenta), "\x1b[32mfoo\x1b[m\n");
+        assert!(res.is_ok(), "{}", res.unwrap_err());
+
+        let expected = format!("{}\x1b[32mfoo\x1b[m{}\n", Fg(Magenta), Fg(Reset));
+        testutil::assert_slices_eq!(&[expected], &printer.messages.borrow());
+    }
+
+    #[test]
+    fn test_prints_with_background() {
+        let printer = BarebonesMockPrinter::default();
+        let res = printer.colored_print((Fg(Magenta), Bg(Yellow)), "foo\nbar");
+        assert!(res.is_ok(), "{}", res.unwrap_err());
+
+        let expected = format!(
+            "{0}{1}foo{2}{3}\n{0}{1}bar{2}{3}",
+            Fg(Magenta),
+            Bg(Yellow),
+            Fg(Reset),
+            Bg(Reset)
+        );
+        testutil::assert_slices_eq!(&[expected], &printer.messages.borrow());
+    }
 }
@@ -0,0 +1,140 @@
+use super::{Error, Printer, Result};
+use std::cell::{Cell, RefCell};
+use std::io::{BufWriter, Write};
+
+/// `FlushPolicy` decides when a [`BufferedPrinter`] flushes its buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// Flush after every message that ends a line, so that each line is visible as soon as it is printed. This is
+    /// best when printing to a terminal.
+    Line,
+    /// Only flush when the buffer is full, or when [`BufferedPrinter::flush`] is called. This is best when printing
+    /// large amounts of output to a file or a pipe.
+    WhenFull,
+}
+
+/// `BufferedPrinter` is a printer that buffers its output to the given [`Write`], flushing it as directed by its
+/// [`FlushPolicy`]. This avoids the cost of writing every message individually, which can be significant when printing
+/// large inputs.
+///
+/// Any buffered output is flushed when the printer is dropped, but errors can only be observed by calling
+/// [`BufferedPrinter::flush`]. A broken pipe stops a search without failing it, so whether one happened can be checked
+/// afterwards with [`BufferedPrinter::broken_pipe`].
+#[allow(clippy::module_name_repetitions)]
+pub struct BufferedPrinter<W: Write> {
+    writer: RefCell<BufWriter<W>>,
+    flush_policy: FlushPolicy,
+    broken_pipe: Cell<bool>,
+}
+
+impl<W: Write> BufferedPrinter<W> {
+    /// Make a new `BufferedPrinter` that prints to the given [`Write`], flushing as directed by the given
+    /// [`FlushPolicy`].
+    pub fn new(writer: W, flush_policy: FlushPolicy) -> Self {
+        Self {
+            writer: RefCell::new(BufWriter::new(writer)),
+            flush_policy,
+            broken_pipe: Cell::new(false),
+        }
+    }
+
+    /// Flush any buffered output.
+    ///
+    /// # Errors
+    ///
+    /// See [`Printer::print`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn flush(&self) -> Result {
+        let res = self.writer.borrow_mut().flush();
+        self.record_result(res.map_err(Error::from))
+    }
+
+    /// Whether printing or flushing has failed because the pipe being printed to was closed (e.g. by `head`).
+    pub fn broken_pipe(&self) -> bool {
+        self.broken_pipe.get()
+    }
+
+    /// Note if the given result is a broken pipe, before handing it back.
+    fn record_result(&self, res: Result) -> Result {
+        if let Err(Error::BrokenPipe(_)) = res {
+            self.broken_pipe.set(true);
+        }
+
+        res
+    }
+}
+
+impl<W: Write> Printer for BufferedPrinter<W> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(len = msg.as_ref().len())))]
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        let msg = msg.as_ref();
+        let res = self.writer.borrow_mut().write_all(msg);
+        self.record_result(res.map_err(Error::from))?;
+
+        if self.flush_policy == FlushPolicy::Line && msg.contains(&b'\n') {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::rc::Rc;
+    use test_case::test_case;
+
+    /// A [`Write`] that can be inspected while it is owned by a [`BufferedPrinter`].
+    #[derive(Clone, Default)]
+    struct SharedWriter(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test_case(FlushPolicy::Line, "hello\n"; "line")]
+    #[test_case(FlushPolicy::WhenFull, ""; "when full")]
+    fn test_flushes_as_directed(flush_policy: FlushPolicy, expected_before_flush: &str) {
+        let writer = SharedWriter::default();
+        let printer = BufferedPrinter::new(writer.clone(), flush_policy);
+        printer.print("hello\n").expect("print failed");
+        printer.print("world").expect("print failed");
+        assert_eq!(
+            expected_before_flush.as_bytes(),
+            writer.0.borrow().as_slice()
+        );
+
+        printer.flush().expect("flush failed");
+        assert_eq!(b"hello\nworld", writer.0.borrow().as_slice());
+    }
+    /// A [`Write`] whose reader has gone away.
+    struct ClosedPipe;
+
+    impl Write for ClosedPipe {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_records_broken_pipe() {
+        let printer = BufferedPrinter::new(ClosedPipe, FlushPolicy::Line);
+        assert!(!printer.broken_pipe());
+
+        let res = printer.print("hello\n");
+        assert!(matches!(res, Err(Error::BrokenPipe(_))));
+        assert!(printer.broken_pipe());
+    }
+}
@@ -0,0 +1,302 @@
+use super::{Printer, Result};
+use crate::color::{AnsiValue, Fg};
+use crate::lines;
+use crate::style::{Bold, Highlight};
+use crate::theme::Theme;
+use std::cell::Cell;
+use std::ops::Range;
+
+/// The width that line numbers are right-aligned to within the gutter.
+const LINE_NUMBER_WIDTH: usize = 4;
+
+/// `Gutter` is the style of the gutter that holds the line numbers of a [`DecoratedPrinter`], which sets it apart from
+/// the lines that follow it, whether they matched or not.
+#[derive(Copy, Clone, Debug)]
+pub struct Gutter {
+    /// The color of the line numbers, and of the grid surrounding them.
+    pub color: AnsiValue,
+    /// The character that separates the gutter from each line.
+    pub separator: char,
+    /// The number of spaces between each line number and the separator.
+    pub padding: usize,
+}
+
+impl Gutter {
+    /// The width of the gutter, not including the separator that follows it.
+    fn width(&self) -> usize {
+        LINE_NUMBER_WIDTH + self.padding
+    }
+}
+
+impl Default for Gutter {
+    fn default() -> Self {
+        Self {
+            color: Theme::DEFAULT.line_number,
+            separator: '│',
+            padding: 3,
+        }
+    }
+}
+
+/// `DecoratedPrinter` wraps another [`Printer`] to decorate its output, in the style of `bat`. The output is given a
+/// header naming the file, and each line is prefixed with a gutter holding its line number, all separated by a grid.
+/// The gutter can be styled with a [`Gutter`].
+///
+/// The header is printed along with the first line, and the grid is only closed off by calling
+/// [`finish`](`DecoratedPrinter::finish`), once all lines have been printed.
+#[allow(clippy::module_name_repetitions)]
+pub struct DecoratedPrinter<P: Printer> {
+    printer: P,
+    file_name: String,
+    width: usize,
+    gutter: Gutter,
+    started: Cell<bool>,
+    at_line_start: Cell<bool>,
+    line_number: Cell<u64>,
+}
+
+impl<P: Printer> DecoratedPrinter<P> {
+    /// Make a new `DecoratedPrinter` that prints to the given [`Printer`]. `file_name` will be displayed in the header,
+    /// and `width` is the width of the terminal, which the grid will span.
+    pub fn new(printer: P, file_name: &str, width: usize) -> Self {
+        Self::with_color(printer, file_name, width, Theme::DEFAULT.line_number)
+    }
+
+    /// Make a new `DecoratedPrinter`, as with [`DecoratedPrinter::new`], whose line numbers and grid are drawn in the
+    /// given color.
+    pub fn with_color(printer: P, file_name: &str, width: usize, color: AnsiValue) -> Self {
+        Self::with_gutter(
+            printer,
+            file_name,
+            width,
+            Gutter {
+                color,
+                ..Gutter::default()
+            },
+        )
+    }
+
+    /// Make a new `DecoratedPrinter`, as with [`DecoratedPrinter::new`], whose gutter is styled with the given
+    /// [`Gutter`].
+    pub fn with_gutter(printer: P, file_name: &str, width: usize, gutter: Gutter) -> Self {
+        Self {
+            printer,
+            file_name: file_name.to_string(),
+            width,
+            gutter,
+            started: Cell::new(false),
+            at_line_start: Cell::new(true),
+            line_number: Cell::new(0),
+        }
+    }
+
+    /// Close off the grid below the printed lines. If nothing has been printed, nothing is done.
+    ///
+    /// # Errors
+    ///
+    /// See [`Printer::print`].
+    pub fn finish(&self) -> Result {
+        if !self.started.get() {
+            return Ok(());
+        }
+
+        if !self.at_line_start.get() {
+            self.printer.print("\n")?;
+        }
+
+        self.print_border('┴')
+    }
+
+    /// Print each line in the given message, using `print_line` to print the contents of each line after its gutter.
+    fn print_lines<F: Fn(&[u8]) -> Result>(&self, msg: &[u8], print_line: F) -> Result {
+        for (component, joining_newline) in lines::line_split(msg) {
+            if component.is_empty() && joining_newline.is_none() {
+                continue;
+            }
+
+            if self.at_line_start.get() {
+                self.print_gutter()?;
+            }
+
+            print_line(&[component, joining_newline.unwrap_or_default()].concat())?;
+            self.at_line_start.set(joining_newline.is_some());
+        }
+
+        Ok(())
+    }
+
+    fn print_gutter(&self) -> Result {
+        if !self.started.replace(true) {
+            self.print_header()?;
+        }
+
+        let line_number = self.line_number.get() + 1;
+        self.line_number.set(line_number);
+
+        self.printer.styled_print(
+            Fg(self.gutter.color),
+            format!(
+                "{line_number:>LINE_NUMBER_WIDTH$}{padding}{separator}",
+                padding = " ".repeat(self.gutter.padding),
+                separator = self.gutter.separator,
+            ),
+        )?;
+        self.printer.print(" ")
+    }
+
+    fn print_header(&self) -> Result {
+        self.print_border('┬')?;
+        self.printer.styled_print(
+            Fg(self.gutter.color),
+            format!(
+                "{:width$}{separator}",
+                "",
+                width = self.gutter.width(),
+                separator = self.gutter.separator,
+            ),
+        )?;
+        self.printer.print(" File: ")?;
+        self.printer.styled_print(Bold, &self.file_name)?;
+        self.printer.print("\n")?;
+
+        self.print_border('┼')
+    }
+
+    /// Print a horizontal line of the grid, which crosses the gutter's vertical line with the given character.
+    fn print_border(&self, crossing: char) -> Result {
+        let gutter_width = self.gutter.width();
+        let rest_width = self.width.saturating_sub(gutter_width + 1);
+        self.printer.styled_print(
+            Fg(self.gutter.color),
+            format!(
+                "{gutter}{crossing}{rest}\n",
+                gutter = "─".repeat(gutter_width),
+                rest = "─".repeat(rest_width),
+            ),
+        )
+    }
+}
+
+impl<P: Printer> Printer for DecoratedPrinter<P> {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |line| self.printer.print(line))
+    }
+
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |line| {
+            self.printer.colored_print(&highlight, line)
+        })
+    }
+
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |line| {
+            self.printer.styled_print(&highlight, line)
+        })
+    }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        spans: &[(Range<usize>, G)],
+    ) -> Result {
+        // As the message is a single line, it is given back whole, and the spans still apply to it
+        self.print_lines(line, |line| {
+            self.printer.colored_print_spans(&highlight, line, spans)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{LightBlack, LightRed, Reset};
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+
+    fn border(crossing: char) -> String {
+        format!("{}───────{crossing}──{}\n", Fg(LightBlack), Fg(Reset))
+    }
+
+    fn gutter(line_number: u64) -> [String; 2] {
+        [
+            format!("{}{line_number:>4}   │{}", Fg(LightBlack), Fg(Reset)),
+            " ".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_decorates_lines() {
+        let mock_printer = MockPrinter::default();
+        let printer = DecoratedPrinter::new(&mock_printer, "foo.log", 10);
+        printer.print("hello\n").expect("print failed");
+        printer
+            .colored_print(Fg(LightRed), "world\n")
+            .expect("print failed");
+        printer.finish().expect("finish failed");
+
+        let [gutter1, gutter1_space] = gutter(1);
+        let [gutter2, gutter2_space] = gutter(2);
+        testutil::assert_slices_eq!(
+            &[
+                border('┬'),
+                format!("{}       │{}", Fg(LightBlack), Fg(Reset)),
+                " File: ".to_string(),
+                format!("{Bold}foo.log\x1b[22m"),
+                "\n".to_string(),
+                border('┼'),
+                gutter1,
+                gutter1_space,
+                "hello\n".to_string(),
+                gutter2,
+                gutter2_space,
+                border('┴'),
+            ],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["world\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_styles_gutter() {
+        let mock_printer = MockPrinter::default();
+        let gutter = Gutter {
+            color: AnsiValue(2),
+            separator: '|',
+            padding: 1,
+        };
+        let printer = DecoratedPrinter::with_gutter(&mock_printer, "foo.log", 8, gutter);
+        printer.print("hello\n").expect("print failed");
+        printer.finish().expect("finish failed");
+
+        let color = Fg(AnsiValue(2));
+        let reset = Fg(Reset);
+        testutil::assert_slices_eq!(
+            &[
+                format!("{color}─────┬──{reset}\n"),
+                format!("{color}     |{reset}"),
+                " File: ".to_string(),
+                format!("{Bold}foo.log\x1b[22m"),
+                "\n".to_string(),
+                format!("{color}─────┼──{reset}\n"),
+                format!("{color}   1 |{reset}"),
+                " ".to_string(),
+                "hello\n".to_string(),
+                format!("{color}─────┴──{reset}\n"),
+            ],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_prints_nothing_for_empty_input() {
+        let mock_printer = MockPrinter::default();
+        let printer = DecoratedPrinter::new(&mock_printer, "foo.log", 10);
+        printer.finish().expect("finish failed");
+
+        assert!(mock_printer.uncolored_messages.borrow().is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use super::{Printer, Result};
+use crate::lines;
+use crate::style::Highlight;
+use std::fmt::Write;
+use std::ops::Range;
+
+/// `EscapingPrinter` wraps another [`Printer`] so that control characters, which could otherwise garble the terminal,
+/// are printed as escapes (e.g. `\x00`). Tabs and line terminators are printed as-is.
+///
+/// This is useful when printing the contents of a file that may be binary. Bytes that are not valid UTF-8 are printed
+/// as escapes too, so that the original bytes can be recovered from the output.
+///
+/// Control characters can instead be printed in caret notation (e.g. `^L` or `^[`), as `cat -v` does, with
+/// [`EscapingPrinter::with_caret_notation`]. This is easier to read when only a few stray control characters are
+/// expected, such as in log lines.
+#[allow(clippy::module_name_repetitions)]
+pub struct EscapingPrinter<P: Printer> {
+    printer: P,
+    caret_notation: bool,
+}
+
+impl<P: Printer> EscapingPrinter<P> {
+    /// Make a new `EscapingPrinter` that prints to the given [`Printer`].
+    pub fn new(printer: P) -> Self {
+        Self {
+            printer,
+            caret_notation: false,
+        }
+    }
+
+    /// Make a new `EscapingPrinter` that prints to the given [`Printer`], printing control characters in caret
+    /// notation. Those in the C1 range (U+0080 to U+009F) are printed with an `M-` prefix (e.g. `M-^E`), as `cat -v`
+    /// does.
+    pub fn with_caret_notation(printer: P) -> Self {
+        Self {
+            printer,
+            caret_notation: true,
+        }
+    }
+
+    /// Escape every control character in the given message, other than tabs and line terminators, along with every
+    /// byte that is not valid UTF-8.
+    fn escape(&self, msg: &[u8]) -> String {
+        let mut escaped = String::with_capacity(msg.len());
+        for (component, joining_newline) in lines::line_split(msg) {
+            for chunk in component.utf8_chunks() {
+                for c in chunk.valid().chars() {
+                    if !c.is_control() || c == '\t' {
+                        escaped.push(c);
+                    } else if self.caret_notation {
+                        push_caret_notation(&mut escaped, c);
+                    } else {
+                        // All control characters are below U+00A0, so always fit in two hex digits. Writing to a
+                        // String also cannot fail.
+                        write!(escaped, "\\x{:02x}", u32::from(c)).unwrap();
+                    }
+                }
+
+                for byte in chunk.invalid() {
+                    write!(escaped, "\\x{byte:02x}").unwrap();
+                }
+            }
+
+            // Line terminators are always ASCII, so each byte is a char of its own
+            escaped.extend(
+                joining_newline
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|&b| char::from(b)),
+            );
+        }
+
+        escaped
+    }
+}
+
+impl<P: Printer> Printer for EscapingPrinter<P> {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        self.printer.print(self.escape(msg.as_ref()))
+    }
+
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.printer
+            .colored_print(highlight, self.escape(msg.as_ref()))
+    }
+
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.printer
+            .styled_print(highlight, self.escape(msg.as_ref()))
+    }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        spans: &[(Range<usize>, G)],
+    ) -> Result {
+        // Escaping changes the length of the line, so each span must be escaped separately to know where it ends up
+        let mut escaped_line = String::with_capacity(line.len());
+        let mut escaped_spans = Vec::with_capacity(spans.len());
+        let mut pos = 0;
+        for (span, span_highlight) in spans {
+            escaped_line.push_str(&self.escape(&line[pos..span.start]));
+            let start = escaped_line.len();
+            escaped_line.push_str(&self.escape(&line[span.clone()]));
+            escaped_spans.push((start..escaped_line.len(), span_highlight));
+            pos = span.end;
+        }
+
+        escaped_line.push_str(&self.escape(&line[pos..]));
+
+        self.printer
+            .colored_print_spans(highlight, escaped_line.as_bytes(), &escaped_spans)
+    }
+}
+
+/// Push the given control character onto `escaped` in caret notation, where each character is shown as `^` followed by
+/// the character 64 places above it (e.g. `^@` for NUL, and `^?` for DEL).
+pub(crate) fn push_caret_notation(escaped: &mut String, c: char) {
+    let mut code = u32::from(c);
+    if code >= 0x80 {
+        escaped.push_str("M-");
+        code -= 0x80;
+    }
+
+    escaped.push('^');
+    // Control characters in either range are below 0x20, other than DEL, which wraps around to '?'
+    escaped.push(char::from_u32((code + 0x40) % 0x80).unwrap_or('?'));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Fg, LightRed};
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+    use test_case::test_case;
+
+    #[test_case("hello\tworld\n", "hello\tworld\n"; "tabs and newlines are kept")]
+    #[test_case("hello\r\nworld", "hello\r\nworld"; "crlf is kept")]
+    #[test_case("ELF\x00\x01\x7f\n", "ELF\\x00\\x01\\x7f\n"; "control bytes are escaped")]
+    #[test_case("\x1b[31mred\n", "\\x1b[31mred\n"; "escape sequences are escaped")]
+    #[test_case("a\rb", "a\\x0db"; "lone carriage return is escaped")]
+    #[test_case("next\u{85}line", "next\\x85line"; "c1 controls are escaped")]
+    fn test_escapes_control_characters(msg: &str, expected: &str) {
+        let mock_printer = MockPrinter::default();
+        let printer = EscapingPrinter::new(&mock_printer);
+        printer.print(msg).expect("print failed");
+        printer
+            .colored_print(Fg(LightRed), msg)
+            .expect("print failed");
+
+        testutil::assert_slices_eq!(
+            &[expected.to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &[expected.to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+
+    #[test_case("page\x0cbreak\n", "page^Lbreak\n"; "form feed")]
+    #[test_case("\x1b[31mred\n", "^[[31mred\n"; "escape sequences")]
+    #[test_case("\x00\x7f\t\n", "^@^?\t\n"; "nul and del")]
+    #[test_case("next\u{85}line", "nextM-^Eline"; "c1 controls")]
+    fn test_escapes_in_caret_notation(msg: &str, expected: &str) {
+        let mock_printer = MockPrinter::default();
+        let printer = EscapingPrinter::with_caret_notation(&mock_printer);
+        printer.print(msg).expect("print failed");
+
+        testutil::assert_slices_eq!(
+            &[expected.to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_escapes_invalid_utf8() {
+        let mock_printer = MockPrinter::default();
+        let printer = EscapingPrinter::new(&mock_printer);
+        printer.print(b"caf\xe9 \xff\n").expect("print failed");
+
+        testutil::assert_slices_eq!(
+            &["caf\\xe9 \\xff\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use super::{Printer, Result};
+use crate::color::{AnsiValue, Fg};
+use crate::lines;
+use crate::style::Highlight;
+use crate::theme::Theme;
+use std::cell::Cell;
+use std::ops::Range;
+
+/// `FilenamePrinter` wraps another [`Printer`] to prefix each line with the name of the file it came from, followed by
+/// a colon, as `grep -H` does. The name is styled distinctly from the rest of the line, and is never highlighted as a
+/// match. The colon can be swapped for another separator with [`FilenamePrinter::separator`].
+#[allow(clippy::module_name_repetitions)]
+pub struct FilenamePrinter<P: Printer> {
+    printer: P,
+    file_name: String,
+    color: AnsiValue,
+    separator: u8,
+    at_line_start: Cell<bool>,
+}
+
+impl<P: Printer> FilenamePrinter<P> {
+    /// Make a new `FilenamePrinter` that prints to the given [`Printer`], prefixing each line with `file_name`.
+    pub fn new(printer: P, file_name: &str) -> Self {
+        Self::with_color(printer, file_name, Theme::DEFAULT.header)
+    }
+
+    /// Make a new `FilenamePrinter`, as with [`FilenamePrinter::new`], whose prefixes are printed in the given color.
+    pub fn with_color(printer: P, file_name: &str, color: AnsiValue) -> Self {
+        Self {
+            printer,
+            file_name: file_name.to_string(),
+            color,
+            separator: b':',
+            at_line_start: Cell::new(true),
+        }
+    }
+
+    /// Follow each file name with `separator`, rather than a colon. A NUL byte (`b'\0'`) lets the output be parsed even
+    /// if file names hold colons or newlines, as `grep -Z` does.
+    #[must_use]
+    pub fn separator(mut self, separator: u8) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Print each line in the given message, using `print_line` to print the contents of each line after its prefix.
+    fn print_lines<F: Fn(&[u8]) -> Result>(&self, msg: &[u8], print_line: F) -> Result {
+        for (component, joining_newline) in lines::line_split(msg) {
+            if component.is_empty() && joining_newline.is_none() {
+                continue;
+            }
+
+            if self.at_line_start.get() {
+                self.printer.styled_print(Fg(self.color), &self.file_name)?;
+                self.printer.print([self.separator])?;
+            }
+
+            print_line(&[component, joining_newline.unwrap_or_default()].concat())?;
+            self.at_line_start.set(joining_newline.is_some());
+        }
+
+        Ok(())
+    }
+}
+
+impl<P: Printer> Printer for FilenamePrinter<P> {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |line| self.printer.print(line))
+    }
+
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |line| {
+            self.printer.colored_print(&highlight, line)
+        })
+    }
+
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |line| {
+            self.printer.styled_print(&highlight, line)
+        })
+    }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        spans: &[(Range<usize>, G)],
+    ) -> Result {
+        // As the message is a single line, it is given back whole, and the spans still apply to it
+        self.print_lines(line, |line| {
+            self.printer.colored_print_spans(&highlight, line, spans)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{LightRed, Reset};
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+
+    #[test]
+    fn test_prefixes_each_line() {
+        let mock_printer = MockPrinter::default();
+        let printer = FilenamePrinter::with_color(&mock_printer, "foo.log", AnsiValue(5));
+        printer.print("hello\nthe ").expect("print failed");
+        printer
+            .colored_print(Fg(LightRed), "brown fox\n")
+            .expect("print failed");
+
+        let prefix = format!("{}foo.log{}", Fg(AnsiValue(5)), Fg(Reset));
+        testutil::assert_slices_eq!(
+            &[
+                prefix.clone(),
+                ":".to_string(),
+                "hello\n".to_string(),
+                prefix,
+                ":".to_string(),
+                "the ".to_string(),
+            ],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["brown fox\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_separates_prefix_with_given_separator() {
+        let mock_printer = MockPrinter::default();
+        let printer = FilenamePrinter::new(&mock_printer, "foo.log").separator(b'\0');
+        printer.print("hello\n").expect("print failed");
+
+        testutil::assert_slices_eq!(
+            &[
+                format!("{}foo.log{}", Fg(Theme::DEFAULT.header), Fg(Reset)),
+                "\0".to_string(),
+                "hello\n".to_string(),
+            ],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use super::{Printer, Result};
+use crate::ansi;
+use crate::style::Highlight;
+use std::ops::Range;
+
+/// `PlainPrinter` wraps another [`Printer`] so that nothing is ever highlighted or styled, such as when printing to
+/// something other than a terminal. Messages given to [`colored_print`](`Printer::colored_print`) still have any colors
+/// they contain removed, but messages given to [`styled_print`](`Printer::styled_print`) are printed as-is.
+///
+/// Since the printers in this module apply all of their own colors through `colored_print` and `styled_print`, this
+/// should be the printer that they ultimately print to.
+#[allow(clippy::module_name_repetitions)]
+pub struct PlainPrinter<P: Printer> {
+    printer: P,
+}
+
+impl<P: Printer> PlainPrinter<P> {
+    /// Make a new `PlainPrinter` that prints to the given [`Printer`].
+    pub fn new(printer: P) -> Self {
+        Self { printer }
+    }
+}
+
+impl<P: Printer> Printer for PlainPrinter<P> {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        self.printer.print(msg)
+    }
+
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, _highlight: H, msg: M) -> Result {
+        self.printer.print(ansi::strip_sgr_sequences(msg.as_ref()))
+    }
+
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, _highlight: H, msg: M) -> Result {
+        self.printer.print(msg)
+    }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        _highlight: H,
+        line: &[u8],
+        _spans: &[(Range<usize>, G)],
+    ) -> Result {
+        self.printer.print(ansi::strip_sgr_sequences(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Fg, LightRed};
+    use crate::print::TailHeaderPrinter;
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+
+    #[test]
+    fn test_never_highlights() {
+        let mock_printer = MockPrinter::default();
+        let printer = PlainPrinter::new(&mock_printer);
+        printer
+            .colored_print(Fg(LightRed), "\x1b[1mhello\x1b[m\n")
+            .expect("print failed");
+        printer
+            .styled_print(Fg(LightRed), "\x1b[1mworld\x1b[m\n")
+            .expect("print failed");
+
+        assert!(mock_printer.colored_messages.borrow().is_empty());
+        testutil::assert_slices_eq!(
+            &["hello\n".to_string(), "\x1b[1mworld\x1b[m\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_removes_colors_of_wrapping_printers() {
+        let mock_printer = MockPrinter::default();
+        let printer = TailHeaderPrinter::new(PlainPrinter::new(&mock_printer), true);
+        printer
+            .print("==> foo.log <==\nhello\n")
+            .expect("print failed");
+
+        testutil::assert_slices_eq!(
+            &[
+                "==> foo.log <==\n".to_string(),
+                "foo.log".to_string(),
+                ":".to_string(),
+                "hello\n".to_string(),
+            ],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+}
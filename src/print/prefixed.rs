@@ -0,0 +1,189 @@
+use super::{Printer, Result};
+use crate::color::{AnsiValue, Fg};
+use crate::lines;
+use crate::prefixed;
+use crate::style::Highlight;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// The colors that will be assigned to each source prefix, in order of appearance. Red is left out so that prefixes
+/// are never mistaken for a match.
+const PREFIX_PALETTE: [u8; 10] = [6, 2, 3, 4, 5, 14, 10, 11, 12, 13];
+
+/// `PrefixedPrinter` wraps another [`Printer`] to print lines that start with the name of their source, such as the
+/// output of `kubectl logs --prefix` or `docker compose logs` (see [`crate::prefixed`] for more details).
+///
+/// Each distinct prefix is assigned its own color, which will stay the same for as long as the printer is used, and
+/// only the message following the prefix will be highlighted.
+#[allow(clippy::module_name_repetitions)]
+pub struct PrefixedPrinter<P: Printer> {
+    printer: P,
+    prefix_colors: RefCell<HashMap<Vec<u8>, u8>>,
+}
+
+impl<P: Printer> PrefixedPrinter<P> {
+    /// Make a new `PrefixedPrinter` that prints to the given [`Printer`].
+    pub fn new(printer: P) -> Self {
+        Self {
+            printer,
+            prefix_colors: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Print each line in the given message, using `print_message` to print the portion of each line following its
+    /// prefix.
+    fn print_lines<F: Fn(&[u8]) -> Result>(&self, msg: &[u8], print_message: F) -> Result {
+        for (component, joining_newline) in lines::line_split(msg) {
+            let newline = joining_newline.unwrap_or_default();
+            let (prefix, message) = component.split_at(prefixed::prefix_len(component));
+            if !prefix.is_empty() {
+                self.print_prefix(prefix)?;
+            }
+
+            if !message.is_empty() || !newline.is_empty() {
+                print_message(&[message, newline].concat())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_prefix(&self, prefix: &[u8]) -> Result {
+        let source = prefix.trim_ascii_end();
+        let separator = &prefix[source.len()..];
+        let color = self.color_for_source(source);
+
+        self.printer.styled_print(Fg(AnsiValue(color)), source)?;
+        self.printer.print(separator)
+    }
+
+    fn color_for_source(&self, source: &[u8]) -> u8 {
+        let mut prefix_colors = self.prefix_colors.borrow_mut();
+        let num_sources = prefix_colors.len();
+
+        *prefix_colors
+            .entry(source.to_vec())
+            .or_insert_with(|| PREFIX_PALETTE[num_sources % PREFIX_PALETTE.len()])
+    }
+}
+
+impl<P: Printer> Printer for PrefixedPrinter<P> {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |message| self.printer.print(message))
+    }
+
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |message| {
+            self.printer.colored_print(&highlight, message)
+        })
+    }
+
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |message| {
+            self.printer.styled_print(&highlight, message)
+        })
+    }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        spans: &[(Range<usize>, G)],
+    ) -> Result {
+        self.print_lines(line, |message| {
+            // The message is the end of the line, so the spans must be moved past the prefix, and any part of them
+            // within the prefix dropped
+            let prefix_len = line.len() - message.len();
+            let message_spans = spans
+                .iter()
+                .filter(|(span, _)| span.end > prefix_len)
+                .map(|(span, span_highlight)| {
+                    let start = span.start.saturating_sub(prefix_len);
+                    (start..span.end - prefix_len, span_highlight)
+                })
+                .collect::<Vec<_>>();
+
+            self.printer
+                .colored_print_spans(&highlight, message, &message_spans)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{LightRed, Reset, Yellow};
+    use crate::print::WritePrinter;
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+
+    #[test]
+    fn test_only_highlights_message() {
+        let mock_printer = MockPrinter::default();
+        let printer = PrefixedPrinter::new(&mock_printer);
+        printer
+            .colored_print(Fg(LightRed), "web-1  | hello\n")
+            .expect("print failed");
+
+        testutil::assert_slices_eq!(
+            &["hello\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &[
+                format!("{}web-1  |{}", Fg(AnsiValue(6)), Fg(Reset)),
+                " ".to_string()
+            ],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_moves_spans_past_prefix() {
+        let printer = PrefixedPrinter::new(WritePrinter::new(Vec::new()));
+        printer
+            .colored_print_spans(Fg(LightRed), b"[web-1] hello\n", &[(8..10, Fg(Yellow))])
+            .expect("print failed");
+
+        let expected = format!(
+            "{}[web-1]{} {}he{}{}llo{}\n",
+            Fg(AnsiValue(6)),
+            Fg(Reset),
+            Fg(Yellow),
+            Fg(Reset),
+            Fg(LightRed),
+            Fg(Reset)
+        );
+        assert_eq!(
+            expected,
+            String::from_utf8(printer.printer.into_inner()).expect("not utf-8")
+        );
+    }
+
+    #[test]
+    fn test_sources_keep_their_colors() {
+        let mock_printer = MockPrinter::default();
+        let printer = PrefixedPrinter::new(&mock_printer);
+        printer
+            .print("[web-1] hello\n[web-2] hello\n[web-1] hello\n")
+            .expect("print failed");
+
+        let web1_prefix = format!("{}[web-1]{}", Fg(AnsiValue(6)), Fg(Reset));
+        let web2_prefix = format!("{}[web-2]{}", Fg(AnsiValue(2)), Fg(Reset));
+        testutil::assert_slices_eq!(
+            &[
+                web1_prefix.clone(),
+                " ".to_string(),
+                "hello\n".to_string(),
+                web2_prefix,
+                " ".to_string(),
+                "hello\n".to_string(),
+                web1_prefix,
+                " ".to_string(),
+                "hello\n".to_string(),
+            ],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use super::{Printer, Result};
+use crate::style::{Highlight, Reset};
+use std::cell::Cell;
+use std::ops::Range;
+use std::thread;
+
+/// `ResettingPrinter` wraps another [`Printer`], guaranteeing that all colors and styles are reset once it is dropped
+/// if printing failed partway through a message, or if the thread is panicking. Without this, a colored line that was
+/// cut short would leave the terminal in its color after the program exits.
+///
+/// Nothing extra is printed if every message was printed in full. Failures to print the reset are ignored, as there is
+/// nothing more that can be done about them.
+#[allow(clippy::module_name_repetitions)]
+pub struct ResettingPrinter<P: Printer> {
+    printer: P,
+    failed: Cell<bool>,
+}
+
+impl<P: Printer> ResettingPrinter<P> {
+    /// Make a new `ResettingPrinter` that prints to the given [`Printer`].
+    pub fn new(printer: P) -> Self {
+        Self {
+            printer,
+            failed: Cell::new(false),
+        }
+    }
+
+    /// Note if the given result is a failure, before handing it back.
+    fn record_result(&self, res: Result) -> Result {
+        if res.is_err() {
+            self.failed.set(true);
+        }
+
+        res
+    }
+}
+
+impl<P: Printer> Printer for ResettingPrinter<P> {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        self.record_result(self.printer.print(msg))
+    }
+
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.record_result(self.printer.colored_print(highlight, msg))
+    }
+
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.record_result(self.printer.styled_print(highlight, msg))
+    }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        spans: &[(Range<usize>, G)],
+    ) -> Result {
+        self.record_result(self.printer.colored_print_spans(highlight, line, spans))
+    }
+}
+
+impl<P: Printer> Drop for ResettingPrinter<P> {
+    fn drop(&mut self) {
+        if self.failed.get() || thread::panicking() {
+            let _ = self.printer.print(Reset.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Fg, LightRed};
+    use crate::print;
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+    use std::io;
+    use std::panic;
+
+    #[test]
+    fn test_does_not_reset_after_printing_in_full() {
+        let mock_printer = MockPrinter::default();
+        {
+            let printer = ResettingPrinter::new(&mock_printer);
+            printer
+                .colored_print(Fg(LightRed), "hello\n")
+                .expect("print failed");
+        }
+
+        assert!(mock_printer.uncolored_messages.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_resets_after_failed_print() {
+        let mut mock_printer = MockPrinter::default();
+        mock_printer.fail_next(print::Error::from(io::Error::other("disk full")));
+
+        {
+            let printer = ResettingPrinter::new(&mock_printer);
+            let res = printer.colored_print(Fg(LightRed), "hello\n");
+            assert!(res.is_err());
+        }
+
+        testutil::assert_slices_eq!(
+            &["\x1b[m".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_resets_when_panicking() {
+        let mock_printer = MockPrinter::default();
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let _printer = ResettingPrinter::new(&mock_printer);
+            panic!("failed partway through");
+        }));
+
+        assert!(res.is_err());
+        testutil::assert_slices_eq!(
+            &["\x1b[m".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use super::{Printer, Result};
+use crate::lines;
+use crate::style::Highlight;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::ops::Range;
+
+/// `SqueezingPrinter` wraps another [`Printer`], collapsing each run of blank lines into a single blank line, as with
+/// `cat -s`. A line is blank if it has nothing before its terminator, other than a carriage return.
+///
+/// Whether the last line printed was blank is kept across messages, so runs are collapsed even when each line is
+/// printed on its own, and a line printed in several parts is only treated as blank if all of them are empty.
+#[allow(clippy::module_name_repetitions)]
+pub struct SqueezingPrinter<P: Printer> {
+    printer: P,
+    at_line_start: Cell<bool>,
+    last_line_blank: Cell<bool>,
+}
+
+impl<P: Printer> SqueezingPrinter<P> {
+    /// Make a new `SqueezingPrinter` that prints to the given [`Printer`].
+    pub fn new(printer: P) -> Self {
+        Self {
+            printer,
+            at_line_start: Cell::new(true),
+            last_line_blank: Cell::new(false),
+        }
+    }
+
+    /// Remove the blank lines from the given message that follow another blank line.
+    fn squeeze<'a>(&self, msg: &'a [u8]) -> Cow<'a, [u8]> {
+        let mut squeezed: Option<Vec<u8>> = None;
+        let mut pos = 0;
+        for line in msg.split_inclusive(|&b| b == b'\n') {
+            let ends_line = line.ends_with(b"\n");
+            let blank =
+                self.at_line_start.get() && ends_line && lines::without_terminator(line).is_empty();
+            if blank && self.last_line_blank.get() {
+                squeezed.get_or_insert_with(|| msg[..pos].to_vec());
+            } else if let Some(squeezed) = squeezed.as_mut() {
+                squeezed.extend_from_slice(line);
+            }
+
+            pos += line.len();
+            self.at_line_start.set(ends_line);
+            self.last_line_blank.set(blank);
+        }
+
+        squeezed.map_or(Cow::Borrowed(msg), Cow::Owned)
+    }
+}
+
+impl<P: Printer> Printer for SqueezingPrinter<P> {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        let msg = msg.as_ref();
+        let squeezed = self.squeeze(msg);
+        if squeezed.is_empty() && !msg.is_empty() {
+            return Ok(());
+        }
+
+        self.printer.print(squeezed)
+    }
+
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        let msg = msg.as_ref();
+        let squeezed = self.squeeze(msg);
+        if squeezed.is_empty() && !msg.is_empty() {
+            return Ok(());
+        }
+
+        self.printer.colored_print(highlight, squeezed)
+    }
+
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        let msg = msg.as_ref();
+        let squeezed = self.squeeze(msg);
+        if squeezed.is_empty() && !msg.is_empty() {
+            return Ok(());
+        }
+
+        self.printer.styled_print(highlight, squeezed)
+    }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        spans: &[(Range<usize>, G)],
+    ) -> Result {
+        // The line is either dropped as a whole or left untouched, so the spans never need to be moved
+        if self.squeeze(line).is_empty() && !line.is_empty() {
+            return Ok(());
+        }
+
+        self.printer.colored_print_spans(highlight, line, spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Fg, LightRed};
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+    use test_case::test_case;
+
+    #[test_case("a\n\n\n\nb\n", "a\n\nb\n"; "run of blank lines")]
+    #[test_case("a\n\nb\n\nc\n", "a\n\nb\n\nc\n"; "single blank lines")]
+    #[test_case("\n\n\na\n", "\na\n"; "leading blank lines")]
+    #[test_case("a\r\n\r\n\r\nb\r\n", "a\r\n\r\nb\r\n"; "carriage returns")]
+    #[test_case("a\n \n \n", "a\n \n \n"; "whitespace is not blank")]
+    fn test_squeezes_blank_lines(msg: &str, expected: &str) {
+        let mock_printer = MockPrinter::default();
+        let printer = SqueezingPrinter::new(&mock_printer);
+        printer.print(msg).expect("print failed");
+
+        testutil::assert_slices_eq!(
+            &[expected.to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_squeezes_across_messages() {
+        let mock_printer = MockPrinter::default();
+        let printer = SqueezingPrinter::new(&mock_printer);
+        for msg in ["a\n", "\n", "\n", "", "\n"] {
+            printer.print(msg).expect("print failed");
+        }
+        printer
+            .colored_print(Fg(LightRed), "\n")
+            .expect("print failed");
+        printer.print("b").expect("print failed");
+        printer.print("\n").expect("print failed");
+        printer.print("\n").expect("print failed");
+
+        testutil::assert_slices_eq!(
+            &[
+                "a\n".to_string(),
+                "\n".to_string(),
+                String::new(),
+                "b".to_string(),
+                "\n".to_string(),
+                "\n".to_string()
+            ],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        assert!(mock_printer.colored_messages.borrow().is_empty());
+    }
+}
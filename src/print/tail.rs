@@ -0,0 +1,199 @@
+use super::{Printer, Result};
+use crate::color::{AnsiValue, Fg};
+use crate::lines;
+use crate::style::{Bold, Highlight};
+use crate::theme::Theme;
+use std::cell::RefCell;
+use std::ops::Range;
+
+const HEADER_PREFIX: &[u8] = b"==> ";
+const HEADER_SUFFIX: &[u8] = b" <==";
+
+/// `TailHeaderPrinter` wraps another [`Printer`] to understand the output of `tail` when it is given multiple files.
+/// In this output, `tail` separates the contents of each file with a header of the form `==> filename <==`.
+///
+/// These headers will be styled distinctly from the rest of the output, and will never be highlighted as a match.
+/// Optionally, every subsequent line can be prefixed with the name of the file it came from.
+#[allow(clippy::module_name_repetitions)]
+pub struct TailHeaderPrinter<P: Printer> {
+    printer: P,
+    prefix_lines: bool,
+    color: AnsiValue,
+    current_file: RefCell<Option<Vec<u8>>>,
+}
+
+impl<P: Printer> TailHeaderPrinter<P> {
+    /// Make a new `TailHeaderPrinter` that prints to the given [`Printer`]. If `prefix_lines` is set, each line
+    /// following a header will be prefixed with the filename from that header.
+    pub fn new(printer: P, prefix_lines: bool) -> Self {
+        Self::with_color(printer, prefix_lines, Theme::DEFAULT.header)
+    }
+
+    /// Make a new `TailHeaderPrinter`, as with [`TailHeaderPrinter::new`], whose headers and prefixes are printed in
+    /// the given color.
+    pub fn with_color(printer: P, prefix_lines: bool, color: AnsiValue) -> Self {
+        Self {
+            printer,
+            prefix_lines,
+            color,
+            current_file: RefCell::new(None),
+        }
+    }
+
+    /// Print each line in the given message, using `print_line` to print the lines that are not headers.
+    fn print_lines<F: Fn(&[u8]) -> Result>(&self, msg: &[u8], print_line: F) -> Result {
+        for (component, joining_newline) in lines::line_split(msg) {
+            let newline = joining_newline.unwrap_or_default();
+            if let Some(filename) = parse_header(component) {
+                self.print_header(component, newline)?;
+                self.current_file.replace(Some(filename.to_vec()));
+                continue;
+            }
+
+            if component.is_empty() {
+                // tail puts blank lines between files, which we don't want to prefix.
+                if !newline.is_empty() {
+                    self.printer.print(newline)?;
+                }
+
+                continue;
+            }
+
+            self.print_prefix()?;
+            print_line(&[component, newline].concat())?;
+        }
+
+        Ok(())
+    }
+
+    fn print_header(&self, header: &[u8], newline: &[u8]) -> Result {
+        self.printer
+            .styled_print((Bold, Fg(self.color)), [header, newline].concat())
+    }
+
+    fn print_prefix(&self) -> Result {
+        if !self.prefix_lines {
+            return Ok(());
+        }
+
+        match self.current_file.borrow().as_ref() {
+            Some(filename) => {
+                self.printer.styled_print(Fg(self.color), filename)?;
+                self.printer.print(":")
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl<P: Printer> Printer for TailHeaderPrinter<P> {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |line| self.printer.print(line))
+    }
+
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |line| {
+            self.printer.colored_print(&highlight, line)
+        })
+    }
+
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.print_lines(msg.as_ref(), |line| {
+            self.printer.styled_print(&highlight, line)
+        })
+    }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        spans: &[(Range<usize>, G)],
+    ) -> Result {
+        // As the message is a single line, it is given back whole, and the spans still apply to it
+        self.print_lines(line, |line| {
+            self.printer.colored_print_spans(&highlight, line, spans)
+        })
+    }
+}
+
+/// `parse_header` gets the filename from the given line if it is a `tail` file header.
+fn parse_header(line: &[u8]) -> Option<&[u8]> {
+    line.strip_prefix(HEADER_PREFIX)?
+        .strip_suffix(HEADER_SUFFIX)
+        .filter(|filename| !filename.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{LightRed, Magenta, Reset};
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+    use test_case::test_case;
+
+    #[test_case("==> foo.log <==", Some("foo.log"); "header")]
+    #[test_case("==> my file.log <==", Some("my file.log"); "filename with spaces")]
+    #[test_case("==> <==", None; "no filename")]
+    #[test_case("==> foo.log", None; "missing suffix")]
+    #[test_case("hello ==> foo.log <==", None; "header in the middle of a line")]
+    fn test_parse_header(line: &str, expected: Option<&str>) {
+        assert_eq!(expected.map(str::as_bytes), parse_header(line.as_bytes()));
+    }
+
+    #[test]
+    fn test_headers_are_never_highlighted() {
+        let mock_printer = MockPrinter::default();
+        let printer = TailHeaderPrinter::new(&mock_printer, false);
+        printer
+            .colored_print(Fg(LightRed), "==> foo.log <==\n")
+            .expect("print failed");
+
+        assert!(mock_printer.colored_messages.borrow().is_empty());
+        testutil::assert_slices_eq!(
+            &[format!(
+                "{}{}==> foo.log <==\x1b[22m{}\n",
+                Bold,
+                Fg(Magenta),
+                Fg(Reset),
+            )],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_prefixes_lines_with_current_file() {
+        let mock_printer = MockPrinter::default();
+        let printer = TailHeaderPrinter::new(&mock_printer, true);
+        printer
+            .print("before any header\n==> foo.log <==\nhello\n\n")
+            .expect("print failed");
+        printer
+            .colored_print(Fg(LightRed), "world\n")
+            .expect("print failed");
+
+        let prefix = format!("{}foo.log{}", Fg(Magenta), Fg(Reset));
+        let header = format!(
+            "{}{}==> foo.log <==\x1b[22m{}\n",
+            Bold,
+            Fg(Magenta),
+            Fg(Reset),
+        );
+        testutil::assert_slices_eq!(
+            &[
+                "before any header\n".to_string(),
+                header,
+                prefix.clone(),
+                ":".to_string(),
+                "hello\n".to_string(),
+                "\n".to_string(),
+                prefix,
+                ":".to_string(),
+            ],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["world\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use super::{Printer, Result};
+use crate::ansi;
+use crate::style::Highlight;
+use std::cell::RefCell;
+use std::io::Write;
+use std::ops::Range;
+
+/// `MatchTeePrinter` wraps another [`Printer`], and will copy every highlighted (i.e. matching) line to the given
+/// [`Write`], in addition to printing it as normal. Lines written to the [`Write`] will not have any colors.
+///
+/// This is useful for producing an extract of only the matching lines, while still viewing the full output.
+#[allow(clippy::module_name_repetitions)]
+pub struct MatchTeePrinter<P: Printer, W: Write> {
+    printer: P,
+    matches_writer: RefCell<W>,
+}
+
+impl<P: Printer, W: Write> MatchTeePrinter<P, W> {
+    /// Make a new `MatchTeePrinter` that prints to the given [`Printer`], and copies matching lines to the given
+    /// [`Write`].
+    pub fn new(printer: P, matches_writer: W) -> Self {
+        Self {
+            printer,
+            matches_writer: RefCell::new(matches_writer),
+        }
+    }
+}
+
+impl<P: Printer, W: Write> Printer for MatchTeePrinter<P, W> {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        self.printer.print(msg)
+    }
+
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        let uncolored_msg = ansi::strip_sgr_sequences(msg.as_ref());
+        self.matches_writer.borrow_mut().write_all(&uncolored_msg)?;
+
+        self.printer.colored_print(highlight, msg)
+    }
+
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.printer.styled_print(highlight, msg)
+    }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        spans: &[(Range<usize>, G)],
+    ) -> Result {
+        let uncolored_line = ansi::strip_sgr_sequences(line);
+        self.matches_writer
+            .borrow_mut()
+            .write_all(&uncolored_line)?;
+
+        self.printer.colored_print_spans(highlight, line, spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Fg, LightRed};
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+
+    #[test]
+    fn test_copies_only_matches() {
+        let mock_printer = MockPrinter::default();
+        let mut matches = Vec::new();
+        {
+            let printer = MatchTeePrinter::new(&mock_printer, &mut matches);
+            printer.print("hello\n").expect("print failed");
+            printer
+                .colored_print(Fg(LightRed), "\x1b[32mworld\x1b[m\n")
+                .expect("print failed");
+        }
+
+        assert_eq!("world\n", String::from_utf8(matches).expect("not utf-8"));
+        testutil::assert_slices_eq!(
+            &["hello\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["\x1b[32mworld\x1b[m\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+}
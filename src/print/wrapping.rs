@@ -0,0 +1,178 @@
+use super::{Printer, Result};
+use crate::columns;
+use crate::style::Highlight;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::ops::Range;
+
+/// `WrappingPrinter` wraps another [`Printer`] so that lines wider than the terminal are wrapped onto the next row by
+/// the printer itself, rather than by the terminal. As each row then ends in a newline, highlights are applied to every
+/// row a line is wrapped onto; some terminals otherwise reset colors when they wrap a line, leaving the rest of a long
+/// match unhighlighted.
+///
+/// The column that printing has reached is kept across messages, so a line printed in several parts (e.g. after a
+/// line number) is still wrapped at the right place. Escape sequences that set colors take up no columns.
+#[allow(clippy::module_name_repetitions)]
+pub struct WrappingPrinter<P: Printer> {
+    printer: P,
+    width: usize,
+    column: Cell<usize>,
+}
+
+impl<P: Printer> WrappingPrinter<P> {
+    /// Make a new `WrappingPrinter` that prints to the given [`Printer`], wrapping lines at the given width in columns.
+    pub fn new(printer: P, width: usize) -> Self {
+        Self {
+            printer,
+            width,
+            column: Cell::new(0),
+        }
+    }
+
+    /// Find the byte offsets in the given message that a newline must be inserted at to wrap it, continuing from the
+    /// column that printing has reached.
+    fn find_breaks(&self, msg: &[u8]) -> Vec<usize> {
+        let mut breaks = Vec::new();
+        let mut column = self.column.get();
+        for unit in columns::units(msg) {
+            let mut next_column = unit.next_column(column);
+            if next_column > self.width && column > 0 && unit.is_visible() {
+                breaks.push(unit.bytes.start);
+                next_column = unit.next_column(0);
+            }
+
+            column = next_column;
+        }
+
+        self.column.set(column);
+
+        breaks
+    }
+
+    /// Wrap the given message, continuing from the column that printing has reached.
+    fn wrap<'a>(&self, msg: &'a [u8]) -> Cow<'a, [u8]> {
+        let breaks = self.find_breaks(msg);
+        if breaks.is_empty() {
+            return Cow::Borrowed(msg);
+        }
+
+        Cow::Owned(insert_newlines(msg, &breaks))
+    }
+}
+
+impl<P: Printer> Printer for WrappingPrinter<P> {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> Result {
+        self.printer.print(self.wrap(msg.as_ref()))
+    }
+
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.printer
+            .colored_print(highlight, self.wrap(msg.as_ref()))
+    }
+
+    fn styled_print<M: AsRef<[u8]>, H: Highlight>(&self, highlight: H, msg: M) -> Result {
+        self.printer
+            .styled_print(highlight, self.wrap(msg.as_ref()))
+    }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        spans: &[(Range<usize>, G)],
+    ) -> Result {
+        let breaks = self.find_breaks(line);
+        if breaks.is_empty() {
+            return self.printer.colored_print_spans(highlight, line, spans);
+        }
+
+        // Each span moves along by the number of newlines inserted before it. A newline at the very start of a span is
+        // inserted before it, rather than within it.
+        let wrapped_spans = spans
+            .iter()
+            .map(|(span, span_highlight)| {
+                let start = span.start + breaks.partition_point(|&pos| pos <= span.start);
+                let end = span.end + breaks.partition_point(|&pos| pos < span.end);
+                (start..end.max(start), span_highlight)
+            })
+            .collect::<Vec<_>>();
+
+        self.printer
+            .colored_print_spans(highlight, &insert_newlines(line, &breaks), &wrapped_spans)
+    }
+}
+
+/// Insert a newline into the given message at each of the given byte offsets, which must be in order.
+fn insert_newlines(msg: &[u8], breaks: &[usize]) -> Vec<u8> {
+    let mut wrapped = Vec::with_capacity(msg.len() + breaks.len());
+    let mut pos = 0;
+    for &break_pos in breaks {
+        wrapped.extend_from_slice(&msg[pos..break_pos]);
+        wrapped.push(b'\n');
+        pos = break_pos;
+    }
+
+    wrapped.extend_from_slice(&msg[pos..]);
+
+    wrapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Fg, LightRed, Yellow};
+    use crate::print::WritePrinter;
+    use crate::style::{End, Start};
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+    use test_case::test_case;
+
+    #[test_case("hello\n", "hello\n"; "short line")]
+    #[test_case("hello world\n", "hello\n worl\nd\n"; "long line")]
+    #[test_case("hello\nworld\n", "hello\nworld\n"; "line exactly as wide")]
+    #[test_case("日本語\n", "日本\n語\n"; "wide characters")]
+    #[test_case("\x1b[31mhello\x1b[m\n", "\x1b[31mhello\x1b[m\n"; "escape sequences")]
+    fn test_wraps_lines(msg: &str, expected: &str) {
+        let mock_printer = MockPrinter::default();
+        let printer = WrappingPrinter::new(&mock_printer, 5);
+        printer.print(msg).expect("print failed");
+
+        testutil::assert_slices_eq!(
+            &[expected.to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_continues_from_previous_message() {
+        let mock_printer = MockPrinter::default();
+        let printer = WrappingPrinter::new(&mock_printer, 5);
+        printer.print("1 │ ").expect("print failed");
+        printer
+            .colored_print(Fg(LightRed), "hello\n")
+            .expect("print failed");
+
+        testutil::assert_slices_eq!(
+            &["h\nello\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_highlights_each_row() {
+        let printer = WrappingPrinter::new(WritePrinter::new(Vec::new()), 5);
+        printer
+            .colored_print_spans(Fg(LightRed), b"abc defgh\n", &[(4..9, Fg(Yellow))])
+            .expect("print failed");
+
+        let output = String::from_utf8(printer.printer.into_inner()).expect("output is not utf-8");
+        let expected = format!(
+            "{}abc {}{start}d{end}\n{start}efgh{end}\n",
+            Start(&Fg(LightRed)),
+            End(&Fg(LightRed)),
+            start = Start(&Fg(Yellow)),
+            end = End(&Fg(Yellow)),
+        );
+        assert_eq!(expected, output);
+    }
+}
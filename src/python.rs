@@ -0,0 +1,126 @@
+//! `python` provides bindings to use hline's highlighting from Python, as the `hline` module. These are built as an
+//! extension module with [maturin](https://www.maturin.rs), using the `python` feature:
+//!
+//! ```python
+//! import hline
+//!
+//! for line in hline.scan("hello\nworld\n", "wor"):
+//!     if line.matched:
+//!         print(line.line_number, line.text, line.spans)
+//! ```
+use crate::Error;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::ops::Range;
+
+/// `HighlightedLine` is a single line of the text given to `scan`, noting whether it matched the pattern and where.
+#[pyclass(module = "hline", frozen, get_all)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HighlightedLine {
+    /// The contents of the line, without its line terminator.
+    text: String,
+    /// The number of the line, starting from 1.
+    line_number: u64,
+    /// Whether or not the line matched.
+    matched: bool,
+    /// The start and end of each match within the line, as indices into [`text`](`HighlightedLine::text`). Unlike the
+    /// spans elsewhere in this crate, these count characters rather than bytes, so that they can be used to slice a
+    /// Python `str`.
+    spans: Vec<(usize, usize)>,
+}
+
+#[pymethods]
+impl HighlightedLine {
+    fn __repr__(&self) -> String {
+        format!(
+            "HighlightedLine(line_number={}, matched={}, text={:?}, spans={:?})",
+            self.line_number,
+            if self.matched { "True" } else { "False" },
+            self.text,
+            self.spans
+        )
+    }
+}
+
+/// Scan the given text for a regular expression, returning a `HighlightedLine` for each of its lines.
+///
+/// Raises a `ValueError` if the pattern is not a valid regular expression.
+#[pyfunction]
+fn scan(text: &str, pattern: &str) -> PyResult<Vec<HighlightedLine>> {
+    scan_text(text, pattern).map_err(|err| match err {
+        Error::RegexError(_) => PyValueError::new_err(err.to_string()),
+        _ => PyRuntimeError::new_err(err.to_string()),
+    })
+}
+
+#[pymodule]
+#[pyo3(name = "hline")]
+fn hline_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<HighlightedLine>()?;
+    module.add_function(wrap_pyfunction!(scan, module)?)?;
+
+    Ok(())
+}
+
+fn scan_text(text: &str, pattern: &str) -> Result<Vec<HighlightedLine>, Error> {
+    let mut highlighted_lines = Vec::new();
+    crate::scan_pattern_with_callback(text.as_bytes(), pattern, |event| {
+        highlighted_lines.push(HighlightedLine {
+            text: event.text().into_owned(),
+            line_number: event.line_number,
+            matched: event.matched,
+            spans: event
+                .spans
+                .iter()
+                .map(|span| char_span(event.line, span))
+                .collect(),
+        });
+    })?;
+
+    Ok(highlighted_lines)
+}
+
+/// Convert the given span of bytes within a line to a span of characters.
+fn char_span(line: &[u8], span: &Range<usize>) -> (usize, usize) {
+    // A match may not fall on a character boundary if the pattern disables Unicode, so count lossily
+    let start = String::from_utf8_lossy(&line[..span.start]).chars().count();
+    let len = String::from_utf8_lossy(&line[span.clone()]).chars().count();
+
+    (start, start + len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("brown fox", 6..9, (6, 9); "ascii")]
+    #[test_case("brøwn føx", 7..11, (6, 9); "multi-byte characters before")]
+    #[test_case("brown føx", 6..10, (6, 9); "multi-byte characters within")]
+    fn test_converts_spans_to_characters(line: &str, span: Range<usize>, expected: (usize, usize)) {
+        assert_eq!(expected, char_span(line.as_bytes(), &span));
+    }
+
+    #[test]
+    fn test_scans_text() {
+        let highlighted_lines = scan_text("the quick\nbrøwn føx\n", "f.x").expect("scan failed");
+
+        assert_eq!(
+            vec![
+                HighlightedLine {
+                    text: "the quick".to_string(),
+                    line_number: 1,
+                    matched: false,
+                    spans: vec![],
+                },
+                HighlightedLine {
+                    text: "brøwn føx".to_string(),
+                    line_number: 2,
+                    matched: true,
+                    spans: vec![(6, 9)],
+                },
+            ],
+            highlighted_lines
+        );
+    }
+}
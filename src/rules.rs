@@ -0,0 +1,292 @@
+//! `rules` provides a small rules engine, which allows each line to be handled differently depending on which pattern
+//! it matches.
+//!
+//! Rules are evaluated in order, and the first rule whose pattern matches a line decides how that line is handled.
+//! They can be loaded from a TOML file of the form
+//!
+//! ```toml
+//! [[rule]]
+//! pattern = "DEBUG"
+//! action = "suppress"
+//!
+//! [[rule]]
+//! pattern = "ERROR"
+//! color = "red"
+//! action = "bell"
+//!
+//! [[rule]]
+//! pattern = "WARN"
+//! style = "bold black on yellow"
+//! ```
+//!
+//! `color` may be a color name (e.g. `red` or `light-red`) or a number from 0-255, and defaults to `light-red`.
+//! `style` may add text attributes and a background color, as parsed by [`Style`]; a `color` given alongside it takes
+//! precedence over any foreground color it sets. `action` defaults to `highlight`. See [`Action`] for all of the
+//! possible actions.
+use crate::color::{AnsiValue, ParseColorError};
+use crate::style::{ParseStyleError, Style};
+use grep::matcher::{Match, Matcher, NoCaptures, NoError};
+use grep::regex;
+use grep::regex::RegexMatcher;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// The color used for rules that do not specify one; this is the same color used to highlight a single pattern.
+const DEFAULT_COLOR: AnsiValue = AnsiValue(9);
+
+/// `Error` represents the possible errors that can occur when loading rules.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The rules file was not valid TOML, or did not have the expected structure.
+    #[error("Invalid rules: {0}")]
+    InvalidToml(
+        /// The original error produced while parsing the TOML.
+        toml::de::Error,
+    ),
+    /// A rule's pattern was not a valid regular expression.
+    #[error("Invalid pattern \"{pattern}\": {source}")]
+    InvalidPattern {
+        /// The pattern that failed to parse.
+        pattern: String,
+        /// The original error produced by the regular expression engine.
+        source: regex::Error,
+    },
+    /// A rule's color was not a valid color.
+    #[error("Invalid color: {0}")]
+    InvalidColor(
+        /// The original error produced while parsing the color.
+        ParseColorError,
+    ),
+    /// A rule's style was not a valid style.
+    #[error("Invalid style: {0}")]
+    InvalidStyle(
+        /// The original error produced while parsing the style.
+        ParseStyleError,
+    ),
+}
+
+/// `Action` represents what should be done with a line that matches a [`Rule`].
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Highlight the line in the rule's style.
+    #[default]
+    Highlight,
+    /// Do not print the line at all.
+    Suppress,
+    /// Highlight the line, and then stop scanning the input.
+    Stop,
+    /// Highlight the line, and ring the terminal's bell.
+    Bell,
+}
+
+/// `Rule` pairs a pattern with the way lines that match it should be handled.
+#[derive(Debug)]
+pub struct Rule {
+    matcher: RegexMatcher,
+    style: Style,
+    action: Action,
+}
+
+impl Rule {
+    /// Make a new `Rule`, which will perform `action` on lines matching `pattern`, highlighting them in `color` where
+    /// applicable.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidPattern`] if the pattern is not a valid regular expression.
+    pub fn new(pattern: &str, color: AnsiValue, action: Action) -> Result<Self, Error> {
+        Self::with_style(pattern, Style::fg(color), action)
+    }
+
+    /// Make a new `Rule`, which will perform `action` on lines matching `pattern`, highlighting them with `style`
+    /// where applicable.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidPattern`] if the pattern is not a valid regular expression.
+    pub fn with_style(pattern: &str, style: Style, action: Action) -> Result<Self, Error> {
+        let matcher = RegexMatcher::new(pattern).map_err(|err| Error::InvalidPattern {
+            pattern: pattern.to_string(),
+            source: err,
+        })?;
+
+        Ok(Self {
+            matcher,
+            style,
+            action,
+        })
+    }
+
+    /// The color that lines matching this rule should be highlighted in. If the rule's style has no foreground color,
+    /// this is the default color, `light-red`.
+    #[must_use]
+    pub fn color(&self) -> AnsiValue {
+        self.style.fg.unwrap_or(DEFAULT_COLOR)
+    }
+
+    /// The style that lines matching this rule should be highlighted with.
+    #[must_use]
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// The action that should be performed on lines matching this rule.
+    #[must_use]
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    fn is_match(&self, line: &[u8]) -> bool {
+        // RegexMatcher can never fail to match
+        self.matcher.is_match(line).unwrap_or(false)
+    }
+}
+
+/// `Rules` is an ordered set of [`Rule`]s.
+///
+/// `Rules` is also a [`Matcher`], which matches any line that at least one of its rules matches.
+#[derive(Debug)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+/// `RulesFile` is the structure of a TOML rules file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleDefinition>,
+}
+
+/// `RuleDefinition` is the structure of a single rule in a TOML rules file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleDefinition {
+    pattern: String,
+    color: Option<String>,
+    style: Option<String>,
+    #[serde(default)]
+    action: Action,
+}
+
+impl Rules {
+    /// Make a new `Rules` from the given rules, which will be evaluated in the order given.
+    #[must_use]
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self { rules }
+    }
+
+    /// Load rules from the given TOML. See the [module documentation](`self`) for its format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TOML is not a valid rules file, or any of its patterns or colors are invalid.
+    pub fn from_toml(toml_str: &str) -> Result<Self, Error> {
+        let rules_file: RulesFile = toml::from_str(toml_str).map_err(Error::InvalidToml)?;
+        let rules = rules_file
+            .rules
+            .into_iter()
+            .map(|definition| {
+                let mut style = definition
+                    .style
+                    .map_or(Ok(Style::default()), |style| style.parse())
+                    .map_err(Error::InvalidStyle)?;
+                let color = definition
+                    .color
+                    .map(|color| color.parse())
+                    .transpose()
+                    .map_err(Error::InvalidColor)?;
+                style.fg = color.or(style.fg).or(Some(DEFAULT_COLOR));
+
+                Rule::with_style(&definition.pattern, style, definition.action)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::new(rules))
+    }
+
+    /// Get the first rule that matches the given line, if any.
+    #[must_use]
+    pub fn rule_for(&self, line: &[u8]) -> Option<&Rule> {
+        self.rules.iter().find(|rule| rule.is_match(line))
+    }
+}
+
+impl Matcher for Rules {
+    type Captures = NoCaptures;
+    type Error = NoError;
+
+    fn find_at(&self, haystack: &[u8], at: usize) -> Result<Option<Match>, Self::Error> {
+        let found = self
+            .rules
+            .iter()
+            .find_map(|rule| rule.matcher.find_at(haystack, at).ok().flatten());
+
+        Ok(found)
+    }
+
+    fn new_captures(&self) -> Result<Self::Captures, Self::Error> {
+        Ok(NoCaptures::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const RULES: &str = r#"
+        [[rule]]
+        pattern = "DEBUG"
+        action = "suppress"
+
+        [[rule]]
+        pattern = "ERROR"
+        color = "yellow"
+        action = "bell"
+
+        [[rule]]
+        pattern = "ERROR|WARN"
+
+        [[rule]]
+        pattern = "NOTICE"
+        style = "underline green"
+    "#;
+
+    #[test_case(b"DEBUG: ERROR", Some((9, Action::Suppress)); "first matching rule wins")]
+    #[test_case(b"ERROR: disk on fire", Some((3, Action::Bell)); "rule with color")]
+    #[test_case(b"WARN: disk is warm", Some((9, Action::Highlight)); "defaults")]
+    #[test_case(b"NOTICE: disk is new", Some((2, Action::Highlight)); "rule with style")]
+    #[test_case(b"INFO: all good", None; "no matching rule")]
+    fn test_rule_for(line: &[u8], expected: Option<(u8, Action)>) {
+        let rules = Rules::from_toml(RULES).expect("failed to load rules");
+        let rule = rules
+            .rule_for(line)
+            .map(|rule| (rule.color().0, rule.action()));
+
+        assert_eq!(expected, rule);
+    }
+
+    #[test_case("[[rule]]\npattern = \"(\"", "Invalid pattern"; "invalid pattern")]
+    #[test_case("[[rule]]\npattern = \"a\"\ncolor = \"reddish\"", "Invalid color"; "invalid color")]
+    #[test_case("[[rule]]\npattern = \"a\"\naction = \"explode\"", "Invalid rules"; "invalid action")]
+    #[test_case("[[rule]]\npattern = \"a\"\nstyle = \"loud\"", "Invalid style"; "invalid style")]
+    #[test_case("[[rule]]\ncolor = \"red\"", "Invalid rules"; "missing pattern")]
+    fn test_invalid_rules(toml_str: &str, expected_msg_prefix: &str) {
+        let err = Rules::from_toml(toml_str).expect_err("rules should not have loaded");
+
+        assert!(
+            err.to_string().starts_with(expected_msg_prefix),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn test_matches_any_rule() {
+        let rules = Rules::from_toml(RULES).expect("failed to load rules");
+
+        assert!(rules.is_match(b"hello WARN").expect("failed to match"));
+        assert!(!rules.is_match(b"hello INFO").expect("failed to match"));
+    }
+}
@@ -1,22 +1,176 @@
 //! `sink` provides utilities to handle the search results provided by `grep`.
+//!
+//! [`ContextPrintingSink`] is the [`Sink`] that performs the printing done by the functions in the crate root. Other
+//! sinks can wrap it to extend its behavior, by forwarding the results they receive to it, such as
+//! [`MatchCountingSink`]. These can then be searched with using [`scan_matcher_to_sink`](`crate::scan_matcher_to_sink`).
+//!
+//! Sinks of your own can also be used alongside the printing [`Sink`] with [`TeeSink`]. The [`Sink`] trait, and the
+//! types it is given, are re-exported here so that these can be written without depending on `grep` directly. Your
+//! sinks must report errors as an [`enum@Error`], which can be made from a message with [`SinkError::error_message`].
+use crate::color::{AnsiValue, Fg};
+use crate::columns;
+use crate::lines;
 use crate::print;
 use crate::print::{Printer, StdoutPrinter};
-use grep::searcher::{Searcher, Sink, SinkContext, SinkError, SinkMatch};
-use std::fmt::Display;
+use crate::style::{Attribute, Highlight, Style};
+use crate::theme::Theme;
+use grep::matcher::{Captures, Matcher};
+use grep::regex::RegexMatcher;
+use grep::searcher::LineIter;
+pub use grep::searcher::{Searcher, Sink, SinkContext, SinkError, SinkFinish, SinkMatch};
+use regex::Regex;
+use std::borrow::Cow;
+use std::fmt::{Display, Write};
 use std::io;
-use std::panic;
-use termion::color::{Fg, LightRed};
+use std::ops::Range;
 use thiserror::Error;
 
+mod binary;
+mod callback;
+mod counting;
+mod csv;
+mod json;
+#[cfg(feature = "lua")]
+mod plugin;
+mod position;
+mod progress;
+mod rules;
+mod stats;
+mod tee;
+mod vimgrep;
+
+pub(crate) use binary::BinaryQuittingSink;
+pub use callback::{CallbackSink, LineEvent, OwnedLineEvent};
+pub use counting::MatchCountingSink;
+pub(crate) use csv::{CsvSink, HEADER as CSV_HEADER};
+pub use json::JsonStats;
+pub(crate) use json::{write_summary as write_json_summary, JsonSink};
+#[cfg(feature = "lua")]
+pub(crate) use plugin::PluginSink;
+pub(crate) use position::PositionTrackingSink;
+pub use progress::{Progress, ProgressInterval, ProgressSink};
+pub(crate) use rules::RulesSink;
+pub use stats::{ScanStats, StatsSink};
+pub use tee::TeeSink;
+pub(crate) use vimgrep::VimgrepSink;
+
 const PASSTHRU_PANIC_MSG: &str = "passthru is not enabled on the given searcher";
 
-pub(crate) struct ContextPrintingSink<P: Printer> {
+/// The color that matching lines are highlighted in, unless another is given. This is light red.
+const DEFAULT_MATCH_COLOR: AnsiValue = AnsiValue(9);
+
+/// The colors that capture groups are highlighted in, in order of their group number: red, green, yellow, blue, magenta,
+/// and cyan. Groups past the end of the palette start again from the beginning.
+const GROUP_PALETTE: [u8; 6] = [1, 2, 3, 4, 5, 6];
+
+/// `ContextPrintingSink` prints every line it is given to a [`Printer`], highlighting those that matched. Matching
+/// lines are highlighted in light red, unless another color is given with [`ContextPrintingSink::with_color`], or
+/// another [`Highlight`], such as a background color, is given with [`ContextPrintingSink::with_highlight`]. Lines that
+/// did not match are printed as-is, unless a [`Theme`] that colors them is given with
+/// [`ContextPrintingSink::with_theme`], or a color or [`Style`] is given with [`ContextPrintingSink::context_color`] or
+/// [`ContextPrintingSink::context_style`], respectively. Invalid UTF-8 is handled as chosen with
+/// [`ContextPrintingSink::invalid_utf8`]. The capture groups of the pattern can be given their own colors with
+/// [`ContextPrintingSink::capture_groups`], and noisy lines that did not match can be dimmed with
+/// [`ContextPrintingSink::dim`]. Each line can be prefixed with its line number or byte offset with
+/// [`ContextPrintingSink::line_number`] or [`ContextPrintingSink::byte_offset`], and matches can be marked for output
+/// without colors with [`ContextPrintingSink::markers`]. The text that matched can be replaced with
+/// [`ContextPrintingSink::replace`], and adjacent matches can be told apart with [`ContextPrintingSink::alternate`].
+///
+/// To print only matching lines, like `grep`, disable [`ContextPrintingSink::passthru`] and search without passthru.
+///
+/// This can be used to search with a [`Searcher`] directly, while printing as the functions in the crate root do.
+///
+/// ```
+/// use grep::regex::RegexMatcher;
+/// use grep::searcher::SearcherBuilder;
+/// use hline::color::AnsiValue;
+/// use hline::print::WritePrinter;
+/// use hline::sink::{ContextPrintingSink, InvalidUtf8};
+///
+/// let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+/// let printer = WritePrinter::new(Vec::new());
+/// let sink = ContextPrintingSink::with_color(&printer, AnsiValue(2))
+///     .context_color(Some(AnsiValue(8)))
+///     .invalid_utf8(InvalidUtf8::Escape);
+///
+/// SearcherBuilder::new()
+///     .passthru(true)
+///     .build()
+///     .search_slice(matcher, b"brown fox\n", sink)
+///     .expect("search failed");
+/// ```
+///
+/// # Panics
+///
+/// Unless [`ContextPrintingSink::passthru`] is disabled, the searcher this sink is used with must have passthru
+/// enabled, so that every line of the input is received. If it does not, the search will panic.
+#[allow(clippy::module_name_repetitions, clippy::struct_excessive_bools)]
+pub struct ContextPrintingSink<P: Printer, H: Highlight = Fg<AnsiValue>> {
     printer: P,
+    highlight: H,
+    context_style: Option<Style>,
+    matches_left: Option<u64>,
+    matches_until_stop: Option<u64>,
+    invalid_utf8: InvalidUtf8,
+    passthru: bool,
+    capture_groups: Option<Regex>,
+    dim: Option<RegexMatcher>,
+    line_number: bool,
+    byte_offset: bool,
+    markers: Option<RegexMatcher>,
+    max_columns: Option<usize>,
+    truncation_matcher: Option<RegexMatcher>,
+    replacement: Option<(RegexMatcher, Vec<u8>)>,
+    alternate_highlight: Option<H>,
+    alternating: bool,
+    last_match_end: Option<u64>,
+}
+
+/// `InvalidUtf8` decides how [`ContextPrintingSink`] prints input that is not valid UTF-8.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum InvalidUtf8 {
+    /// Replace each invalid sequence with `U+FFFD`.
+    #[default]
+    Replace,
+    /// Print each invalid byte as an escape (e.g. `\xff`), so that the original bytes can be recovered.
+    Escape,
+    /// Print each invalid byte as-is, so that the output holds exactly the bytes of the input.
+    Keep,
+}
+
+impl InvalidUtf8 {
+    /// Prepare the given bytes for printing, handling any invalid UTF-8 sequences as chosen.
+    pub(crate) fn decode(self, bytes: &[u8]) -> Cow<'_, [u8]> {
+        match self {
+            Self::Replace => match String::from_utf8_lossy(bytes) {
+                Cow::Borrowed(decoded) => Cow::Borrowed(decoded.as_bytes()),
+                Cow::Owned(decoded) => Cow::Owned(decoded.into_bytes()),
+            },
+            Self::Keep => Cow::Borrowed(bytes),
+            Self::Escape => {
+                if std::str::from_utf8(bytes).is_ok() {
+                    return Cow::Borrowed(bytes);
+                }
+
+                let mut decoded = String::with_capacity(bytes.len());
+                for chunk in bytes.utf8_chunks() {
+                    decoded.push_str(chunk.valid());
+                    for byte in chunk.invalid() {
+                        // Writing to a String cannot fail
+                        write!(decoded, "\\x{byte:02x}").unwrap();
+                    }
+                }
+
+                Cow::Owned(decoded.into_bytes())
+            }
+        }
+    }
 }
 
 /// `Error` represents an error that happens during the search process
 ///
 #[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     /// Printing to the given printer failed due to an i/o error.
     #[error("Print failure: {0}")]
@@ -25,6 +179,13 @@ pub enum Error {
         io::Error,
     ),
 
+    /// Reading from the input being searched failed due to an i/o error.
+    #[error("Read failure: {0}")]
+    ReadFailed(
+        /// The original i/o error that caused the read failure.
+        io::Error,
+    ),
+
     /// The `SearchError` variant is specifically used to represent errors reported by the internal grep library, and
     /// their reasons may not be specifically matchable as a result.
     // This error is a bit custom, and is intended to be produced by callers on the SinkError trait. As such,
@@ -34,6 +195,22 @@ pub enum Error {
         /// An error message provided by the underlying grep library.
         String,
     ),
+
+    /// The plugin that lines were passed through failed.
+    #[cfg(feature = "lua")]
+    // The plugin's errors already say that they came from the plugin
+    #[error("{0}")]
+    PluginFailed(
+        /// The original error produced by the plugin.
+        crate::plugin::Error,
+    ),
+
+    /// The search was stopped because binary data was found in the input.
+    #[error("Binary data found at byte {0}")]
+    BinaryData(
+        /// The byte offset in the input at which the binary data was found.
+        u64,
+    ),
 }
 
 impl From<print::Error> for Error {
@@ -50,79 +227,555 @@ impl SinkError for Error {
     fn error_message<T: Display>(message: T) -> Self {
         Error::SearchError(message.to_string())
     }
+
+    fn error_io(err: io::Error) -> Self {
+        Error::ReadFailed(err)
+    }
 }
 
-impl<P: Printer> ContextPrintingSink<P> {
-    fn get_sink_result_for_print_result(res: print::Result) -> Result<bool, Error> {
-        match res {
-            Err(print::Error::Other(_)) => Err(Error::from(res.unwrap_err())),
-            // It is not an error case to have a broken pipe; it just means we can't output anything more and we
-            // shouldn't keep searching
-            Err(print::Error::BrokenPipe(_)) => Ok(false),
-            Ok(_) => Ok(true),
+/// Make a line with `^` under each match of the given matcher within the given line, and whitespace elsewhere. Tabs are
+/// kept, so that the markers line up with the tab stops of the line. If there are no matches, the line is empty.
+fn marker_line(matcher: &RegexMatcher, line: &[u8]) -> String {
+    let mut marker_line = String::new();
+    let mut pos = 0;
+    // RegexMatcher never fails to search
+    let _ = matcher.find_iter(line, |found_match| {
+        let before = String::from_utf8_lossy(&line[pos..found_match.start()]);
+        let match_text = String::from_utf8_lossy(&line[found_match]);
+
+        marker_line.extend(before.chars().map(|c| if c == '\t' { '\t' } else { ' ' }));
+        marker_line.extend(match_text.chars().map(|_| '^'));
+        pos = found_match.end();
+        true
+    });
+
+    marker_line
+}
+
+/// Find the spans of the given line that are matched by each capture group of the given regex, along with the color
+/// each should be highlighted in. Capture groups are only found in lines that are valid UTF-8.
+fn capture_group_spans(regex: &Regex, line: &[u8]) -> Vec<(Range<usize>, Fg<AnsiValue>)> {
+    let Ok(line) = std::str::from_utf8(line) else {
+        return Vec::new();
+    };
+
+    let mut spans = Vec::new();
+    let mut last_end = 0;
+    for captures in regex.captures_iter(line) {
+        // Group 0 is the whole match, which is highlighted as the rest of the line is
+        for (group_number, group) in captures.iter().enumerate().skip(1) {
+            // Groups are numbered by where they start, so any group that starts before the previous one ends is nested
+            // within it
+            match group {
+                Some(group) if group.start() >= last_end => {
+                    let color = GROUP_PALETTE[(group_number - 1) % GROUP_PALETTE.len()];
+                    spans.push((group.range(), Fg(AnsiValue(color))));
+                    last_end = group.end();
+                }
+                _ => {}
+            }
         }
     }
+
+    spans
+}
+
+/// Replace each match of the given matcher within the given text with the given template, interpolating the capture
+/// groups it refers to.
+fn replace_matches(matcher: &RegexMatcher, template: &[u8], text: &[u8]) -> Vec<u8> {
+    let mut replaced = Vec::with_capacity(text.len());
+    // RegexMatcher never fails to search
+    let Ok(mut captures) = matcher.new_captures() else {
+        return text.to_vec();
+    };
+    let _ = matcher.replace_with_captures(text, &mut captures, &mut replaced, |captures, dst| {
+        captures.interpolate(|name| matcher.capture_index(name), text, template, dst);
+        true
+    });
+
+    replaced
+}
+
+/// Ensure the given searcher has passthru enabled, which is required by sinks that print every line.
+fn validate_searcher(searcher: &Searcher) {
+    // We cannot operate normally if this happens
+    assert!(searcher.passthru(), "{}", PASSTHRU_PANIC_MSG);
+}
+
+/// Convert the result of a print into the result a [`Sink`] should produce.
+fn get_sink_result_for_print_result(res: print::Result) -> Result<bool, Error> {
+    match res {
+        Err(print::Error::Other(_)) => Err(Error::from(res.unwrap_err())),
+        // It is not an error case to have a broken pipe; it just means we can't output anything more and we
+        // shouldn't keep searching
+        Err(print::Error::BrokenPipe(_)) => {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("output was closed; stopping the search");
+
+            Ok(false)
+        }
+        Ok(()) => Ok(true),
+    }
 }
 
 impl<P: Printer> ContextPrintingSink<P> {
+    /// Make a new `ContextPrintingSink` that prints to the given [`Printer`].
     #[must_use]
     pub fn new(printer: P) -> Self {
-        ContextPrintingSink { printer }
+        Self::with_color(printer, DEFAULT_MATCH_COLOR)
+    }
+
+    /// Make a new `ContextPrintingSink` that prints to the given [`Printer`], highlighting matching lines in the given
+    /// color.
+    #[must_use]
+    pub fn with_color(printer: P, color: AnsiValue) -> Self {
+        Self::with_highlight(printer, Fg(color))
+    }
+}
+
+impl<P: Printer, H: Highlight> ContextPrintingSink<P, H> {
+    /// Make a new `ContextPrintingSink` that prints to the given [`Printer`], highlighting matching lines with the
+    /// given [`Highlight`].
+    #[must_use]
+    pub fn with_highlight(printer: P, highlight: H) -> Self {
+        ContextPrintingSink {
+            printer,
+            highlight,
+            context_style: None,
+            matches_left: None,
+            matches_until_stop: None,
+            invalid_utf8: InvalidUtf8::default(),
+            passthru: true,
+            capture_groups: None,
+            dim: None,
+            line_number: false,
+            byte_offset: false,
+            markers: None,
+            max_columns: None,
+            truncation_matcher: None,
+            replacement: None,
+            alternate_highlight: None,
+            alternating: false,
+            last_match_end: None,
+        }
+    }
+
+    /// Only highlight the first `max_count` matching lines. Any matching lines after these are printed as if they did
+    /// not match, unless [`ContextPrintingSink::passthru`] is disabled, in which case the search stops once they have
+    /// been printed. If `max_count` is `None`, which is the default, every matching line is highlighted.
+    #[must_use]
+    pub fn max_count(mut self, max_count: Option<u64>) -> Self {
+        self.matches_left = max_count;
+        self
+    }
+
+    /// Stop the search entirely once `stop_after` matching lines have been printed, without printing any of the lines
+    /// after them, even with [`ContextPrintingSink::passthru`] enabled. This differs from
+    /// [`ContextPrintingSink::max_count`], which passes the remaining lines through. If `stop_after` is `None`, which is
+    /// the default, the search continues to the end of the input.
+    #[must_use]
+    pub fn stop_after(mut self, stop_after: Option<u64>) -> Self {
+        self.matches_until_stop = stop_after;
+        self
+    }
+
+    /// Print lines that did not match in the given color. If `color` is `None`, which is the default unless a
+    /// [`Theme`] was given, they are printed as-is.
+    #[must_use]
+    pub fn context_color(mut self, color: Option<AnsiValue>) -> Self {
+        self.context_style = color.map(Style::fg);
+        self
+    }
+
+    /// Print lines that did not match with the given [`Style`], such as to dim them so that matching lines stand out.
+    /// If `style` is `None`, which is the default unless a [`Theme`] was given, they are printed as-is. This replaces
+    /// any color given with [`ContextPrintingSink::context_color`].
+    #[must_use]
+    pub fn context_style(mut self, style: Option<Style>) -> Self {
+        self.context_style = style;
+        self
+    }
+
+    /// Choose how input that is not valid UTF-8 is printed. Defaults to [`InvalidUtf8::Replace`].
+    #[must_use]
+    pub fn invalid_utf8(mut self, invalid_utf8: InvalidUtf8) -> Self {
+        self.invalid_utf8 = invalid_utf8;
+        self
+    }
+
+    /// Whether the searcher will give this sink every line of the input. Enabled by default, in which case the search
+    /// panics if the searcher does not have passthru enabled. If disabled, only the lines the searcher gives are
+    /// printed, so that a searcher without passthru will print only the matching lines.
+    #[must_use]
+    pub fn passthru(mut self, passthru: bool) -> Self {
+        self.passthru = passthru;
+        self
+    }
+
+    /// Highlight each capture group of `regex` within matching lines in its own color, rather than with the highlight
+    /// for the rest of the line: group 1 in red, group 2 in green, then yellow, blue, magenta, and cyan. Groups nested
+    /// within another are colored as the group that contains them. `regex` should match the same lines as the pattern
+    /// that is searched for. If `regex` is `None`, which is the default, matching lines are highlighted whole.
+    #[must_use]
+    pub fn capture_groups(mut self, regex: Option<Regex>) -> Self {
+        self.capture_groups = regex;
+        self
+    }
+
+    /// Print lines that did not match, but are matched by `matcher`, dimmed, so that noisy lines (e.g. health checks)
+    /// recede into the background. If `matcher` is `None`, which is the default, no lines are dimmed.
+    #[must_use]
+    pub fn dim(mut self, matcher: Option<RegexMatcher>) -> Self {
+        self.dim = matcher;
+        self
+    }
+
+    /// Prefix each line with its line number, followed by a colon, as `grep -n` does. This comes before the byte offset,
+    /// if that is printed too. Disabled by default.
+    ///
+    /// # Panics
+    ///
+    /// If this is enabled, the searcher this sink is used with must count line numbers, as it does by default. If it
+    /// does not, the search will panic.
+    #[must_use]
+    pub fn line_number(mut self, line_number: bool) -> Self {
+        self.line_number = line_number;
+        self
+    }
+
+    /// Prefix each line with the byte offset of its start within the input, followed by a colon, as `grep -b` does.
+    /// Disabled by default.
+    #[must_use]
+    pub fn byte_offset(mut self, byte_offset: bool) -> Self {
+        self.byte_offset = byte_offset;
+        self
     }
 
-    fn validate_searcher(searcher: &Searcher) {
-        if !searcher.passthru() {
-            // We cannot operate normally if this happens
-            panic!("{}", PASSTHRU_PANIC_MSG)
+    /// After each matching line, print a line with `^` markers under each match within it, as found by `matcher`, so
+    /// that matches can be seen where colors are unavailable, such as in CI logs. `matcher` should be the same as the
+    /// one searched with. If `matcher` is `None`, which is the default, no markers are printed.
+    ///
+    /// Markers are only aligned for lines terminated by newlines, and assume each character takes up a single column.
+    #[must_use]
+    pub fn markers(mut self, matcher: Option<RegexMatcher>) -> Self {
+        self.markers = matcher;
+        self
+    }
+
+    /// Truncate lines wider than `max_columns` columns, marking the text removed from them with an ellipsis (`…`). If
+    /// `matcher` is given, lines are truncated around the first match it finds within them, so that the match stays
+    /// visible where it fits; `matcher` should be the same as the one searched with. If `max_columns` is `None`, which
+    /// is the default, lines are printed in full.
+    #[must_use]
+    pub fn max_columns(
+        mut self,
+        max_columns: Option<usize>,
+        matcher: Option<RegexMatcher>,
+    ) -> Self {
+        self.max_columns = max_columns;
+        self.truncation_matcher = matcher;
+        self
+    }
+
+    /// In matching lines, replace each match of the matcher with the template, given together as `replacement`. Within
+    /// the template, `$1` or `${1}` refers to the text matched by capture group 1, `${name}` to the group with that
+    /// name, and `$$` to a literal `$`. The matcher should be the same as the one searched with. Lines that did not
+    /// match are printed as-is. If `replacement` is `None`, which is the default, matching lines are printed as-is too.
+    ///
+    /// Replaced lines are highlighted whole, so [`ContextPrintingSink::capture_groups`] and
+    /// [`ContextPrintingSink::markers`] do not apply to them.
+    #[must_use]
+    pub fn replace(mut self, replacement: Option<(RegexMatcher, Vec<u8>)>) -> Self {
+        self.replacement = replacement;
+        self
+    }
+
+    /// Highlight matches that directly follow another match in the input with `highlight`, switching back and forth
+    /// between it and the usual highlight, so that adjacent matches can be told apart. A match that spans several lines
+    /// is highlighted as one. If `highlight` is `None`, which is the default, every match is highlighted alike.
+    #[must_use]
+    pub fn alternate(mut self, highlight: Option<H>) -> Self {
+        self.alternate_highlight = highlight;
+        self
+    }
+
+    /// Get the highlight that the current match should be printed with.
+    fn match_highlight(&self) -> &H {
+        match &self.alternate_highlight {
+            Some(alternate_highlight) if self.alternating => alternate_highlight,
+            _ => &self.highlight,
+        }
+    }
+
+    /// Print the given lines, which start at the given line number and byte offset of the input, with `print_line`.
+    /// Printers keep newlines out of the highlights they apply, but they know nothing of other line terminators, so
+    /// these are split off and printed separately.
+    fn print_lines<F: Fn(&[u8]) -> print::Result>(
+        &self,
+        searcher: &Searcher,
+        bytes: &[u8],
+        line_number: Option<u64>,
+        byte_offset: u64,
+        print_line: F,
+    ) -> print::Result {
+        let line_terminator = searcher.line_terminator().as_byte();
+        if line_terminator == b'\n'
+            && !self.line_number
+            && !self.byte_offset
+            && self.max_columns.is_none()
+        {
+            return print_line(&self.invalid_utf8.decode(bytes));
+        }
+
+        let mut line_number = self
+            .line_number
+            .then(|| line_number.expect("line numbers are not enabled on the given searcher"));
+        let mut line_offset = byte_offset;
+        for line in LineIter::new(line_terminator, bytes) {
+            if let Some(number) = line_number {
+                self.printer.print(format!("{number}:"))?;
+                line_number = Some(number + 1);
+            }
+
+            if self.byte_offset {
+                self.printer.print(format!("{line_offset}:"))?;
+                line_offset += line.len() as u64;
+            }
+
+            match line.split_last() {
+                Some((&b'\n', contents)) if line_terminator == b'\n' => {
+                    print_line(&[&*self.fit_line(contents), b"\n"].concat())?;
+                }
+                Some((&last, contents)) if last == line_terminator => {
+                    print_line(&self.fit_line(contents))?;
+                    self.printer.print([line_terminator])?;
+                }
+                _ => print_line(&self.fit_line(line))?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prepare the given line, which must not have its terminator, for printing, truncating it if it is too wide.
+    fn fit_line<'a>(&self, line: &'a [u8]) -> Cow<'a, [u8]> {
+        let decoded = self.invalid_utf8.decode(line);
+        let Some(max_columns) = self.max_columns else {
+            return decoded;
+        };
+
+        // RegexMatcher never fails to search
+        let found_match = self
+            .truncation_matcher
+            .as_ref()
+            .and_then(|matcher| matcher.find(&decoded).ok().flatten());
+        match columns::truncate(
+            &decoded,
+            max_columns,
+            found_match.map(|m| m.start()..m.end()),
+        ) {
+            Cow::Borrowed(_) => decoded,
+            Cow::Owned(truncated) => Cow::Owned(truncated),
         }
     }
+
+    fn print_match(&self, data: &[u8]) -> print::Result {
+        if let Some((matcher, template)) = &self.replacement {
+            let replaced = replace_matches(matcher, template, data);
+            return self.printer.colored_print(self.match_highlight(), replaced);
+        }
+
+        if self.capture_groups.is_none() && self.markers.is_none() {
+            return self.printer.colored_print(self.match_highlight(), data);
+        }
+
+        for line in data.split_inclusive(|&b| b == b'\n') {
+            let contents = lines::without_terminator(line);
+            match &self.capture_groups {
+                Some(regex) => {
+                    let spans = capture_group_spans(regex, contents);
+                    self.printer
+                        .colored_print_spans(self.match_highlight(), line, &spans)?;
+                }
+                None => self.printer.colored_print(self.match_highlight(), line)?,
+            }
+
+            if let Some(matcher) = &self.markers {
+                self.print_markers(matcher, line, contents)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Print a line of markers under the matches within the given line, which has already been printed. `contents` is
+    /// the line without its newline.
+    fn print_markers(&self, matcher: &RegexMatcher, line: &[u8], contents: &[u8]) -> print::Result {
+        let marker_line = marker_line(matcher, contents);
+        if marker_line.is_empty() {
+            return Ok(());
+        }
+
+        if !line.ends_with(b"\n") {
+            self.printer.print("\n")?;
+        }
+
+        self.printer.print(format!("{marker_line}\n"))
+    }
+
+    fn print_context(&self, data: &[u8]) -> print::Result {
+        let Some(dim) = &self.dim else {
+            return self.print_undimmed_context(data);
+        };
+
+        for line in data.split_inclusive(|&b| b == b'\n') {
+            // RegexMatcher never fails to search
+            if let Ok(true) = dim.is_match(line) {
+                self.printer
+                    .styled_print((Attribute::Dim, self.context_style), line)?;
+            } else {
+                self.print_undimmed_context(line)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_undimmed_context(&self, data: &[u8]) -> print::Result {
+        match self.context_style {
+            Some(style) => self.printer.styled_print(style, data),
+            None => self.printer.print(data),
+        }
+    }
+}
+
+impl<P: Printer> ContextPrintingSink<P, Style> {
+    /// Make a new `ContextPrintingSink` that prints to the given [`Printer`], styling both matching and non-matching
+    /// lines with the colors and attributes of the given [`Theme`].
+    #[must_use]
+    pub fn with_theme(printer: P, theme: &Theme) -> Self {
+        Self::with_highlight(printer, theme.match_highlight())
+            .context_style(theme.context_highlight())
+    }
 }
 
 impl Default for ContextPrintingSink<StdoutPrinter> {
     fn default() -> Self {
-        ContextPrintingSink {
-            printer: StdoutPrinter {},
-        }
+        ContextPrintingSink::new(StdoutPrinter {})
     }
 }
 
-impl<P: Printer> Sink for ContextPrintingSink<P> {
+impl<P: Printer, H: Highlight> Sink for ContextPrintingSink<P, H> {
     type Error = Error;
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, fields(byte_offset = sink_match.absolute_byte_offset()))
+    )]
     fn matched(
         &mut self,
         searcher: &Searcher,
         sink_match: &SinkMatch,
     ) -> Result<bool, Self::Error> {
-        Self::validate_searcher(searcher);
+        if self.passthru {
+            validate_searcher(searcher);
+        }
 
-        let print_res = self
-            .printer
-            .colored_print(Fg(LightRed), String::from_utf8_lossy(sink_match.bytes()));
+        // Nothing is printed once the search should have stopped, such as when stopping after no matches at all
+        if self.matches_until_stop == Some(0) {
+            return Ok(false);
+        }
 
-        Self::get_sink_result_for_print_result(print_res)
+        let highlight = match self.matches_left {
+            // Without passthru, the remaining lines are not needed, so there's no sense in searching for them
+            Some(0) if !self.passthru => return Ok(false),
+            Some(0) => false,
+            Some(matches_left) => {
+                let num_lines = sink_match.lines().count() as u64;
+                self.matches_left = Some(matches_left.saturating_sub(num_lines));
+                true
+            }
+            None => true,
+        };
+
+        let byte_offset = sink_match.absolute_byte_offset();
+        if highlight {
+            // Only a match that picks up where the last one ended switches highlights; any other starts afresh
+            self.alternating = self.last_match_end == Some(byte_offset) && !self.alternating;
+            self.last_match_end = Some(byte_offset + sink_match.bytes().len() as u64);
+        }
+
+        let line_number = sink_match.line_number();
+        let print_res = self.print_lines(
+            searcher,
+            sink_match.bytes(),
+            line_number,
+            byte_offset,
+            |data| {
+                if highlight {
+                    self.print_match(data)
+                } else {
+                    self.print_context(data)
+                }
+            },
+        );
+
+        let stop = self.matches_until_stop.is_some_and(|matches_until_stop| {
+            let num_lines = sink_match.lines().count() as u64;
+            let matches_until_stop = matches_until_stop.saturating_sub(num_lines);
+            self.matches_until_stop = Some(matches_until_stop);
+            matches_until_stop == 0
+        });
+
+        get_sink_result_for_print_result(print_res).map(|keep_searching| keep_searching && !stop)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, fields(byte_offset = context.absolute_byte_offset()))
+    )]
     fn context(
         &mut self,
         searcher: &Searcher,
         context: &SinkContext<'_>,
     ) -> Result<bool, Self::Error> {
-        Self::validate_searcher(searcher);
+        if self.passthru {
+            validate_searcher(searcher);
+        }
 
-        let data = String::from_utf8_lossy(context.bytes());
-        let print_res = self.printer.print(data);
+        if self.matches_until_stop == Some(0) {
+            return Ok(false);
+        }
 
-        Self::get_sink_result_for_print_result(print_res)
+        let print_res = self.print_lines(
+            searcher,
+            context.bytes(),
+            context.line_number(),
+            context.absolute_byte_offset(),
+            |data| self.print_context(data),
+        );
+
+        get_sink_result_for_print_result(print_res)
+    }
+
+    fn context_break(&mut self, _searcher: &Searcher) -> Result<bool, Self::Error> {
+        // Separate groups of lines that are not contiguous, as grep does. The separator is not part of the input, so it
+        // is never dimmed, even if it happens to match the pattern given to dim lines with.
+        get_sink_result_for_print_result(self.print_undimmed_context(b"--\n"))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::color::Reset;
+    use crate::print::WritePrinter;
+    use crate::style::{Attributes, End, Start};
+    use crate::testutil;
     use crate::testutil::mock_print::MockPrinter;
-    use grep::regex::RegexMatcher;
+    use grep::matcher::LineTerminator;
     use grep::searcher::SearcherBuilder;
+    use std::panic;
     use test_case::test_case;
 
     const SEARCH_TEXT: &str = "The quick \n\
@@ -147,9 +800,7 @@ mod tests {
             let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
 
             let mock_printer = MockPrinter::default();
-            let sink = ContextPrintingSink {
-                printer: &mock_printer,
-            };
+            let sink = ContextPrintingSink::new(&mock_printer);
 
             let mut builder = SearcherBuilder::new();
             for setting in settings {
@@ -176,4 +827,486 @@ mod tests {
             };
         }
     }
+
+    #[test_case(None, &["fox 1\n", "fox 2\n", "fox 3\n"], &["hello\n"]; "no limit")]
+    #[test_case(Some(2), &["fox 1\n", "fox 2\n"], &["hello\n", "fox 3\n"]; "limited")]
+    #[test_case(Some(0), &[], &["fox 1\n", "hello\n", "fox 2\n", "fox 3\n"]; "zero")]
+    fn test_highlights_up_to_max_count(
+        max_count: Option<u64>,
+        expected_colored: &[&str],
+        expected_uncolored: &[&str],
+    ) {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::new(&mock_printer).max_count(max_count);
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"fox 1\nhello\nfox 2\nfox 3\n", sink)
+            .expect("search failed");
+
+        let to_strings =
+            |messages: &[&str]| messages.iter().map(ToString::to_string).collect::<Vec<_>>();
+        testutil::assert_slices_eq!(
+            &to_strings(expected_colored),
+            &mock_printer.colored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &to_strings(expected_uncolored),
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test_case(None, &["fox 1\n", "fox 2\n", "fox 3\n"], &["the\n", "hello\n"]; "no limit")]
+    #[test_case(Some(2), &["fox 1\n", "fox 2\n"], &["the\n", "hello\n"]; "limited")]
+    #[test_case(Some(0), &[], &[]; "zero")]
+    fn test_stops_after_matches(
+        stop_after: Option<u64>,
+        expected_colored: &[&str],
+        expected_uncolored: &[&str],
+    ) {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::new(&mock_printer).stop_after(stop_after);
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"the\nfox 1\nhello\nfox 2\nfox 3\n", sink)
+            .expect("search failed");
+
+        let to_strings =
+            |messages: &[&str]| messages.iter().map(ToString::to_string).collect::<Vec<_>>();
+        testutil::assert_slices_eq!(
+            &to_strings(expected_colored),
+            &mock_printer.colored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &to_strings(expected_uncolored),
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test_case(None, &["fox 1\n", "fox 2\n", "fox 3\n"]; "no limit")]
+    #[test_case(Some(2), &["fox 1\n", "fox 2\n"]; "limited")]
+    fn test_prints_only_matches_without_passthru(
+        max_count: Option<u64>,
+        expected_colored: &[&str],
+    ) {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::new(&mock_printer)
+            .passthru(false)
+            .max_count(max_count);
+        SearcherBuilder::new()
+            .build()
+            .search_slice(matcher, b"fox 1\nhello\nfox 2\nfox 3\n", sink)
+            .expect("search failed");
+
+        let expected_colored = expected_colored
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        testutil::assert_slices_eq!(&expected_colored, &mock_printer.colored_messages.borrow());
+        assert!(mock_printer.uncolored_messages.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_separates_context_groups_without_passthru() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::new(&mock_printer).passthru(false);
+        SearcherBuilder::new()
+            .before_context(1)
+            .build()
+            .search_slice(matcher, b"a\nfox 1\nb\nc\nfox 2\n", sink)
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &["a\n".to_string(), "--\n".to_string(), "c\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["fox 1\n".to_string(), "fox 2\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_does_not_dim_context_group_separators() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let dim_matcher = RegexMatcher::new("-").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::new(&mock_printer)
+            .passthru(false)
+            .dim(Some(dim_matcher));
+        SearcherBuilder::new()
+            .after_context(1)
+            .build()
+            .search_slice(matcher, b"fox 1\na\nb\nfox 2\n", sink)
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &["a\n".to_string(), "--\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test_case("(fox)", &[(6..9, 1)]; "single group")]
+    #[test_case("(\\w+) (\\w+)", &[(0..5, 1), (6..9, 2)]; "multiple groups")]
+    #[test_case("((b)rown) (fox)", &[(0..5, 1), (6..9, 3)]; "nested groups")]
+    #[test_case("(cat)|(fox)", &[(6..9, 2)]; "unmatched group")]
+    #[test_case("fox", &[]; "no groups")]
+    fn test_finds_capture_group_spans(pattern: &str, expected: &[(Range<usize>, u8)]) {
+        let regex = Regex::new(pattern).expect("regexp doesn't compile");
+        let spans = capture_group_spans(&regex, b"brown fox")
+            .into_iter()
+            .map(|(span, color)| (span, color.0 .0))
+            .collect::<Vec<_>>();
+
+        assert_eq!(expected, spans.as_slice());
+    }
+
+    #[test]
+    fn test_dims_matching_context() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let dim_matcher = RegexMatcher::new("health").expect("regexp doesn't compile");
+        let printer = WritePrinter::new(Vec::new());
+        let sink = ContextPrintingSink::new(&printer).dim(Some(dim_matcher));
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"health check\nbrown fox\nhello\n", sink)
+            .expect("search failed");
+
+        let expected = format!(
+            "{0}health check{1}\n{2}brown fox{3}\nhello\n",
+            Start(&Attribute::Dim),
+            End(&Attribute::Dim),
+            Fg(AnsiValue(9)),
+            Fg(Reset)
+        );
+        assert_eq!(
+            expected,
+            String::from_utf8(printer.into_inner()).expect("not utf-8")
+        );
+    }
+
+    #[test_case(true, "fox\nfox\nfox\nno\nfox\n", &[Some(9), Some(1), Some(9), None, Some(9)]; "passthru")]
+    #[test_case(false, "fox\nfox\nno\nfox\n", &[Some(9), Some(1), Some(9)]; "only matching")]
+    fn test_alternates_highlight_of_adjacent_matches(
+        passthru: bool,
+        input: &str,
+        expected_colors: &[Option<u8>],
+    ) {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let printer = WritePrinter::new(Vec::new());
+        let sink = ContextPrintingSink::new(&printer)
+            .passthru(passthru)
+            .alternate(Some(Fg(AnsiValue(1))));
+        SearcherBuilder::new()
+            .passthru(passthru)
+            .build()
+            .search_slice(matcher, input.as_bytes(), sink)
+            .expect("search failed");
+
+        // Without passthru, only the matching lines are printed
+        let expected = input
+            .lines()
+            .filter(|line| passthru || line.contains("fox"))
+            .zip(expected_colors)
+            .map(|(line, color)| match color {
+                Some(color) => format!("{}{line}{}\n", Fg(AnsiValue(*color)), Fg(Reset)),
+                None => format!("{line}\n"),
+            })
+            .collect::<String>();
+        assert_eq!(
+            expected,
+            String::from_utf8(printer.into_inner()).expect("not utf-8")
+        );
+    }
+
+    #[test_case(false, ["1:", "2:", "3:"]; "line numbers")]
+    #[test_case(true, ["1:0:", "2:6:", "3:16:"]; "line numbers and byte offsets")]
+    fn test_prints_line_numbers(byte_offset: bool, prefixes: [&str; 3]) {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let printer = WritePrinter::new(Vec::new());
+        let sink = ContextPrintingSink::new(&printer)
+            .line_number(true)
+            .byte_offset(byte_offset);
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"hello\nbrown fox\nend", sink)
+            .expect("search failed");
+
+        let expected = format!(
+            "{}hello\n{}{}brown fox{}\n{}end",
+            prefixes[0],
+            prefixes[1],
+            Fg(AnsiValue(9)),
+            Fg(Reset),
+            prefixes[2]
+        );
+        assert_eq!(
+            expected,
+            String::from_utf8(printer.into_inner()).expect("not utf-8")
+        );
+    }
+
+    #[test_case(b'\n'; "newline")]
+    #[test_case(b'\0'; "nul")]
+    fn test_prints_byte_offsets(line_terminator: u8) {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let printer = WritePrinter::new(Vec::new());
+        let sink = ContextPrintingSink::new(&printer).byte_offset(true);
+        let terminator = char::from(line_terminator);
+        SearcherBuilder::new()
+            .passthru(true)
+            .line_terminator(LineTerminator::byte(line_terminator))
+            .build()
+            .search_slice(
+                matcher,
+                format!("hello{terminator}brown fox{terminator}end").as_bytes(),
+                sink,
+            )
+            .expect("search failed");
+
+        let expected = format!(
+            "0:hello{terminator}6:{}brown fox{}{terminator}16:end",
+            Fg(AnsiValue(9)),
+            Fg(Reset)
+        );
+        assert_eq!(
+            expected,
+            String::from_utf8(printer.into_inner()).expect("not utf-8")
+        );
+    }
+
+    #[test_case("fox", "brown fox", "      ^^^"; "single match")]
+    #[test_case("o", "brown fox", "  ^    ^"; "multiple matches")]
+    #[test_case("fox", "\tbrown\tfox", "\t     \t^^^"; "tabs are kept")]
+    #[test_case("f.x", "brüwn fox", "      ^^^"; "multibyte characters")]
+    #[test_case("cat", "brown fox", ""; "no matches")]
+    fn test_marker_line(pattern: &str, line: &str, expected: &str) {
+        let matcher = RegexMatcher::new(pattern).expect("regexp doesn't compile");
+        assert_eq!(expected, marker_line(&matcher, line.as_bytes()));
+    }
+
+    #[test]
+    fn test_prints_markers_under_matches() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::new(&mock_printer).markers(Some(matcher.clone()));
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"hello\nbrown fox", sink)
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &[
+                "hello\n".to_string(),
+                "\n".to_string(),
+                "      ^^^\n".to_string()
+            ],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_replaces_invalid_utf8() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::new(&mock_printer);
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"caf\xe9\nbrown \xff fox\n", sink)
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &["caf\u{FFFD}\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["brown \u{FFFD} fox\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_escapes_invalid_utf8() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::new(&mock_printer).invalid_utf8(InvalidUtf8::Escape);
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"caf\xe9\nbrown \xff fox\n", sink)
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &["caf\\xe9\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["brown \\xff fox\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_truncates_lines_around_match() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink =
+            ContextPrintingSink::new(&mock_printer).max_columns(Some(10), Some(matcher.clone()));
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(
+                matcher,
+                b"the quick brown fox jumped over\nthe lazy dog sat\n",
+                sink,
+            )
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &["…n fox ju…\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["the lazy …\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test_case("o", "0", "the quick br0wn f0x\n"; "literal")]
+    #[test_case(r"(\w+) (\w+)", "$2 $1", "quick the fox brown\n"; "numbered groups")]
+    #[test_case(r"(?P<animal>fox)", "[${animal}]", "the quick brown [fox]\n"; "named groups")]
+    #[test_case("fox", "$$5", "the quick brown $5\n"; "escaped dollar sign")]
+    fn test_replaces_matches(pattern: &str, template: &str, expected: &str) {
+        let matcher = RegexMatcher::new(pattern).expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::new(&mock_printer)
+            .replace(Some((matcher.clone(), template.as_bytes().to_vec())));
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"the quick brown fox\nlazy\n", sink)
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &[expected.to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["lazy\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_keeps_invalid_utf8() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let printer = WritePrinter::new(Vec::new());
+        let sink = ContextPrintingSink::new(&printer)
+            .context_color(None)
+            .invalid_utf8(InvalidUtf8::Keep);
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"caf\xe9\nbrown \xff fox\n", sink)
+            .expect("search failed");
+
+        let output = printer.into_inner();
+        assert!(output.starts_with(b"caf\xe9\n"));
+        assert!(output
+            .windows(b"brown \xff fox".len())
+            .any(|window| window == b"brown \xff fox"));
+    }
+
+    #[test]
+    fn test_does_not_color_other_line_terminators() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::new(&mock_printer);
+        SearcherBuilder::new()
+            .passthru(true)
+            .line_terminator(LineTerminator::byte(b'\0'))
+            .build()
+            .search_slice(matcher, b"brown fox\0jumped\0", sink)
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &["brown fox".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["\0".to_string(), "jumped".to_string(), "\0".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_styles_context_with_color() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::new(&mock_printer).context_color(Some(AnsiValue(8)));
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, "The quick \nbrown fox \n".as_bytes(), sink)
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &[format!("{}The quick {}\n", Fg(AnsiValue(8)), Fg(Reset))],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_styles_context_with_style() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let style = Style {
+            attributes: Attributes::NONE.with(Attribute::Dim),
+            ..Style::fg(AnsiValue(8))
+        };
+        let sink = ContextPrintingSink::new(&mock_printer).context_style(Some(style));
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, "The quick \nbrown fox \n".as_bytes(), sink)
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &[format!("{}The quick {}\n", Start(&style), End(&style))],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+
+    #[test]
+    fn test_styles_context_with_theme() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = ContextPrintingSink::with_theme(&mock_printer, &Theme::SOLARIZED);
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, "The quick \nbrown fox \n".as_bytes(), sink)
+            .expect("search failed");
+
+        let context_color = Theme::SOLARIZED
+            .context
+            .expect("theme has no context color");
+        testutil::assert_slices_eq!(
+            &[format!("{}The quick {}\n", Fg(context_color), Fg(Reset))],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["brown fox \n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
 }
@@ -0,0 +1,90 @@
+use super::Error;
+use grep::searcher::{Searcher, Sink, SinkContext, SinkFinish, SinkMatch};
+
+/// `BinaryQuittingSink` forwards every result it is given to another [`Sink`], failing with [`Error::BinaryData`] if
+/// the search is stopped because binary data was found. Without this, a searcher that quits on binary data would end
+/// the search as if the input had simply ended there.
+pub(crate) struct BinaryQuittingSink<S: Sink<Error = Error>> {
+    sink: S,
+}
+
+impl<S: Sink<Error = Error>> BinaryQuittingSink<S> {
+    pub(crate) fn new(sink: S) -> Self {
+        Self { sink }
+    }
+}
+
+impl<S: Sink<Error = Error>> Sink for BinaryQuittingSink<S> {
+    type Error = Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        self.sink.matched(searcher, sink_match)
+    }
+
+    fn context(
+        &mut self,
+        searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        self.sink.context(searcher, context)
+    }
+
+    fn context_break(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.sink.context_break(searcher)
+    }
+
+    fn binary_data(
+        &mut self,
+        searcher: &Searcher,
+        binary_byte_offset: u64,
+    ) -> Result<bool, Self::Error> {
+        if searcher.binary_detection().quit_byte().is_some() {
+            return Err(Error::BinaryData(binary_byte_offset));
+        }
+
+        self.sink.binary_data(searcher, binary_byte_offset)
+    }
+
+    fn begin(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.sink.begin(searcher)
+    }
+
+    fn finish(&mut self, searcher: &Searcher, finish: &SinkFinish) -> Result<(), Self::Error> {
+        self.sink.finish(searcher, finish)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::ContextPrintingSink;
+    use crate::testutil::mock_print::MockPrinter;
+    use grep::regex::RegexMatcher;
+    use grep::searcher::{BinaryDetection, SearcherBuilder};
+    use test_case::test_case;
+
+    #[test_case(BinaryDetection::quit(b'\0'), Some(6); "quitting")]
+    #[test_case(BinaryDetection::none(), None; "not detecting")]
+    fn test_fails_only_when_quitting(detection: BinaryDetection, expected_offset: Option<u64>) {
+        let matcher = RegexMatcher::new("hello").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let sink = BinaryQuittingSink::new(ContextPrintingSink::new(&mock_printer));
+        let res = SearcherBuilder::new()
+            .passthru(true)
+            .binary_detection(detection)
+            .build()
+            .search_reader(matcher, b"hello\n\0world\n".as_slice(), sink);
+
+        match (res, expected_offset) {
+            (Err(Error::BinaryData(offset)), Some(expected_offset)) => {
+                assert_eq!(expected_offset, offset);
+            }
+            (Ok(()), None) => {}
+            (res, _) => panic!("unexpected search result {res:?}"),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use super::{validate_searcher, Error};
+use crate::lines;
+use grep::matcher::Matcher;
+use grep::regex::RegexMatcher;
+use grep::searcher::{LineIter, Searcher, Sink, SinkContext, SinkError, SinkMatch};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// `LineEvent` describes a single line of the input, as given to the callback of a [`CallbackSink`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct LineEvent<'a> {
+    /// The contents of the line, without its line terminator.
+    pub line: &'a [u8],
+    /// Whether or not the line matched.
+    pub matched: bool,
+    /// The number of the line, starting from 1.
+    pub line_number: u64,
+    /// The offset of the start of the line from the start of the input, in bytes.
+    pub byte_offset: u64,
+    /// The start and end of each match within the line, as byte offsets into [`line`](`LineEvent::line`). This is
+    /// empty for lines that did not match, or if the [`CallbackSink`] was not given a matcher to find them with.
+    pub spans: &'a [Range<usize>],
+}
+
+impl LineEvent<'_> {
+    /// Get the contents of the line as text. Any invalid UTF-8 is replaced with `U+FFFD`.
+    #[must_use]
+    pub fn text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.line)
+    }
+
+    /// Get the start and end of each match within the line, as byte offsets from the start of the input.
+    pub fn absolute_spans(&self) -> impl Iterator<Item = Range<u64>> + '_ {
+        self.spans
+            .iter()
+            .map(|span| self.byte_offset + span.start as u64..self.byte_offset + span.end as u64)
+    }
+}
+
+/// `OwnedLineEvent` is a [`LineEvent`] that owns its line and spans, so that it can be kept after the search has moved
+/// on from the line it describes, such as by [`ScanLines`](`crate::iter::ScanLines`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OwnedLineEvent {
+    /// The contents of the line, without its line terminator.
+    pub line: Vec<u8>,
+    /// Whether or not the line matched.
+    pub matched: bool,
+    /// The number of the line, starting from 1.
+    pub line_number: u64,
+    /// The offset of the start of the line from the start of the input, in bytes.
+    pub byte_offset: u64,
+    /// The start and end of each match within the line, as byte offsets into [`line`](`OwnedLineEvent::line`).
+    pub spans: Vec<Range<usize>>,
+}
+
+impl OwnedLineEvent {
+    /// Borrow this event as a [`LineEvent`], such as to get its [`text`](`LineEvent::text`).
+    #[must_use]
+    pub fn as_event(&self) -> LineEvent<'_> {
+        LineEvent {
+            line: &self.line,
+            matched: self.matched,
+            line_number: self.line_number,
+            byte_offset: self.byte_offset,
+            spans: &self.spans,
+        }
+    }
+}
+
+impl From<&LineEvent<'_>> for OwnedLineEvent {
+    fn from(event: &LineEvent) -> Self {
+        Self {
+            line: event.line.to_vec(),
+            matched: event.matched,
+            line_number: event.line_number,
+            byte_offset: event.byte_offset,
+            spans: event.spans.to_vec(),
+        }
+    }
+}
+
+/// `CallbackSink` calls the given function with a [`LineEvent`] for every line it is given, allowing the results of a
+/// search to be consumed without printing them. If it is made with [`CallbackSink::with_matcher`], each event will
+/// also hold the spans of the matches within its line.
+///
+/// # Panics
+///
+/// Like [`ContextPrintingSink`](`super::ContextPrintingSink`), the searcher this sink is used with must have passthru
+/// enabled. It must also have line numbers enabled, which is the default.
+#[allow(clippy::module_name_repetitions)]
+pub struct CallbackSink<F: FnMut(&LineEvent), M: Matcher = RegexMatcher> {
+    callback: F,
+    matcher: Option<M>,
+}
+
+impl<F: FnMut(&LineEvent)> CallbackSink<F> {
+    /// Make a new `CallbackSink` that calls the given function for every line. The events it is given will not hold
+    /// the spans of any matches.
+    #[must_use]
+    pub fn new(callback: F) -> Self {
+        Self {
+            callback,
+            matcher: None,
+        }
+    }
+}
+
+impl<F: FnMut(&LineEvent), M: Matcher> CallbackSink<F, M> {
+    /// Make a new `CallbackSink` that calls the given function for every line, finding the spans of the matches within
+    /// each matching line with `matcher`. This must be the same matcher used for the search.
+    #[must_use]
+    pub fn with_matcher(matcher: M, callback: F) -> Self {
+        Self {
+            callback,
+            matcher: Some(matcher),
+        }
+    }
+
+    fn handle_lines(
+        &mut self,
+        bytes: &[u8],
+        matched: bool,
+        mut line_number: u64,
+        mut byte_offset: u64,
+    ) -> Result<(), Error> {
+        let mut spans = Vec::new();
+        for line in LineIter::new(b'\n', bytes) {
+            let line_contents = lines::without_terminator(line);
+            spans.clear();
+            if let (true, Some(matcher)) = (matched, &self.matcher) {
+                matcher
+                    .find_iter(line_contents, |found_match| {
+                        spans.push(found_match.start()..found_match.end());
+                        true
+                    })
+                    .map_err(Error::error_message)?;
+            }
+
+            (self.callback)(&LineEvent {
+                line: line_contents,
+                matched,
+                line_number,
+                byte_offset,
+                spans: &spans,
+            });
+
+            line_number += 1;
+            byte_offset += line.len() as u64;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F: FnMut(&LineEvent), M: Matcher> Sink for CallbackSink<F, M> {
+    type Error = Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        validate_searcher(searcher);
+
+        let line_number = sink_match
+            .line_number()
+            .expect("line numbers are not enabled on the given searcher");
+        self.handle_lines(
+            sink_match.bytes(),
+            true,
+            line_number,
+            sink_match.absolute_byte_offset(),
+        )?;
+
+        Ok(true)
+    }
+
+    fn context(
+        &mut self,
+        searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        validate_searcher(searcher);
+
+        let line_number = context
+            .line_number()
+            .expect("line numbers are not enabled on the given searcher");
+        self.handle_lines(
+            context.bytes(),
+            false,
+            line_number,
+            context.absolute_byte_offset(),
+        )?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grep::searcher::SearcherBuilder;
+
+    #[test]
+    fn test_calls_back_for_every_line() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mut events = Vec::new();
+        let sink = CallbackSink::new(|event: &LineEvent| {
+            events.push((
+                event.text().to_string(),
+                event.matched,
+                event.line_number,
+                event.byte_offset,
+            ));
+        });
+
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"The quick\r\nbrown fox\njumped", sink)
+            .expect("search failed");
+
+        assert_eq!(
+            vec![
+                ("The quick".to_string(), false, 1, 0),
+                ("brown fox".to_string(), true, 2, 11),
+                ("jumped".to_string(), false, 3, 21),
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn test_finds_spans_with_matcher() {
+        let matcher = RegexMatcher::new("o").expect("regexp doesn't compile");
+        let mut events = Vec::new();
+        let sink = CallbackSink::with_matcher(&matcher, |event: &LineEvent| {
+            events.push((
+                event.spans.to_vec(),
+                event.absolute_spans().collect::<Vec<_>>(),
+            ));
+        });
+
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(&matcher, b"The quick\nbrown fox\n", sink)
+            .expect("search failed");
+
+        assert_eq!(
+            vec![(vec![], vec![]), (vec![2..3, 7..8], vec![12..13, 17..18]),],
+            events
+        );
+    }
+}
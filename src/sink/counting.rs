@@ -0,0 +1,120 @@
+use grep::searcher::{Searcher, Sink, SinkContext, SinkFinish, SinkMatch};
+
+/// `MatchCountingSink` wraps another [`Sink`], and counts the number of matching lines that pass through it. All
+/// results are forwarded to the wrapped [`Sink`] unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use grep::regex::RegexMatcher;
+/// use hline::print::StdoutPrinter;
+/// use hline::sink::{ContextPrintingSink, MatchCountingSink};
+///
+/// let matcher = RegexMatcher::new("world").expect("regexp doesn't compile");
+/// let mut sink = MatchCountingSink::new(ContextPrintingSink::new(StdoutPrinter::new()));
+/// hline::scan_matcher_to_sink("hello\nworld\n".as_bytes(), matcher, &mut sink)
+///     .expect("search failed");
+///
+/// assert_eq!(1, sink.matched_lines());
+/// ```
+#[allow(clippy::module_name_repetitions)]
+pub struct MatchCountingSink<S: Sink> {
+    sink: S,
+    matched_lines: u64,
+}
+
+impl<S: Sink> MatchCountingSink<S> {
+    /// Make a new `MatchCountingSink` that forwards all results to the given [`Sink`].
+    #[must_use]
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            matched_lines: 0,
+        }
+    }
+
+    /// Get the number of matching lines that have been seen so far.
+    #[must_use]
+    pub fn matched_lines(&self) -> u64 {
+        self.matched_lines
+    }
+
+    /// Get the wrapped [`Sink`] back.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S: Sink> Sink for MatchCountingSink<S> {
+    type Error = S::Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        self.matched_lines += sink_match.lines().count() as u64;
+
+        self.sink.matched(searcher, sink_match)
+    }
+
+    fn context(
+        &mut self,
+        searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        self.sink.context(searcher, context)
+    }
+
+    fn context_break(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.sink.context_break(searcher)
+    }
+
+    fn binary_data(
+        &mut self,
+        searcher: &Searcher,
+        binary_byte_offset: u64,
+    ) -> Result<bool, Self::Error> {
+        self.sink.binary_data(searcher, binary_byte_offset)
+    }
+
+    fn begin(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.sink.begin(searcher)
+    }
+
+    fn finish(&mut self, searcher: &Searcher, finish: &SinkFinish) -> Result<(), Self::Error> {
+        self.sink.finish(searcher, finish)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::ContextPrintingSink;
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+    use grep::regex::RegexMatcher;
+    use grep::searcher::SearcherBuilder;
+
+    #[test]
+    fn test_counts_and_forwards_matches() {
+        let matcher = RegexMatcher::new("fox|dog").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let mut sink = MatchCountingSink::new(ContextPrintingSink::new(&mock_printer));
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"quick\nbrown fox\nlazy\ndog\n", &mut sink)
+            .expect("search failed");
+
+        assert_eq!(2, sink.matched_lines());
+        testutil::assert_slices_eq!(
+            &["brown fox\n".to_string(), "dog\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["quick\n".to_string(), "lazy\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use super::{get_sink_result_for_print_result, Error};
+use crate::lines;
+use crate::print;
+use grep::matcher::Matcher;
+use grep::searcher::{Searcher, Sink, SinkError, SinkMatch};
+use std::borrow::Cow;
+use std::io::Write;
+
+/// The header row of the CSV written by [`CsvSink`].
+pub(crate) const HEADER: &str = "file,line_number,byte_offset,matched_text,full_line\n";
+
+/// `CsvSink` writes each matching line as a row of CSV, with the columns given by [`HEADER`]. Lines that do not match
+/// are not written at all, and neither is the header row.
+///
+/// The searcher used with this sink must have line numbers enabled.
+pub(crate) struct CsvSink<M: Matcher, W: Write> {
+    matcher: M,
+    source_name: String,
+    writer: W,
+}
+
+impl<M: Matcher, W: Write> CsvSink<M, W> {
+    /// Make a new `CsvSink`. `matcher` must be the same matcher used for the search, and `source_name` is the name of
+    /// the input that will be written in the `file` column.
+    pub(crate) fn new(matcher: M, source_name: &str, writer: W) -> Self {
+        Self {
+            matcher,
+            source_name: source_name.to_string(),
+            writer,
+        }
+    }
+
+    fn write_row(
+        &mut self,
+        line_number: u64,
+        byte_offset: u64,
+        line: &[u8],
+        line_terminator: u8,
+    ) -> Result<bool, Error> {
+        let line = lines::without_line_terminator(line, line_terminator);
+        let found_match = match self.matcher.find(line) {
+            Ok(Some(found_match)) => found_match,
+            // If a line doesn't have a match, it is not a matching line and should not get a row.
+            Ok(None) => return Ok(true),
+            Err(err) => return Err(Error::error_message(err)),
+        };
+
+        let row = format!(
+            "{},{},{},{},{}\n",
+            escape_field(&self.source_name),
+            line_number,
+            byte_offset,
+            escape_field(&String::from_utf8_lossy(&line[found_match])),
+            escape_field(&String::from_utf8_lossy(line)),
+        );
+
+        self.write(&row)
+    }
+
+    fn write(&mut self, data: &str) -> Result<bool, Error> {
+        let write_res = self
+            .writer
+            .write_all(data.as_bytes())
+            .map_err(print::Error::from);
+
+        get_sink_result_for_print_result(write_res)
+    }
+}
+
+impl<M: Matcher, W: Write> Sink for CsvSink<M, W> {
+    type Error = Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        let line_terminator = searcher.line_terminator().as_byte();
+        let first_line_number = sink_match
+            .line_number()
+            .expect("line numbers are not enabled on the given searcher");
+        let mut byte_offset = sink_match.absolute_byte_offset();
+
+        for (line_number, line) in (first_line_number..).zip(sink_match.lines()) {
+            if !self.write_row(line_number, byte_offset, line, line_terminator)? {
+                return Ok(false);
+            }
+
+            byte_offset += line.len() as u64;
+        }
+
+        Ok(true)
+    }
+}
+
+/// `escape_field` quotes the given field, as per RFC 4180, if it contains any characters that require it.
+fn escape_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\r', '\n']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grep::regex::RegexMatcher;
+    use grep::searcher::SearcherBuilder;
+    use test_case::test_case;
+
+    #[test_case("hello", "hello"; "plain field")]
+    #[test_case("hello, world", "\"hello, world\""; "field with comma")]
+    #[test_case("say \"hello\"", "\"say \"\"hello\"\"\""; "field with quotes")]
+    fn test_escape_field(field: &str, expected: &str) {
+        assert_eq!(expected, escape_field(field));
+    }
+
+    #[test]
+    fn test_writes_matching_lines() {
+        let matcher = RegexMatcher::new("fo+").expect("regexp doesn't compile");
+        let mut output = Vec::new();
+        let sink = CsvSink::new(matcher.clone(), "my.log", &mut output);
+        SearcherBuilder::new()
+            .line_number(true)
+            .build()
+            .search_slice(matcher, b"hello\nfooo, bar\r\nworld\n", sink)
+            .expect("search failed");
+
+        assert_eq!(
+            "my.log,2,6,fooo,\"fooo, bar\"\n",
+            String::from_utf8(output).expect("not utf-8")
+        );
+    }
+}
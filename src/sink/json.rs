@@ -0,0 +1,449 @@
+use super::{get_sink_result_for_print_result, validate_searcher, Error};
+use crate::lines;
+use crate::print;
+use grep::matcher::Matcher;
+use grep::searcher::{LineIter, Searcher, Sink, SinkContext, SinkError, SinkFinish, SinkMatch};
+use serde::{Serialize, Serializer};
+use std::borrow::Cow;
+use std::io;
+use std::io::Write;
+use std::ops::Add;
+use std::time::{Duration, Instant};
+
+/// `JsonSink` writes a message for every line it is given as a JSON object, each on its own line (i.e. JSON Lines),
+/// following the schema of ripgrep's `--json` output, so that tools that read ripgrep's output can read it too. Each
+/// object has a `type`, and its contents in `data`:
+///
+/// - `begin`: written before anything else, holding the `path` of the input.
+/// - `match` and `context`: written for lines that did and did not match, respectively. These hold the `path`, the
+///   `lines` themselves, with their line terminators, their `line_number`, starting from 1, their `absolute_offset`
+///   into the input, and the `submatches` within them. Each submatch holds the text of the `match`, and its `start`
+///   and `end` as byte offsets into the lines. Context lines have no submatches.
+/// - `end`: written once the input has been scanned, holding the `path`, the `binary_offset` at which binary data was
+///   found, if any, and the `stats` of the scan (see [`JsonStats`]).
+///
+/// Paths and lines are written as `{"text": ...}`. Unlike ripgrep, which writes text that is not valid UTF-8 as
+/// base64 under `bytes`, invalid UTF-8 is replaced with `U+FFFD`.
+///
+/// The searcher used with this sink must have passthru and line numbers enabled.
+pub(crate) struct JsonSink<M: Matcher, W: Write> {
+    matcher: M,
+    source_name: String,
+    writer: W,
+    stats: JsonStats,
+    started_at: Option<Instant>,
+}
+
+/// `JsonStats` holds the statistics written in the `end` message of a scan written as JSON. The statistics of several
+/// scans can be added together, to be written in the `summary` message that follows all of them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+#[allow(clippy::module_name_repetitions)]
+pub struct JsonStats {
+    /// The time taken by the scans.
+    #[serde(serialize_with = "serialize_duration")]
+    pub elapsed: Duration,
+    /// The number of inputs that were scanned.
+    pub searches: u64,
+    /// The number of inputs that had at least one match.
+    pub searches_with_match: u64,
+    /// The number of bytes of input that were scanned.
+    pub bytes_searched: u64,
+    /// The number of bytes of JSON that were written for the `match` and `context` messages.
+    pub bytes_printed: u64,
+    /// The number of lines that matched.
+    pub matched_lines: u64,
+    /// The number of matches within those lines.
+    pub matches: u64,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "lowercase")]
+enum Message<'a> {
+    Begin {
+        path: Text<'a>,
+    },
+    Match(LinesObject<'a>),
+    Context(LinesObject<'a>),
+    End {
+        path: Text<'a>,
+        binary_offset: Option<u64>,
+        stats: JsonStats,
+    },
+    Summary {
+        #[serde(serialize_with = "serialize_duration")]
+        elapsed_total: Duration,
+        stats: JsonStats,
+    },
+}
+
+#[derive(Serialize)]
+struct LinesObject<'a> {
+    path: Text<'a>,
+    lines: Text<'a>,
+    line_number: u64,
+    absolute_offset: u64,
+    submatches: Vec<Submatch<'a>>,
+}
+
+#[derive(Serialize)]
+struct Submatch<'a> {
+    #[serde(rename = "match")]
+    matched: Text<'a>,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Serialize)]
+struct Text<'a> {
+    text: Cow<'a, str>,
+}
+
+impl<M: Matcher, W: Write> JsonSink<M, W> {
+    /// Make a new `JsonSink`. `matcher` must be the same matcher used for the search, and `source_name` is the name of
+    /// the input that will be written as the `path` of each message.
+    pub(crate) fn new(matcher: M, source_name: &str, writer: W) -> Self {
+        Self {
+            matcher,
+            source_name: source_name.to_string(),
+            writer,
+            stats: JsonStats::default(),
+            started_at: None,
+        }
+    }
+
+    /// Get the statistics of the scan. These are only complete once the search has finished.
+    pub(crate) fn stats(&self) -> JsonStats {
+        self.stats
+    }
+
+    /// Find the matches within the given lines, as byte offsets into them. Each line is searched without its line
+    /// terminator, as it was when the searcher matched it.
+    fn find_submatches<'b>(
+        &self,
+        bytes: &'b [u8],
+        line_terminator: u8,
+    ) -> Result<Vec<Submatch<'b>>, Error> {
+        let mut submatches = Vec::new();
+        let mut line_offset = 0;
+        for line in LineIter::new(line_terminator, bytes) {
+            let haystack = lines::without_line_terminator(line, line_terminator);
+            self.push_submatches(haystack, line_offset, &mut submatches)?;
+            line_offset += line.len();
+        }
+
+        Ok(submatches)
+    }
+
+    fn push_submatches<'b>(
+        &self,
+        haystack: &'b [u8],
+        offset: usize,
+        submatches: &mut Vec<Submatch<'b>>,
+    ) -> Result<(), Error> {
+        self.matcher
+            .find_iter(haystack, |found_match| {
+                submatches.push(Submatch {
+                    matched: Text {
+                        text: String::from_utf8_lossy(&haystack[found_match]),
+                    },
+                    start: offset + found_match.start(),
+                    end: offset + found_match.end(),
+                });
+                true
+            })
+            .map_err(Error::error_message)
+    }
+}
+
+impl<M: Matcher, W: Write> Sink for JsonSink<M, W> {
+    type Error = Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        validate_searcher(searcher);
+
+        let line_number = sink_match
+            .line_number()
+            .expect("line numbers are not enabled on the given searcher");
+        let submatches =
+            self.find_submatches(sink_match.bytes(), searcher.line_terminator().as_byte())?;
+        self.stats.matched_lines += sink_match.lines().count() as u64;
+        self.stats.matches += submatches.len() as u64;
+        self.stats.searches_with_match = 1;
+
+        let message = Message::Match(LinesObject {
+            path: Text::from(self.source_name.as_str()),
+            lines: Text {
+                text: String::from_utf8_lossy(sink_match.bytes()),
+            },
+            line_number,
+            absolute_offset: sink_match.absolute_byte_offset(),
+            submatches,
+        });
+
+        write_lines(&mut self.writer, &mut self.stats, &message)
+    }
+
+    fn context(
+        &mut self,
+        searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        validate_searcher(searcher);
+
+        let line_number = context
+            .line_number()
+            .expect("line numbers are not enabled on the given searcher");
+        let message = Message::Context(LinesObject {
+            path: Text::from(self.source_name.as_str()),
+            lines: Text {
+                text: String::from_utf8_lossy(context.bytes()),
+            },
+            line_number,
+            absolute_offset: context.absolute_byte_offset(),
+            submatches: Vec::new(),
+        });
+
+        write_lines(&mut self.writer, &mut self.stats, &message)
+    }
+
+    fn begin(&mut self, _searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.started_at = Some(Instant::now());
+
+        let write_res = write_message(
+            &mut self.writer,
+            &Message::Begin {
+                path: Text::from(self.source_name.as_str()),
+            },
+        );
+        get_sink_result_for_print_result(write_res.map(|_| ()).map_err(print::Error::from))
+    }
+
+    fn finish(&mut self, _searcher: &Searcher, finish: &SinkFinish) -> Result<(), Self::Error> {
+        self.stats.searches = 1;
+        self.stats.bytes_searched = finish.byte_count();
+        if let Some(started_at) = self.started_at {
+            self.stats.elapsed = started_at.elapsed();
+        }
+
+        let message = Message::End {
+            path: Text::from(self.source_name.as_str()),
+            binary_offset: finish.binary_byte_offset(),
+            stats: self.stats,
+        };
+        let write_res = write_message(&mut self.writer, &message);
+
+        // If output was closed, there is nothing left to do anyway
+        get_sink_result_for_print_result(write_res.map(|_| ()).map_err(print::Error::from))
+            .map(|_| ())
+    }
+}
+
+impl<'a> From<&'a str> for Text<'a> {
+    fn from(text: &'a str) -> Self {
+        Self {
+            text: Cow::Borrowed(text),
+        }
+    }
+}
+
+impl Add for JsonStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            elapsed: self.elapsed + other.elapsed,
+            searches: self.searches + other.searches,
+            searches_with_match: self.searches_with_match + other.searches_with_match,
+            bytes_searched: self.bytes_searched + other.bytes_searched,
+            bytes_printed: self.bytes_printed + other.bytes_printed,
+            matched_lines: self.matched_lines + other.matched_lines,
+            matches: self.matches + other.matches,
+        }
+    }
+}
+
+/// Write the `summary` message that follows the messages of every scan, with the given statistics summed across all
+/// of them, and the total time taken.
+pub(crate) fn write_summary<W: Write>(
+    mut writer: W,
+    stats: JsonStats,
+    elapsed_total: Duration,
+) -> io::Result<()> {
+    let message = Message::Summary {
+        elapsed_total,
+        stats,
+    };
+
+    write_message(&mut writer, &message).map(|_| ())
+}
+
+/// Write the given `match` or `context` message, counting the bytes written in the given statistics.
+fn write_lines<W: Write>(
+    writer: &mut W,
+    stats: &mut JsonStats,
+    message: &Message,
+) -> Result<bool, Error> {
+    let write_res = write_message(writer, message);
+    if let Ok(bytes_written) = write_res {
+        stats.bytes_printed += bytes_written as u64;
+    }
+
+    get_sink_result_for_print_result(write_res.map(|_| ()).map_err(print::Error::from))
+}
+
+/// Write the given message on its own line, returning the number of bytes written.
+fn write_message<W: Write>(writer: &mut W, message: &Message) -> io::Result<usize> {
+    // Serializing these types cannot fail; only the write can.
+    let mut encoded = serde_json::to_string(message).expect("failed to encode message as JSON");
+    encoded.push('\n');
+
+    writer.write_all(encoded.as_bytes())?;
+
+    Ok(encoded.len())
+}
+
+/// Serialize a [`Duration`] as ripgrep does, as its whole seconds, the nanoseconds past them, and a human-readable
+/// number of seconds.
+fn serialize_duration<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct DurationObject {
+        secs: u64,
+        nanos: u32,
+        human: String,
+    }
+
+    DurationObject {
+        secs: duration.as_secs(),
+        nanos: duration.subsec_nanos(),
+        human: format!("{:.6}s", duration.as_secs_f64()),
+    }
+    .serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grep::regex::RegexMatcher;
+    use grep::searcher::SearcherBuilder;
+    use serde_json::{json, Value};
+
+    /// Parse each line of the given output as JSON, leaving out the time taken, which can't be known ahead of time.
+    fn parse_messages(output: &[u8]) -> Vec<Value> {
+        String::from_utf8_lossy(output)
+            .lines()
+            .map(|line| {
+                let mut message: Value = serde_json::from_str(line).expect("not valid JSON");
+                if let Some(stats) = message
+                    .pointer_mut("/data/stats")
+                    .and_then(Value::as_object_mut)
+                {
+                    stats.remove("elapsed");
+                }
+
+                message
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_writes_every_line() {
+        let matcher = RegexMatcher::new("o").expect("regexp doesn't compile");
+        let mut output = Vec::new();
+        let mut sink = JsonSink::new(matcher.clone(), "my.log", &mut output);
+        SearcherBuilder::new()
+            .passthru(true)
+            .line_number(true)
+            .build()
+            .search_slice(matcher, b"hello \"world\"\r\nabc\n", &mut sink)
+            .expect("search failed");
+
+        let stats = sink.stats();
+        let path = json!({"text": "my.log"});
+        assert_eq!(
+            vec![
+                json!({"type": "begin", "data": {"path": path}}),
+                json!({
+                    "type": "match",
+                    "data": {
+                        "path": path,
+                        "lines": {"text": "hello \"world\"\r\n"},
+                        "line_number": 1,
+                        "absolute_offset": 0,
+                        "submatches": [
+                            {"match": {"text": "o"}, "start": 4, "end": 5},
+                            {"match": {"text": "o"}, "start": 8, "end": 9},
+                        ],
+                    },
+                }),
+                json!({
+                    "type": "context",
+                    "data": {
+                        "path": path,
+                        "lines": {"text": "abc\n"},
+                        "line_number": 2,
+                        "absolute_offset": 15,
+                        "submatches": [],
+                    },
+                }),
+                json!({
+                    "type": "end",
+                    "data": {
+                        "path": path,
+                        "binary_offset": null,
+                        "stats": {
+                            "searches": 1,
+                            "searches_with_match": 1,
+                            "bytes_searched": 19,
+                            "bytes_printed": stats.bytes_printed,
+                            "matched_lines": 1,
+                            "matches": 2,
+                        },
+                    },
+                }),
+            ],
+            parse_messages(&output)
+        );
+    }
+
+    #[test]
+    fn test_writes_summary() {
+        let stats = JsonStats {
+            elapsed: Duration::from_millis(1500),
+            searches: 2,
+            searches_with_match: 1,
+            bytes_searched: 100,
+            bytes_printed: 200,
+            matched_lines: 3,
+            matches: 4,
+        };
+        let mut output = Vec::new();
+        write_summary(&mut output, stats + stats, Duration::from_secs(4)).expect("write failed");
+
+        let elapsed_total = json!({"secs": 4, "nanos": 0, "human": "4.000000s"});
+        assert_eq!(
+            vec![json!({
+                "type": "summary",
+                "data": {
+                    "elapsed_total": elapsed_total,
+                    "stats": {
+                        "searches": 4,
+                        "searches_with_match": 2,
+                        "bytes_searched": 200,
+                        "bytes_printed": 400,
+                        "matched_lines": 6,
+                        "matches": 8,
+                    },
+                },
+            })],
+            parse_messages(&output)
+        );
+    }
+}
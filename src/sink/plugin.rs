@@ -0,0 +1,157 @@
+use super::{get_sink_result_for_print_result, validate_searcher, Error};
+use crate::color::{Fg, LightRed};
+use crate::lines;
+use crate::plugin::{LineInfo, LuaPlugin, Verdict};
+use crate::print::Printer;
+use grep::matcher::Matcher;
+use grep::searcher::{Searcher, Sink, SinkContext, SinkMatch};
+
+/// `PluginSink` prints every line it is given to a [`Printer`], after passing it through a [`LuaPlugin`], which may
+/// replace it, change its highlighting, or drop it altogether.
+///
+/// # Panics
+///
+/// Like [`ContextPrintingSink`](`super::ContextPrintingSink`), the searcher this sink is used with must have passthru
+/// enabled.
+#[allow(clippy::module_name_repetitions)]
+pub(crate) struct PluginSink<'p, M: Matcher, P: Printer> {
+    matcher: M,
+    plugin: &'p LuaPlugin,
+    printer: P,
+}
+
+impl<'p, M: Matcher, P: Printer> PluginSink<'p, M, P> {
+    /// Make a new `PluginSink`. The given matcher must be the same one that is searched with, so that the plugin can be
+    /// told where each line matched.
+    pub(crate) fn new(matcher: M, plugin: &'p LuaPlugin, printer: P) -> Self {
+        Self {
+            matcher,
+            plugin,
+            printer,
+        }
+    }
+
+    fn handle_line(
+        &self,
+        line: &[u8],
+        line_number: Option<u64>,
+        matched: bool,
+    ) -> Result<bool, Error> {
+        let content = lines::without_terminator(line);
+        let mut match_ranges = Vec::new();
+        if matched {
+            self.matcher
+                .find_iter(content, |found| {
+                    match_ranges.push(found.start()..found.end());
+                    true
+                })
+                .map_err(|err| Error::SearchError(err.to_string()))?;
+        }
+
+        let info = LineInfo {
+            line_number,
+            matched,
+            matches: &match_ranges,
+        };
+        let verdict = self
+            .plugin
+            .handle_line(content, info)
+            .map_err(Error::PluginFailed)?;
+
+        let print_res = match verdict {
+            Verdict::Keep if matched => self.printer.colored_print(Fg(LightRed), line),
+            Verdict::Keep => self.printer.print(line),
+            Verdict::Drop => Ok(()),
+            Verdict::Transform { line: text, color } => {
+                // The plugin is only given the line's content, so its terminator must be kept from the original
+                let text = match text {
+                    Some(text) => text + &String::from_utf8_lossy(&line[content.len()..]),
+                    None => String::from_utf8_lossy(line).into_owned(),
+                };
+
+                match color {
+                    Some(color) => self.printer.colored_print(Fg(color), text),
+                    None if matched => self.printer.colored_print(Fg(LightRed), text),
+                    None => self.printer.print(text),
+                }
+            }
+        };
+
+        get_sink_result_for_print_result(print_res)
+    }
+}
+
+impl<M: Matcher, P: Printer> Sink for PluginSink<'_, M, P> {
+    type Error = Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        validate_searcher(searcher);
+
+        for (i, line) in sink_match.lines().enumerate() {
+            let line_number = sink_match.line_number().map(|first| first + i as u64);
+            if !self.handle_line(line, line_number, true)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn context(
+        &mut self,
+        searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        validate_searcher(searcher);
+
+        self.handle_line(context.bytes(), context.line_number(), false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+    use grep::regex::RegexMatcher;
+    use grep::searcher::SearcherBuilder;
+
+    const PLUGIN: &str = r#"
+        function on_line(line, info)
+            if line:find("secret") then
+                return false
+            elseif info.matched then
+                return line:sub(1, info.matches[1].last) .. "!"
+            elseif info.line_number == 2 then
+                return { color = "blue" }
+            end
+        end
+    "#;
+
+    #[test]
+    fn test_passes_lines_through_plugin() {
+        let plugin = LuaPlugin::load(PLUGIN, "test").expect("failed to load plugin");
+        let matcher = RegexMatcher::new("ERROR").expect("failed to make matcher");
+        let mock_printer = MockPrinter::default();
+        let sink = PluginSink::new(&matcher, &plugin, &mock_printer);
+        let input = "INFO: hello\nINFO: hi\nsecret ERROR\nERROR: oh no\r\nINFO: bye\n";
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(&matcher, input.as_bytes(), sink)
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &["INFO: hello\n".to_string(), "INFO: bye\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["INFO: hi\n".to_string(), "ERROR!\r\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use super::Error;
+use crate::Position;
+use grep::searcher::{Searcher, Sink, SinkContext, SinkFinish, SinkMatch};
+
+/// `PositionTrackingSink` forwards every result it is given to another [`Sink`], noting the [`Position`] of any that
+/// the wrapped [`Sink`] fails to handle, so that the error can be reported along with where in the input it happened.
+pub(crate) struct PositionTrackingSink<S: Sink<Error = Error>> {
+    sink: S,
+    position: Option<Position>,
+}
+
+impl<S: Sink<Error = Error>> PositionTrackingSink<S> {
+    pub(crate) fn new(sink: S) -> Self {
+        Self {
+            sink,
+            position: None,
+        }
+    }
+
+    /// Get the position of the start of the result the wrapped sink failed to handle, if it failed on one.
+    pub(crate) fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// Note the position of the given result while it is handled, so that it is only kept if handling it fails.
+    fn track<F: FnOnce(&mut S) -> Result<bool, Error>>(
+        &mut self,
+        position: Position,
+        handle: F,
+    ) -> Result<bool, Error> {
+        let res = handle(&mut self.sink);
+        self.position = res.is_err().then_some(position);
+
+        res
+    }
+}
+
+impl<S: Sink<Error = Error>> Sink for PositionTrackingSink<S> {
+    type Error = Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        let position = Position {
+            line_number: sink_match.line_number(),
+            byte_offset: sink_match.absolute_byte_offset(),
+        };
+
+        self.track(position, |sink| sink.matched(searcher, sink_match))
+    }
+
+    fn context(
+        &mut self,
+        searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        let position = Position {
+            line_number: context.line_number(),
+            byte_offset: context.absolute_byte_offset(),
+        };
+
+        self.track(position, |sink| sink.context(searcher, context))
+    }
+
+    fn context_break(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.sink.context_break(searcher)
+    }
+
+    fn binary_data(
+        &mut self,
+        searcher: &Searcher,
+        binary_byte_offset: u64,
+    ) -> Result<bool, Self::Error> {
+        self.sink.binary_data(searcher, binary_byte_offset)
+    }
+
+    fn begin(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.sink.begin(searcher)
+    }
+
+    fn finish(&mut self, searcher: &Searcher, finish: &SinkFinish) -> Result<(), Self::Error> {
+        self.sink.finish(searcher, finish)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grep::regex::RegexMatcher;
+    use grep::searcher::{SearcherBuilder, SinkError};
+    use test_case::test_case;
+
+    /// `MatchFailingSink` fails to handle every match it is given.
+    struct MatchFailingSink;
+
+    impl Sink for MatchFailingSink {
+        type Error = Error;
+
+        fn matched(
+            &mut self,
+            _searcher: &Searcher,
+            _sink_match: &SinkMatch,
+        ) -> Result<bool, Error> {
+            Err(Error::error_message("failed"))
+        }
+    }
+
+    #[test_case("world", Some(Position { line_number: Some(2), byte_offset: 6 }); "failing")]
+    #[test_case("nothing", None; "not failing")]
+    fn test_tracks_position_of_failure(pattern: &str, expected: Option<Position>) {
+        let matcher = RegexMatcher::new(pattern).expect("regexp doesn't compile");
+        let mut sink = PositionTrackingSink::new(MatchFailingSink);
+        let res = SearcherBuilder::new().passthru(true).build().search_slice(
+            matcher,
+            b"hello\nworld\nagain\n",
+            &mut sink,
+        );
+
+        assert_eq!(expected.is_some(), res.is_err());
+        assert_eq!(expected, sink.position());
+    }
+}
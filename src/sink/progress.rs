@@ -0,0 +1,203 @@
+use grep::searcher::{LineIter, Searcher, Sink, SinkContext, SinkFinish, SinkMatch};
+
+/// `Progress` is a snapshot of how far a scan has gotten, as given to the callback of a [`ProgressSink`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Progress {
+    /// The number of bytes of input that have been scanned, up to the end of the last line seen.
+    pub bytes_scanned: u64,
+    /// The number of lines that have been seen.
+    pub lines_seen: u64,
+    /// The number of lines seen that matched.
+    pub lines_matched: u64,
+}
+
+/// `ProgressInterval` sets how often a [`ProgressSink`] reports its progress.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProgressInterval {
+    /// Report progress each time this many more lines have been seen.
+    Lines(u64),
+    /// Report progress each time this many more bytes have been scanned. As progress is only known a line at a time, a
+    /// report may come somewhat after this many bytes.
+    Bytes(u64),
+}
+
+/// `ProgressSink` wraps another [`Sink`], calling the given function with the [`Progress`] of the scan at every
+/// [`ProgressInterval`], and once more when the scan finishes. All results are forwarded to the wrapped [`Sink`]
+/// unchanged. This allows progress to be shown while scanning large inputs, without wrapping the reader being scanned.
+///
+/// Like [`StatsSink`](`super::StatsSink`), lines are only counted if they are given to the sink, so the searcher this
+/// sink is used with should have passthru enabled for every line to be counted.
+///
+/// # Examples
+///
+/// ```
+/// use grep::regex::RegexMatcher;
+/// use hline::print::StdoutPrinter;
+/// use hline::sink::{ContextPrintingSink, ProgressInterval, ProgressSink};
+///
+/// let mut reports = Vec::new();
+/// let matcher = RegexMatcher::new("world").expect("regexp doesn't compile");
+/// let sink = ProgressSink::new(
+///     ContextPrintingSink::new(StdoutPrinter::new()),
+///     ProgressInterval::Lines(1),
+///     |progress| reports.push(progress.lines_seen),
+/// );
+/// hline::scan_matcher_to_sink("hello\nworld\n".as_bytes(), matcher, sink).expect("search failed");
+///
+/// assert_eq!(vec![1, 2, 2], reports);
+/// ```
+#[allow(clippy::module_name_repetitions)]
+pub struct ProgressSink<S: Sink, F: FnMut(&Progress)> {
+    sink: S,
+    interval: ProgressInterval,
+    callback: F,
+    progress: Progress,
+    next_report_at: u64,
+}
+
+impl<S: Sink, F: FnMut(&Progress)> ProgressSink<S, F> {
+    /// Make a new `ProgressSink` that forwards all results to the given [`Sink`], calling `callback` with the progress
+    /// of the scan at every `interval`. An interval of zero is treated as an interval of one.
+    #[must_use]
+    pub fn new(sink: S, interval: ProgressInterval, callback: F) -> Self {
+        let mut progress_sink = Self {
+            sink,
+            interval,
+            callback,
+            progress: Progress::default(),
+            next_report_at: 0,
+        };
+        progress_sink.next_report_at = progress_sink.interval_len();
+
+        progress_sink
+    }
+
+    /// Get the progress made so far.
+    #[must_use]
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// Get the wrapped [`Sink`] back.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+
+    fn interval_len(&self) -> u64 {
+        match self.interval {
+            ProgressInterval::Lines(len) | ProgressInterval::Bytes(len) => len.max(1),
+        }
+    }
+
+    /// Record that the given lines were seen, reporting the progress made if another interval has passed.
+    fn advance(&mut self, num_lines: u64, matched: bool, end_offset: u64) {
+        self.progress.lines_seen += num_lines;
+        if matched {
+            self.progress.lines_matched += num_lines;
+        }
+        self.progress.bytes_scanned = end_offset;
+
+        let made = match self.interval {
+            ProgressInterval::Lines(_) => self.progress.lines_seen,
+            ProgressInterval::Bytes(_) => self.progress.bytes_scanned,
+        };
+        if made >= self.next_report_at {
+            let interval_len = self.interval_len();
+            // Only report once, even if several intervals passed at once
+            self.next_report_at = (made / interval_len + 1) * interval_len;
+            (self.callback)(&self.progress);
+        }
+    }
+}
+
+impl<S: Sink, F: FnMut(&Progress)> Sink for ProgressSink<S, F> {
+    type Error = S::Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        let end_offset = sink_match.absolute_byte_offset() + sink_match.bytes().len() as u64;
+        self.advance(sink_match.lines().count() as u64, true, end_offset);
+
+        self.sink.matched(searcher, sink_match)
+    }
+
+    fn context(
+        &mut self,
+        searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        let line_terminator = searcher.line_terminator().as_byte();
+        let num_lines = LineIter::new(line_terminator, context.bytes()).count() as u64;
+        let end_offset = context.absolute_byte_offset() + context.bytes().len() as u64;
+        self.advance(num_lines, false, end_offset);
+
+        self.sink.context(searcher, context)
+    }
+
+    fn context_break(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.sink.context_break(searcher)
+    }
+
+    fn binary_data(
+        &mut self,
+        searcher: &Searcher,
+        binary_byte_offset: u64,
+    ) -> Result<bool, Self::Error> {
+        self.sink.binary_data(searcher, binary_byte_offset)
+    }
+
+    fn begin(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.sink.begin(searcher)
+    }
+
+    fn finish(&mut self, searcher: &Searcher, finish: &SinkFinish) -> Result<(), Self::Error> {
+        self.progress.bytes_scanned = finish.byte_count();
+        (self.callback)(&self.progress);
+
+        self.sink.finish(searcher, finish)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::ContextPrintingSink;
+    use crate::testutil::mock_print::MockPrinter;
+    use grep::regex::RegexMatcher;
+    use grep::searcher::SearcherBuilder;
+    use test_case::test_case;
+
+    #[test_case(ProgressInterval::Lines(2), &[(12, 2, 1), (27, 4, 2), (27, 4, 2)]; "lines")]
+    #[test_case(ProgressInterval::Bytes(10), &[(12, 2, 1), (23, 3, 1), (27, 4, 2)]; "bytes")]
+    #[test_case(ProgressInterval::Lines(0), &[(6, 1, 0), (12, 2, 1), (23, 3, 1), (27, 4, 2), (27, 4, 2)]; "zero")]
+    fn test_reports_progress_at_intervals(
+        interval: ProgressInterval,
+        expected: &[(u64, u64, u64)],
+    ) {
+        let matcher = RegexMatcher::new("fox|dog").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let mut reports = Vec::new();
+        let sink = ProgressSink::new(
+            ContextPrintingSink::new(&mock_printer),
+            interval,
+            |progress| {
+                reports.push((
+                    progress.bytes_scanned,
+                    progress.lines_seen,
+                    progress.lines_matched,
+                ));
+            },
+        );
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"quick\nfox 1\nbrown lazy\ndog\n", sink)
+            .expect("search failed");
+
+        assert_eq!(expected, reports.as_slice());
+    }
+}
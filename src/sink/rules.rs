@@ -0,0 +1,129 @@
+use super::{get_sink_result_for_print_result, validate_searcher, Error, InvalidUtf8};
+use crate::lines;
+use crate::print::Printer;
+use crate::rules::{Action, Rules};
+use grep::searcher::{Searcher, Sink, SinkContext, SinkMatch};
+
+/// The ASCII bell character, which will make the terminal ring its bell when printed.
+const BELL: &[u8] = b"\x07";
+
+/// `RulesSink` prints every line it is given to a [`Printer`], handling each matching line as directed by the first
+/// of the given [`Rules`] that matches it.
+///
+/// # Panics
+///
+/// Like [`ContextPrintingSink`](`super::ContextPrintingSink`), the searcher this sink is used with must have passthru
+/// enabled.
+#[allow(clippy::module_name_repetitions)]
+pub(crate) struct RulesSink<'r, P: Printer> {
+    rules: &'r Rules,
+    printer: P,
+}
+
+impl<'r, P: Printer> RulesSink<'r, P> {
+    pub(crate) fn new(rules: &'r Rules, printer: P) -> Self {
+        Self { rules, printer }
+    }
+
+    fn handle_line(&self, line: &[u8]) -> Result<bool, Error> {
+        let decoded_line = InvalidUtf8::default().decode(line);
+        let Some(rule) = self.rules.rule_for(lines::without_terminator(line)) else {
+            return get_sink_result_for_print_result(self.printer.print(decoded_line));
+        };
+
+        let print_res = match rule.action() {
+            Action::Highlight | Action::Stop => {
+                self.printer.colored_print(rule.style(), decoded_line)
+            }
+            Action::Suppress => Ok(()),
+            Action::Bell => self
+                .printer
+                .print(BELL)
+                .and_then(|()| self.printer.colored_print(rule.style(), decoded_line)),
+        };
+
+        let keep_going = get_sink_result_for_print_result(print_res)?;
+
+        Ok(keep_going && rule.action() != Action::Stop)
+    }
+}
+
+impl<P: Printer> Sink for RulesSink<'_, P> {
+    type Error = Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        validate_searcher(searcher);
+
+        for line in sink_match.lines() {
+            if !self.handle_line(line)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn context(
+        &mut self,
+        searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        validate_searcher(searcher);
+
+        let data = InvalidUtf8::default().decode(context.bytes());
+        let print_res = self.printer.print(data);
+
+        get_sink_result_for_print_result(print_res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+    use grep::searcher::SearcherBuilder;
+
+    const RULES: &str = r#"
+        [[rule]]
+        pattern = "DEBUG"
+        action = "suppress"
+
+        [[rule]]
+        pattern = "FATAL"
+        action = "stop"
+
+        [[rule]]
+        pattern = "ERROR"
+        action = "bell"
+    "#;
+
+    #[test]
+    fn test_performs_rule_actions() {
+        let rules = Rules::from_toml(RULES).expect("failed to load rules");
+        let mock_printer = MockPrinter::default();
+        let sink = RulesSink::new(&rules, &mock_printer);
+        let input = "INFO: hello\nDEBUG: hello\nERROR: oh no\nFATAL: goodbye\nINFO: hello again\n";
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(&rules, input.as_bytes(), sink)
+            .expect("search failed");
+
+        testutil::assert_slices_eq!(
+            &[
+                "INFO: hello\n".to_string(),
+                String::from_utf8_lossy(BELL).to_string()
+            ],
+            &mock_printer.uncolored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["ERROR: oh no\n".to_string(), "FATAL: goodbye\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+    }
+}
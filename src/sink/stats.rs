@@ -0,0 +1,133 @@
+use grep::searcher::{LineIter, Searcher, Sink, SinkContext, SinkFinish, SinkMatch};
+use std::time::{Duration, Instant};
+
+/// `ScanStats` holds statistics about a completed scan, as collected by a [`StatsSink`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+#[allow(clippy::module_name_repetitions)]
+pub struct ScanStats {
+    /// The number of lines that were scanned.
+    pub lines_scanned: u64,
+    /// The number of lines that matched.
+    pub lines_matched: u64,
+    /// The number of bytes of input that were processed.
+    pub bytes_processed: u64,
+    /// The time taken by the scan.
+    pub elapsed: Duration,
+}
+
+/// `StatsSink` wraps another [`Sink`], and collects [`ScanStats`] about the results that pass through it. All results
+/// are forwarded to the wrapped [`Sink`] unchanged.
+///
+/// Lines are only counted if they are given to the sink, so the searcher this sink is used with should have passthru
+/// enabled for every line to be counted.
+#[allow(clippy::module_name_repetitions)]
+pub struct StatsSink<S: Sink> {
+    sink: S,
+    stats: ScanStats,
+    started_at: Option<Instant>,
+}
+
+impl<S: Sink> StatsSink<S> {
+    /// Make a new `StatsSink` that forwards all results to the given [`Sink`].
+    #[must_use]
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            stats: ScanStats::default(),
+            started_at: None,
+        }
+    }
+
+    /// Get the statistics collected so far. The number of bytes processed and the time taken are only known once the
+    /// search has finished.
+    #[must_use]
+    pub fn stats(&self) -> ScanStats {
+        self.stats
+    }
+
+    /// Get the wrapped [`Sink`] back.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S: Sink> Sink for StatsSink<S> {
+    type Error = S::Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        let num_lines = sink_match.lines().count() as u64;
+        self.stats.lines_scanned += num_lines;
+        self.stats.lines_matched += num_lines;
+
+        self.sink.matched(searcher, sink_match)
+    }
+
+    fn context(
+        &mut self,
+        searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        let line_terminator = searcher.line_terminator().as_byte();
+        self.stats.lines_scanned += LineIter::new(line_terminator, context.bytes()).count() as u64;
+
+        self.sink.context(searcher, context)
+    }
+
+    fn context_break(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.sink.context_break(searcher)
+    }
+
+    fn binary_data(
+        &mut self,
+        searcher: &Searcher,
+        binary_byte_offset: u64,
+    ) -> Result<bool, Self::Error> {
+        self.sink.binary_data(searcher, binary_byte_offset)
+    }
+
+    fn begin(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.started_at = Some(Instant::now());
+
+        self.sink.begin(searcher)
+    }
+
+    fn finish(&mut self, searcher: &Searcher, finish: &SinkFinish) -> Result<(), Self::Error> {
+        self.stats.bytes_processed = finish.byte_count();
+        if let Some(started_at) = self.started_at {
+            self.stats.elapsed = started_at.elapsed();
+        }
+
+        self.sink.finish(searcher, finish)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::ContextPrintingSink;
+    use crate::testutil::mock_print::MockPrinter;
+    use grep::regex::RegexMatcher;
+    use grep::searcher::SearcherBuilder;
+
+    #[test]
+    fn test_collects_stats() {
+        let matcher = RegexMatcher::new("fox|dog").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let mut sink = StatsSink::new(ContextPrintingSink::new(&mock_printer));
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"quick\nbrown fox\nlazy\ndog\n", &mut sink)
+            .expect("search failed");
+
+        let stats = sink.stats();
+        assert_eq!(4, stats.lines_scanned);
+        assert_eq!(2, stats.lines_matched);
+        assert_eq!(25, stats.bytes_processed);
+    }
+}
@@ -0,0 +1,140 @@
+use grep::searcher::{Searcher, Sink, SinkContext, SinkFinish, SinkMatch};
+
+/// `TeeSink` forwards every result it is given to two [`Sink`]s, in order. This allows a [`Sink`] of your own, such as
+/// one that stores matches in a database, to be used alongside one that prints, such as
+/// [`ContextPrintingSink`](`super::ContextPrintingSink`).
+///
+/// The search continues only for as long as both sinks ask for it to. If the first sink fails, the second is not given
+/// the result that it failed on.
+///
+/// # Examples
+///
+/// ```
+/// use hline::print::StdoutPrinter;
+/// use hline::sink::{CallbackSink, ContextPrintingSink, LineEvent, TeeSink};
+///
+/// let mut matching_lines = Vec::new();
+/// let collecting_sink = CallbackSink::new(|event: &LineEvent| {
+///     if event.matched {
+///         matching_lines.push(event.text().to_string());
+///     }
+/// });
+///
+/// let sink = TeeSink::new(ContextPrintingSink::new(StdoutPrinter::new()), collecting_sink);
+/// hline::scan_pattern_to_sink("hello\nworld\n".as_bytes(), "world", sink).expect("search failed");
+///
+/// assert_eq!(vec!["world"], matching_lines);
+/// ```
+#[allow(clippy::module_name_repetitions)]
+pub struct TeeSink<A: Sink, B: Sink<Error = A::Error>> {
+    first: A,
+    second: B,
+}
+
+impl<A: Sink, B: Sink<Error = A::Error>> TeeSink<A, B> {
+    /// Make a new `TeeSink` that forwards all results to both of the given [`Sink`]s.
+    #[must_use]
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// Get the wrapped [`Sink`]s back.
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: Sink, B: Sink<Error = A::Error>> Sink for TeeSink<A, B> {
+    type Error = A::Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        let first_res = self.first.matched(searcher, sink_match)?;
+        let second_res = self.second.matched(searcher, sink_match)?;
+
+        Ok(first_res && second_res)
+    }
+
+    fn context(
+        &mut self,
+        searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        let first_res = self.first.context(searcher, context)?;
+        let second_res = self.second.context(searcher, context)?;
+
+        Ok(first_res && second_res)
+    }
+
+    fn context_break(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        let first_res = self.first.context_break(searcher)?;
+        let second_res = self.second.context_break(searcher)?;
+
+        Ok(first_res && second_res)
+    }
+
+    fn binary_data(
+        &mut self,
+        searcher: &Searcher,
+        binary_byte_offset: u64,
+    ) -> Result<bool, Self::Error> {
+        let first_res = self.first.binary_data(searcher, binary_byte_offset)?;
+        let second_res = self.second.binary_data(searcher, binary_byte_offset)?;
+
+        Ok(first_res && second_res)
+    }
+
+    fn begin(&mut self, searcher: &Searcher) -> Result<bool, Self::Error> {
+        let first_res = self.first.begin(searcher)?;
+        let second_res = self.second.begin(searcher)?;
+
+        Ok(first_res && second_res)
+    }
+
+    fn finish(&mut self, searcher: &Searcher, finish: &SinkFinish) -> Result<(), Self::Error> {
+        self.first.finish(searcher, finish)?;
+        self.second.finish(searcher, finish)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::{CallbackSink, ContextPrintingSink, LineEvent};
+    use crate::testutil;
+    use crate::testutil::mock_print::MockPrinter;
+    use grep::regex::RegexMatcher;
+    use grep::searcher::SearcherBuilder;
+
+    #[test]
+    fn test_forwards_to_both_sinks() {
+        let matcher = RegexMatcher::new("fox").expect("regexp doesn't compile");
+        let mock_printer = MockPrinter::default();
+        let mut matching_lines = Vec::new();
+        let collecting_sink = CallbackSink::new(|event: &LineEvent| {
+            if event.matched {
+                matching_lines.push(event.line_number);
+            }
+        });
+
+        let sink = TeeSink::new(ContextPrintingSink::new(&mock_printer), collecting_sink);
+        SearcherBuilder::new()
+            .passthru(true)
+            .build()
+            .search_slice(matcher, b"quick\nbrown fox\nlazy dog\n", sink)
+            .expect("search failed");
+
+        assert_eq!(vec![2], matching_lines);
+        testutil::assert_slices_eq!(
+            &["brown fox\n".to_string()],
+            &mock_printer.colored_messages.borrow()
+        );
+        testutil::assert_slices_eq!(
+            &["quick\n".to_string(), "lazy dog\n".to_string()],
+            &mock_printer.uncolored_messages.borrow()
+        );
+    }
+}
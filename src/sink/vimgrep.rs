@@ -0,0 +1,109 @@
+use super::{get_sink_result_for_print_result, Error};
+use crate::lines;
+use crate::print;
+use grep::matcher::Matcher;
+use grep::searcher::{Searcher, Sink, SinkError, SinkMatch};
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// `VimgrepSink` writes a line of the form `file:line:column:text` for each match within each matching line, as
+/// `vim`'s `:vimgrep` does, so that the results can be loaded into the quickfix list of an editor. The column is the
+/// byte offset of the start of the match within its line, starting from 1, and the text is the whole line, without its
+/// line terminator. Lines that do not match are not written at all.
+///
+/// The searcher used with this sink must have line numbers enabled.
+pub(crate) struct VimgrepSink<M: Matcher, W: Write> {
+    matcher: M,
+    source_name: String,
+    writer: W,
+}
+
+impl<M: Matcher, W: Write> VimgrepSink<M, W> {
+    /// Make a new `VimgrepSink`. `matcher` must be the same matcher used for the search, and `source_name` is the name
+    /// of the input that each line will start with.
+    pub(crate) fn new(matcher: M, source_name: &str, writer: W) -> Self {
+        Self {
+            matcher,
+            source_name: source_name.to_string(),
+            writer,
+        }
+    }
+
+    /// Write an entry for each match within the given line, which must not have its line terminator.
+    fn write_matches(&mut self, line_number: u64, line: &[u8]) -> Result<bool, Error> {
+        let mut columns = Vec::new();
+        self.matcher
+            .find_iter(line, |found_match| {
+                columns.push(found_match.start() + 1);
+                true
+            })
+            .map_err(Error::error_message)?;
+
+        let text = String::from_utf8_lossy(line);
+        let mut entries = String::new();
+        for column in columns {
+            // Writing to a String cannot fail
+            writeln!(
+                entries,
+                "{}:{line_number}:{column}:{text}",
+                self.source_name
+            )
+            .unwrap();
+        }
+
+        let write_res = self
+            .writer
+            .write_all(entries.as_bytes())
+            .map_err(print::Error::from);
+
+        get_sink_result_for_print_result(write_res)
+    }
+}
+
+impl<M: Matcher, W: Write> Sink for VimgrepSink<M, W> {
+    type Error = Error;
+
+    fn matched(
+        &mut self,
+        searcher: &Searcher,
+        sink_match: &SinkMatch,
+    ) -> Result<bool, Self::Error> {
+        let line_terminator = searcher.line_terminator().as_byte();
+        let first_line_number = sink_match
+            .line_number()
+            .expect("line numbers are not enabled on the given searcher");
+
+        for (line_number, line) in (first_line_number..).zip(sink_match.lines()) {
+            let line = lines::without_line_terminator(line, line_terminator);
+            if !self.write_matches(line_number, line)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grep::regex::RegexMatcher;
+    use grep::searcher::SearcherBuilder;
+
+    #[test]
+    fn test_writes_each_match() {
+        let matcher = RegexMatcher::new("fo+").expect("regexp doesn't compile");
+        let mut output = Vec::new();
+        let sink = VimgrepSink::new(matcher.clone(), "my.log", &mut output);
+        SearcherBuilder::new()
+            .line_number(true)
+            .build()
+            .search_slice(matcher, b"hello\nfooo, bar: fo\r\nworld\n", sink)
+            .expect("search failed");
+
+        assert_eq!(
+            "my.log:2:1:fooo, bar: fo\nmy.log:2:12:fooo, bar: fo\n",
+            String::from_utf8(output).expect("not utf-8")
+        );
+    }
+}
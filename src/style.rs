@@ -0,0 +1,398 @@
+//! `style` provides text styles, such as bold text, as ANSI escape sequences, as well as the [`Highlight`] styles that
+//! messages can be printed in.
+use crate::color::{AnsiValue, Bg, Color, Fg, Reset as ColorReset};
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The names of the attributes that can be parsed into an [`Attribute`], in the order they should be listed.
+pub const ATTRIBUTE_NAMES: &[&str] = &["bold", "dim", "italic", "underline", "reverse"];
+
+/// `ParseAttributeError` is returned when a string does not name a valid attribute.
+#[derive(Error, Debug)]
+#[error("\"{0}\" is not a valid style; expected one of bold, dim, italic, underline, or reverse")]
+pub struct ParseAttributeError(String);
+
+/// `ParseStyleError` is returned when a string is not a valid [`Style`].
+#[derive(Error, Debug)]
+#[error(
+    "\"{0}\" is not a valid style; expected attributes and colors, such as \"bold red\" or \"black on yellow\""
+)]
+pub struct ParseStyleError(String);
+
+/// Bold text.
+#[derive(Copy, Clone, Debug)]
+pub struct Bold;
+
+impl Display for Bold {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[1m")
+    }
+}
+
+/// Bold text can also be used as a [`Highlight`], which undoes only the boldness when it ends.
+impl Highlight for Bold {
+    fn write_start(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+
+    fn write_end(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[22m")
+    }
+}
+
+/// `Attribute` is a text attribute, such as bold or underlined text, that can be applied alongside colors.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Attribute {
+    /// Bold text.
+    Bold,
+    /// Faint text.
+    Dim,
+    /// Italic text.
+    Italic,
+    /// Underlined text.
+    Underline,
+    /// Swap the foreground and background colors.
+    Reverse,
+}
+
+impl Attribute {
+    /// All of the attributes, in the order of [`ATTRIBUTE_NAMES`].
+    const ALL: [Attribute; 5] = [
+        Self::Bold,
+        Self::Dim,
+        Self::Italic,
+        Self::Underline,
+        Self::Reverse,
+    ];
+
+    /// The SGR parameters that turn this attribute on and off, respectively.
+    fn sgr_parameters(self) -> (u8, u8) {
+        match self {
+            // Bold and dim text share an "off" parameter, which is "normal intensity"
+            Self::Bold => (1, 22),
+            Self::Dim => (2, 22),
+            Self::Italic => (3, 23),
+            Self::Underline => (4, 24),
+            Self::Reverse => (7, 27),
+        }
+    }
+
+    /// The bit used to represent this attribute in [`Attributes`].
+    fn bit(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+impl FromStr for Attribute {
+    type Err = ParseAttributeError;
+
+    /// Parse an attribute from its name, as listed in [`ATTRIBUTE_NAMES`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase();
+        ATTRIBUTE_NAMES
+            .iter()
+            .position(|&name| name == normalized)
+            .map(|idx| Self::ALL[idx])
+            .ok_or_else(|| ParseAttributeError(s.to_string()))
+    }
+}
+
+impl Highlight for Attribute {
+    fn write_start(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b[{}m", self.sgr_parameters().0)
+    }
+
+    fn write_end(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b[{}m", self.sgr_parameters().1)
+    }
+}
+
+/// `Attributes` is a set of [`Attribute`]s, which are all applied when it is used as a [`Highlight`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Attributes(u8);
+
+impl Attributes {
+    /// The empty set of attributes.
+    pub const NONE: Attributes = Attributes(0);
+
+    /// Get these attributes, with the given attribute added.
+    #[must_use]
+    pub fn with(self, attribute: Attribute) -> Self {
+        Self(self.0 | attribute.bit())
+    }
+
+    /// Check whether the given attribute is in this set.
+    #[must_use]
+    pub fn contains(self, attribute: Attribute) -> bool {
+        self.0 & attribute.bit() != 0
+    }
+
+    /// Check whether there are no attributes in this set.
+    #[must_use]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    fn iter(self) -> impl Iterator<Item = Attribute> {
+        Attribute::ALL
+            .into_iter()
+            .filter(move |&attribute| self.contains(attribute))
+    }
+}
+
+impl FromIterator<Attribute> for Attributes {
+    fn from_iter<I: IntoIterator<Item = Attribute>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::NONE, Self::with)
+    }
+}
+
+impl Highlight for Attributes {
+    fn write_start(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.iter()
+            .try_for_each(|attribute| attribute.write_start(f))
+    }
+
+    fn write_end(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.iter().try_for_each(|attribute| attribute.write_end(f))
+    }
+}
+
+/// `Style` is a [`Highlight`] made of an optional foreground color, an optional background color, and any number of
+/// [`Attributes`]. Unlike a tuple of highlights, its parts can be chosen at runtime.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Style {
+    /// The foreground color, if any.
+    pub fg: Option<AnsiValue>,
+    /// The background color, if any.
+    pub bg: Option<AnsiValue>,
+    /// The text attributes, such as bold or underlined text.
+    pub attributes: Attributes,
+}
+
+impl Style {
+    /// Make a new `Style` with only the given foreground color.
+    #[must_use]
+    pub fn fg(color: AnsiValue) -> Self {
+        Self {
+            fg: Some(color),
+            ..Self::default()
+        }
+    }
+}
+
+impl FromStr for Style {
+    type Err = ParseStyleError;
+
+    /// Parse a style from a list of words, separated by spaces or commas. Each word is either an attribute, as listed
+    /// in [`ATTRIBUTE_NAMES`], or a color, as parsed by [`AnsiValue`], which sets the foreground color. A color
+    /// preceded by `on` sets the background color instead. For instance, `bold red` or `black on yellow`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseStyleError(s.to_string());
+        let mut words = s
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty())
+            .peekable();
+        if words.peek().is_none() {
+            return Err(err());
+        }
+
+        let mut style = Self::default();
+        while let Some(word) = words.next() {
+            if word.eq_ignore_ascii_case("on") {
+                let bg = words.next().and_then(|color| color.parse().ok());
+                style.bg = Some(bg.ok_or_else(err)?);
+            } else if let Ok(attribute) = word.parse::<Attribute>() {
+                style.attributes = style.attributes.with(attribute);
+            } else {
+                style.fg = Some(word.parse().map_err(|_| err())?);
+            }
+        }
+
+        Ok(style)
+    }
+}
+
+impl Highlight for Style {
+    fn write_start(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.attributes.write_start(f)?;
+        self.fg.map(Fg).write_start(f)?;
+        self.bg.map(Bg).write_start(f)
+    }
+
+    fn write_end(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fg.map(Fg).write_end(f)?;
+        self.bg.map(Bg).write_end(f)?;
+        self.attributes.write_end(f)
+    }
+}
+
+/// Reset all styles and colors to the terminal's defaults.
+#[derive(Copy, Clone, Debug)]
+pub struct Reset;
+
+impl Display for Reset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[m")
+    }
+}
+
+/// `Highlight` is a style that a message can be highlighted in, such as a foreground color, a background color, or a
+/// combination of the two, given as a tuple (e.g. `(Fg(Black), Bg(Yellow))`).
+pub trait Highlight: Debug {
+    /// Write the escape sequences that apply this highlight.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying write fails.
+    fn write_start(&self, f: &mut Formatter<'_>) -> fmt::Result;
+
+    /// Write the escape sequences that undo this highlight, without affecting any other styles.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying write fails.
+    fn write_end(&self, f: &mut Formatter<'_>) -> fmt::Result;
+}
+
+impl<C: Color> Highlight for Fg<C> {
+    fn write_start(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.write_fg(f)
+    }
+
+    fn write_end(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        ColorReset.write_fg(f)
+    }
+}
+
+impl<C: Color> Highlight for Bg<C> {
+    fn write_start(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.write_bg(f)
+    }
+
+    fn write_end(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        ColorReset.write_bg(f)
+    }
+}
+
+impl<A: Highlight, B: Highlight> Highlight for (A, B) {
+    fn write_start(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.write_start(f)?;
+        self.1.write_start(f)
+    }
+
+    fn write_end(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.write_end(f)?;
+        self.1.write_end(f)
+    }
+}
+
+/// An absent highlight does nothing, which allows for highlights that are only decided at runtime.
+impl<H: Highlight> Highlight for Option<H> {
+    fn write_start(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.as_ref()
+            .map_or(Ok(()), |highlight| highlight.write_start(f))
+    }
+
+    fn write_end(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.as_ref()
+            .map_or(Ok(()), |highlight| highlight.write_end(f))
+    }
+}
+
+impl<H: Highlight + ?Sized> Highlight for &H {
+    fn write_start(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        (*self).write_start(f)
+    }
+
+    fn write_end(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        (*self).write_end(f)
+    }
+}
+
+/// `Start` applies the given [`Highlight`] when displayed.
+pub(crate) struct Start<'a, H: Highlight>(pub(crate) &'a H);
+
+impl<H: Highlight> Display for Start<'_, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.write_start(f)
+    }
+}
+
+/// `End` undoes the given [`Highlight`] when displayed.
+pub(crate) struct End<'a, H: Highlight>(pub(crate) &'a H);
+
+impl<H: Highlight> Display for End<'_, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.write_end(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{AnsiValue, Black, Yellow};
+    use test_case::test_case;
+
+    #[test_case(&Fg(Yellow), "\x1b[38;5;3m", "\x1b[39m"; "foreground")]
+    #[test_case(&Bg(Yellow), "\x1b[48;5;3m", "\x1b[49m"; "background")]
+    #[test_case(&(Fg(Black), Bg(Yellow)), "\x1b[38;5;0m\x1b[48;5;3m", "\x1b[39m\x1b[49m"; "both")]
+    #[test_case(&(None::<Fg<AnsiValue>>, Some(Bg(Yellow))), "\x1b[48;5;3m", "\x1b[49m"; "optional")]
+    #[test_case(&Attribute::Underline, "\x1b[4m", "\x1b[24m"; "attribute")]
+    #[test_case(
+        &Attributes::NONE.with(Attribute::Reverse).with(Attribute::Bold),
+        "\x1b[1m\x1b[7m",
+        "\x1b[22m\x1b[27m";
+        "attributes"
+    )]
+    #[test_case(
+        &Style { fg: Some(AnsiValue(1)), bg: None, attributes: Attributes::NONE.with(Attribute::Bold) },
+        "\x1b[1m\x1b[38;5;1m",
+        "\x1b[39m\x1b[22m";
+        "style"
+    )]
+    fn test_highlight_sequences(highlight: &dyn Highlight, start: &str, end: &str) {
+        assert_eq!(start, Start(&highlight).to_string());
+        assert_eq!(end, End(&highlight).to_string());
+    }
+
+    #[test_case("bold", Some(Attribute::Bold); "name")]
+    #[test_case("Underline", Some(Attribute::Underline); "name with capitals")]
+    #[test_case("blinking", None; "unknown name")]
+    fn test_parse_attribute(s: &str, expected: Option<Attribute>) {
+        assert_eq!(expected, s.parse::<Attribute>().ok());
+    }
+
+    #[test_case("red", Some((Some(1), None, Attributes::NONE)); "color")]
+    #[test_case(
+        "bold, underline light-red",
+        Some((Some(9), None, Attributes::NONE.with(Attribute::Bold).with(Attribute::Underline)));
+        "attributes and color"
+    )]
+    #[test_case("black on yellow", Some((Some(0), Some(3), Attributes::NONE)); "background")]
+    #[test_case("red on", None; "missing background")]
+    #[test_case("bold reddish", None; "unknown word")]
+    #[test_case("", None; "empty")]
+    fn test_parse_style(s: &str, expected: Option<(Option<u8>, Option<u8>, Attributes)>) {
+        let style = s.parse::<Style>().ok().map(|style| {
+            (
+                style.fg.map(|color| color.0),
+                style.bg.map(|color| color.0),
+                style.attributes,
+            )
+        });
+
+        assert_eq!(expected, style);
+    }
+
+    #[test]
+    fn test_all_names_are_attributes() {
+        for name in ATTRIBUTE_NAMES {
+            assert!(
+                name.parse::<Attribute>().is_ok(),
+                "{name} is not an attribute"
+            );
+        }
+    }
+}
@@ -1,9 +1,9 @@
 #![cfg(test)]
 use crate::print;
 use crate::print::Printer;
+use crate::style::Highlight;
 use std::cell::RefCell;
-use std::fmt;
-use termion::color;
+use std::ops::Range;
 
 #[derive(Default)]
 pub(crate) struct MockPrinter {
@@ -18,9 +18,9 @@ impl MockPrinter {
     }
 }
 
-impl Printer for &MockPrinter {
-    fn print<S: fmt::Display>(&self, msg: S) -> print::Result {
-        self.uncolored_messages.borrow_mut().push(msg.to_string());
+impl Printer for MockPrinter {
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> print::Result {
+        self.uncolored_messages.borrow_mut().push(to_string(msg));
 
         if self.next_error.borrow().is_some() {
             Err(self.next_error.replace(None).unwrap())
@@ -29,14 +29,10 @@ impl Printer for &MockPrinter {
         }
     }
 
-    fn colored_print<S: fmt::Display, C: color::Color>(
-        &self,
-        _color: color::Fg<C>,
-        msg: S,
-    ) -> print::Result {
-        // Unfortunately, termion colors don't implement PartialEq, so checking for the exact color is not
+    fn colored_print<M: AsRef<[u8]>, H: Highlight>(&self, _highlight: H, msg: M) -> print::Result {
+        // Unfortunately, colors don't implement PartialEq, so checking for the exact color is not
         // feasible unless we wanted to write a wrapper, which I don't care enough to just for unit testing
-        self.colored_messages.borrow_mut().push(msg.to_string());
+        self.colored_messages.borrow_mut().push(to_string(msg));
 
         if self.next_error.borrow().is_some() {
             Err(self.next_error.replace(None).unwrap())
@@ -44,6 +40,15 @@ impl Printer for &MockPrinter {
             Ok(())
         }
     }
+
+    fn colored_print_spans<H: Highlight, G: Highlight>(
+        &self,
+        highlight: H,
+        line: &[u8],
+        _spans: &[(Range<usize>, G)],
+    ) -> print::Result {
+        self.colored_print(highlight, line)
+    }
 }
 
 /// Similar to [`MockPrinter`], except that it only implements required methods
@@ -53,8 +58,13 @@ pub(crate) struct BarebonesMockPrinter {
 }
 
 impl Printer for BarebonesMockPrinter {
-    fn print<S: fmt::Display>(&self, msg: S) -> print::Result {
-        self.messages.borrow_mut().push(msg.to_string());
+    fn print<M: AsRef<[u8]>>(&self, msg: M) -> print::Result {
+        self.messages.borrow_mut().push(to_string(msg));
         Ok(())
     }
 }
+
+/// Convert a printed message to a `String`, so that it can be compared against easily.
+fn to_string<M: AsRef<[u8]>>(msg: M) -> String {
+    String::from_utf8_lossy(msg.as_ref()).into_owned()
+}
@@ -0,0 +1,138 @@
+//! `theme` provides named sets of colors that the output can be styled with.
+//!
+//! A [`Theme`] can be looked up by name (e.g. `"solarized".parse::<Theme>()`), or built directly for a custom look.
+use crate::color::AnsiValue;
+use crate::style::{Attributes, Style};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The names of all of the built-in themes, in the order they should be listed.
+pub const THEME_NAMES: &[&str] = &["default", "solarized", "high-contrast"];
+
+/// `UnknownThemeError` is returned when a string does not name a built-in theme.
+#[derive(Error, Debug)]
+#[error("\"{0}\" is not a known theme; expected one of default, solarized, or high-contrast")]
+pub struct UnknownThemeError(String);
+
+/// `Theme` is the set of colors that output is styled with.
+#[derive(Copy, Clone, Debug)]
+pub struct Theme {
+    /// The foreground color of matching lines. If `None`, the text of matching lines keeps its usual color.
+    pub match_fg: Option<AnsiValue>,
+    /// The background color of matching lines, if any.
+    pub match_bg: Option<AnsiValue>,
+    /// The text attributes of matching lines, such as bold or underlined text.
+    pub match_attributes: Attributes,
+    /// The color of lines that did not match. If `None`, these lines keep their usual color.
+    pub context: Option<AnsiValue>,
+    /// The text attributes of lines that did not match, such as dim text.
+    pub context_attributes: Attributes,
+    /// The color of line numbers, and the grid surrounding them.
+    pub line_number: AnsiValue,
+    /// The color of headers that name the file that follows them.
+    pub header: AnsiValue,
+}
+
+impl Theme {
+    /// The theme used unless another is chosen, which highlights matches in light red.
+    pub const DEFAULT: Theme = Theme {
+        match_fg: Some(AnsiValue(9)),
+        match_bg: None,
+        match_attributes: Attributes::NONE,
+        context: None,
+        context_attributes: Attributes::NONE,
+        line_number: AnsiValue(8),
+        header: AnsiValue(5),
+    };
+
+    /// A theme using the accent colors of the Solarized palette, which dims lines that did not match.
+    pub const SOLARIZED: Theme = Theme {
+        match_fg: Some(AnsiValue(166)),
+        match_bg: None,
+        match_attributes: Attributes::NONE,
+        context: Some(AnsiValue(244)),
+        context_attributes: Attributes::NONE,
+        line_number: AnsiValue(240),
+        header: AnsiValue(33),
+    };
+
+    /// A theme that highlights matches with black text on a bright yellow background, for maximum visibility.
+    pub const HIGH_CONTRAST: Theme = Theme {
+        match_fg: Some(AnsiValue(0)),
+        match_bg: Some(AnsiValue(11)),
+        match_attributes: Attributes::NONE,
+        context: None,
+        context_attributes: Attributes::NONE,
+        line_number: AnsiValue(15),
+        header: AnsiValue(14),
+    };
+
+    /// Get the style that matching lines should be printed with.
+    #[must_use]
+    pub fn match_highlight(&self) -> Style {
+        Style {
+            fg: self.match_fg,
+            bg: self.match_bg,
+            attributes: self.match_attributes,
+        }
+    }
+
+    /// Get the style that lines that did not match should be printed with, if they should be styled at all.
+    #[must_use]
+    pub fn context_highlight(&self) -> Option<Style> {
+        if self.context.is_none() && self.context_attributes.is_empty() {
+            return None;
+        }
+
+        Some(Style {
+            fg: self.context,
+            bg: None,
+            attributes: self.context_attributes,
+        })
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl FromStr for Theme {
+    type Err = UnknownThemeError;
+
+    /// Look up a built-in theme by its name, as listed in [`THEME_NAMES`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "default" => Ok(Self::DEFAULT),
+            "solarized" => Ok(Self::SOLARIZED),
+            "high-contrast" => Ok(Self::HIGH_CONTRAST),
+            _ => Err(UnknownThemeError(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("default", Some(9); "default")]
+    #[test_case("Solarized", Some(166); "name with capitals")]
+    #[test_case("high-contrast", Some(0); "high contrast")]
+    #[test_case("neon", None; "unknown name")]
+    fn test_parse_theme(s: &str, expected_match_fg: Option<u8>) {
+        let theme = s.parse::<Theme>().ok();
+        assert_eq!(
+            expected_match_fg,
+            theme.and_then(|theme| theme.match_fg).map(|color| color.0)
+        );
+    }
+
+    #[test]
+    fn test_all_names_are_themes() {
+        for name in THEME_NAMES {
+            assert!(name.parse::<Theme>().is_ok(), "{name} is not a theme");
+        }
+    }
+}
@@ -0,0 +1,570 @@
+//! `tui` provides [`Pager`], a full-screen view of the results of a scan, in which they can be scrolled through and
+//! searched, much like `less`. The pager is filled with the [`LineEvent`]s of a scan, such as by
+//! [`ScanOptions::scan_pattern_with_callback`](`crate::options::ScanOptions::scan_pattern_with_callback`), and then run
+//! once the scan is done.
+//!
+//! ```no_run
+//! use hline::options::ScanOptions;
+//! use hline::theme::Theme;
+//! use hline::tui::Pager;
+//! use std::io;
+//!
+//! let mut pager = Pager::new(Theme::DEFAULT);
+//! ScanOptions::new()
+//!     .scan_pattern_with_callback(io::stdin(), "error", |event| pager.push(event))
+//!     .expect("scan failed");
+//!
+//! pager.run().expect("pager failed");
+//! ```
+use crate::columns;
+use crate::print;
+use crate::sink::LineEvent;
+use crate::style::{self, Attribute};
+use crate::theme::Theme;
+use regex::Regex;
+use std::fmt::Write;
+use std::io;
+use std::mem;
+
+#[cfg(unix)]
+mod terminal;
+
+/// The keys that are understood by the pager, as they are sent by the terminal after an escape.
+const ESCAPE_SEQUENCES: &[(&[u8], Key)] = &[
+    (b"[A", Key::Up),
+    (b"OA", Key::Up),
+    (b"[B", Key::Down),
+    (b"OB", Key::Down),
+    (b"[5~", Key::PageUp),
+    (b"[6~", Key::PageDown),
+    (b"[H", Key::Home),
+    (b"OH", Key::Home),
+    (b"[1~", Key::Home),
+    (b"[F", Key::End),
+    (b"OF", Key::End),
+    (b"[4~", Key::End),
+];
+
+/// `Key` is a key pressed while the pager is shown.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Key {
+    Char(char),
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Backspace,
+    Escape,
+    /// Ctrl-C, which can't interrupt the program while the pager has the terminal, so quits the pager instead.
+    Interrupt,
+    /// A key with no binding, such as a function key.
+    Other,
+}
+
+/// `Action` is what should happen once the pager has handled a key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Action {
+    Continue,
+    Quit,
+}
+
+/// `Mode` is what the keys pressed in the pager do.
+enum Mode {
+    /// Keys move through the results.
+    Browsing,
+    /// Keys are typed into a search, which holds what has been typed so far.
+    Searching(String),
+}
+
+/// `PagerLine` is a line of the results, as shown in the pager.
+struct PagerLine {
+    /// The contents of the line, with control characters shown in caret notation so that they cannot garble the screen.
+    text: String,
+    matched: bool,
+}
+
+/// `Pager` shows the lines of a scan in the terminal, highlighting those that matched with a [`Theme`]. The lines can
+/// be scrolled through with the arrow keys, `j` and `k`, or a page at a time with space and `b`. `n` and `N` jump to the
+/// next and previous matching line, and `/` searches within the results, after which `n` and `N` jump between the
+/// lines the search found until it is cleared with escape. `q` quits.
+pub struct Pager {
+    lines: Vec<PagerLine>,
+    theme: Theme,
+    num_matched: usize,
+    /// The index of the line at the top of the screen.
+    top: usize,
+    /// The index of the line that was last jumped to, which jumps continue from.
+    current: Option<usize>,
+    mode: Mode,
+    search: Option<Regex>,
+    /// A message to show in place of the status line, until the next key is pressed.
+    message: Option<String>,
+}
+
+impl Pager {
+    /// Make a new `Pager` with no lines, which highlights lines with the given [`Theme`].
+    #[must_use]
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            lines: Vec::new(),
+            theme,
+            num_matched: 0,
+            top: 0,
+            current: None,
+            mode: Mode::Browsing,
+            search: None,
+            message: None,
+        }
+    }
+
+    /// Add the line described by the given [`LineEvent`] to the end of the pager.
+    pub fn push(&mut self, event: &LineEvent) {
+        if event.matched {
+            self.num_matched += 1;
+        }
+
+        self.lines.push(PagerLine {
+            text: displayable(event.line),
+            matched: event.matched,
+        });
+    }
+
+    /// Show the pager in the terminal until it is quit. The terminal is restored to the state it was found in once
+    /// the pager is quit, or if showing it fails. Keys are read from the terminal itself, so the pager can be shown
+    /// even if stdin holds the input that was scanned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no terminal to show the pager in, or if reading from or writing to it fails.
+    #[cfg(unix)]
+    pub fn run(mut self) -> io::Result<()> {
+        let mut terminal = terminal::Terminal::open()?;
+        loop {
+            // The size is checked before every redraw, so that the pager fits the terminal after it is resized
+            let (width, height) = terminal.size()?;
+            terminal.draw(self.render(width, height).as_bytes())?;
+            for key in terminal.read_keys()? {
+                if self.handle_key(key, height) == Action::Quit {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Show the pager in the terminal until it is quit.
+    ///
+    /// # Errors
+    ///
+    /// The pager is only supported on Unix, so this always returns an error elsewhere.
+    #[cfg(not(unix))]
+    pub fn run(self) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the pager is only supported on Unix",
+        ))
+    }
+
+    /// Handle the given key, given the height of the terminal in rows.
+    fn handle_key(&mut self, key: Key, height: usize) -> Action {
+        let page_height = page_height(height);
+        self.message = None;
+        if let Mode::Searching(query) = &mut self.mode {
+            match key {
+                Key::Char(c) if !c.is_control() => query.push(c),
+                // Backspacing past the start of the search cancels it, as it does in less
+                Key::Backspace if query.pop().is_none() => self.mode = Mode::Browsing,
+                Key::Enter => {
+                    let query = mem::take(query);
+                    self.mode = Mode::Browsing;
+                    self.search_for(&query, page_height);
+                }
+                Key::Escape | Key::Interrupt => self.mode = Mode::Browsing,
+                _ => {}
+            }
+
+            return Action::Continue;
+        }
+
+        match key {
+            Key::Char('q') | Key::Interrupt => return Action::Quit,
+            Key::Char('j') | Key::Down | Key::Enter => self.scroll_to(self.top + 1, page_height),
+            Key::Char('k') | Key::Up => self.scroll_to(self.top.saturating_sub(1), page_height),
+            Key::Char(' ' | 'f') | Key::PageDown => {
+                self.scroll_to(self.top + page_height, page_height);
+            }
+            Key::Char('b') | Key::PageUp => {
+                self.scroll_to(self.top.saturating_sub(page_height), page_height);
+            }
+            Key::Char('g') | Key::Home => self.scroll_to(0, page_height),
+            Key::Char('G') | Key::End => self.scroll_to(usize::MAX, page_height),
+            Key::Char('n') => self.jump(true, page_height),
+            Key::Char('N') => self.jump(false, page_height),
+            Key::Char('/') => self.mode = Mode::Searching(String::new()),
+            Key::Escape => self.search = None,
+            _ => {}
+        }
+
+        Action::Continue
+    }
+
+    /// Scroll so that the line with the given index is at the top of the screen, or as close to it as possible
+    /// without scrolling past the last line.
+    fn scroll_to(&mut self, top: usize, page_height: usize) {
+        self.top = top.min(self.lines.len().saturating_sub(page_height));
+        self.current = None;
+    }
+
+    /// Jump to the next line that was found by the search, or that matched if there is no search, either forwards or
+    /// backwards from the line last jumped to, or the top of the screen.
+    fn jump(&mut self, forwards: bool, page_height: usize) {
+        let from = self.current.unwrap_or(self.top);
+        let found = if forwards {
+            (from + 1..self.lines.len()).find(|&idx| self.is_target(idx))
+        } else {
+            (0..from).rev().find(|&idx| self.is_target(idx))
+        };
+
+        match found {
+            Some(idx) => {
+                self.scroll_to(idx, page_height);
+                self.current = Some(idx);
+            }
+            None if self.search.is_some() => self.message = Some("Pattern not found".to_string()),
+            None => self.message = Some("No more matching lines".to_string()),
+        }
+    }
+
+    /// Whether the line with the given index is one that [`Pager::jump`] should stop at.
+    fn is_target(&self, idx: usize) -> bool {
+        let line = &self.lines[idx];
+        match &self.search {
+            Some(search) => search.is_match(&line.text),
+            None => line.matched,
+        }
+    }
+
+    /// Search for the given pattern, jumping to the first line it finds. An empty pattern repeats the last search.
+    fn search_for(&mut self, pattern: &str, page_height: usize) {
+        if !pattern.is_empty() {
+            let Ok(search) = Regex::new(pattern) else {
+                self.message = Some(format!("Invalid pattern: {pattern}"));
+                return;
+            };
+
+            self.search = Some(search);
+        }
+
+        if self.search.is_none() {
+            return;
+        }
+
+        // A new search can find the line at the top of the screen, unlike the jumps after it
+        if self.current.is_none() && self.top < self.lines.len() && self.is_target(self.top) {
+            self.current = Some(self.top);
+        } else {
+            self.jump(true, page_height);
+        }
+    }
+
+    /// Render the screen for a terminal of the given size, in columns and rows.
+    fn render(&self, width: usize, height: usize) -> String {
+        let page_height = page_height(height);
+        let mut screen = String::new();
+        for row in 0..page_height {
+            // Writing to a String cannot fail
+            write!(screen, "\x1b[{};1H\x1b[2K", row + 1).unwrap();
+            match self.lines.get(self.top + row) {
+                Some(line) => screen.push_str(&fit(&self.render_line(line), width)),
+                None => screen.push('~'),
+            }
+        }
+
+        write!(screen, "\x1b[{};1H\x1b[2K", page_height + 1).unwrap();
+        if let Mode::Searching(query) = &self.mode {
+            screen.push_str(&fit(&format!("/{query}"), width));
+            // The cursor is only shown while typing, so that it's clear where the text goes
+            screen.push_str("\x1b[?25h");
+        } else {
+            let status = fit(&self.status(page_height), width);
+            write!(
+                screen,
+                "{}{status}{}\x1b[?25l",
+                style::Start(&Attribute::Reverse),
+                style::End(&Attribute::Reverse)
+            )
+            .unwrap();
+        }
+
+        screen
+    }
+
+    /// Render the given line, highlighted as the [`Theme`] directs, and with anything the search found in reverse video.
+    fn render_line(&self, line: &PagerLine) -> String {
+        let highlight = if line.matched {
+            Some(self.theme.match_highlight())
+        } else {
+            self.theme.context_highlight()
+        };
+
+        let mut rendered = style::Start(&highlight).to_string();
+        let mut pos = 0;
+        if let Some(search) = &self.search {
+            for found in search.find_iter(&line.text) {
+                rendered.push_str(&line.text[pos..found.start()]);
+                // Writing to a String cannot fail
+                write!(
+                    rendered,
+                    "{}{}{}",
+                    style::Start(&Attribute::Reverse),
+                    found.as_str(),
+                    style::End(&Attribute::Reverse)
+                )
+                .unwrap();
+                pos = found.end();
+            }
+        }
+
+        rendered.push_str(&line.text[pos..]);
+        write!(rendered, "{}", style::End(&highlight)).unwrap();
+
+        rendered
+    }
+
+    /// Get the line shown at the bottom of the screen, which describes what is shown and how to move around.
+    fn status(&self, page_height: usize) -> String {
+        if let Some(message) = &self.message {
+            return message.clone();
+        }
+
+        let mut status = if self.lines.is_empty() {
+            "No lines".to_string()
+        } else {
+            let bottom = (self.top + page_height).min(self.lines.len());
+            format!(
+                "Lines {}-{bottom} of {}, {} matching",
+                self.top + 1,
+                self.lines.len(),
+                self.num_matched
+            )
+        };
+
+        match &self.search {
+            Some(search) => write!(
+                status,
+                " | Searching for {search} (n/N: next/previous result, esc: clear)"
+            ),
+            None => write!(status, " | n/N: next/previous match, /: search"),
+        }
+        .unwrap();
+        status.push_str(", q: quit");
+
+        status
+    }
+}
+
+/// Get the number of rows that lines are shown in, given the height of the terminal. The last row holds the status line.
+fn page_height(height: usize) -> usize {
+    height.saturating_sub(1).max(1)
+}
+
+/// Truncate the given text so that it fits within the given width in columns.
+fn fit(text: &str, width: usize) -> String {
+    String::from_utf8_lossy(&columns::truncate(text.as_bytes(), width, None)).into_owned()
+}
+
+/// Prepare the given line for display, replacing any invalid UTF-8 and showing control characters other than tabs in
+/// caret notation.
+fn displayable(line: &[u8]) -> String {
+    let mut text = String::with_capacity(line.len());
+    for c in String::from_utf8_lossy(line).chars() {
+        if c.is_control() && c != '\t' {
+            print::push_caret_notation(&mut text, c);
+        } else {
+            text.push(c);
+        }
+    }
+
+    text
+}
+
+/// Split the given bytes, read from the terminal, into the keys that were pressed.
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut rest = bytes;
+    while let Some((&first, after_first)) = rest.split_first() {
+        let (key, len) = match first {
+            0x1b => parse_escape_sequence(after_first)
+                .map_or((Key::Escape, 1), |(key, len)| (key, len + 1)),
+            b'\r' | b'\n' => (Key::Enter, 1),
+            0x7f | 0x08 => (Key::Backspace, 1),
+            0x03 => (Key::Interrupt, 1),
+            _ => match rest
+                .utf8_chunks()
+                .next()
+                .and_then(|chunk| chunk.valid().chars().next())
+            {
+                Some(c) => (Key::Char(c), c.len_utf8()),
+                None => (Key::Other, 1),
+            },
+        };
+
+        keys.push(key);
+        rest = &rest[len..];
+    }
+
+    keys
+}
+
+/// Parse the escape sequence at the start of the given bytes, which follow an escape. The key it is for is returned,
+/// along with the length of the sequence, or `None` if the escape was pressed on its own.
+fn parse_escape_sequence(bytes: &[u8]) -> Option<(Key, usize)> {
+    if let Some((sequence, key)) = ESCAPE_SEQUENCES
+        .iter()
+        .find(|(sequence, _)| bytes.starts_with(sequence))
+    {
+        return Some((*key, sequence.len()));
+    }
+
+    // Sequences for other keys are skipped whole, so that they aren't mistaken for the keys they are made of. These
+    // are made of parameters, followed by a single byte that ends them.
+    match bytes.split_first() {
+        Some((b'[' | b'O', parameters)) => {
+            let end = parameters
+                .iter()
+                .position(|b| !(0x30..=0x3f).contains(b))
+                .map_or(parameters.len(), |pos| pos + 1);
+
+            Some((Key::Other, end + 1))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const NUM_LINES: usize = 20;
+    const HEIGHT: usize = 6;
+
+    /// Make a pager holding [`NUM_LINES`] lines, where each fifth line matched.
+    fn make_pager() -> Pager {
+        let mut pager = Pager::new(Theme::DEFAULT);
+        for i in 0..NUM_LINES {
+            let line = format!("line {i}");
+            pager.push(&LineEvent {
+                line: line.as_bytes(),
+                matched: i % 5 == 0,
+                line_number: i as u64 + 1,
+                byte_offset: 0,
+                spans: &[],
+            });
+        }
+
+        pager
+    }
+
+    fn press(pager: &mut Pager, keys: &str) {
+        for key in parse_keys(keys.as_bytes()) {
+            pager.handle_key(key, HEIGHT);
+        }
+    }
+
+    #[test_case(b"jq", &[Key::Char('j'), Key::Char('q')]; "characters")]
+    #[test_case(b"\x1b[A\x1bOB", &[Key::Up, Key::Down]; "arrow keys")]
+    #[test_case(b"\x1b[5~\x1b[6~", &[Key::PageUp, Key::PageDown]; "page keys")]
+    #[test_case(b"\x1b", &[Key::Escape]; "escape on its own")]
+    #[test_case(b"\x1b[15~j", &[Key::Other, Key::Char('j')]; "unknown sequence")]
+    #[test_case(b"a\x7f\r\x03", &[Key::Char('a'), Key::Backspace, Key::Enter, Key::Interrupt]; "control keys")]
+    #[test_case("é".as_bytes(), &[Key::Char('é')]; "multibyte characters")]
+    fn test_parses_keys(bytes: &[u8], expected: &[Key]) {
+        assert_eq!(expected, parse_keys(bytes));
+    }
+
+    #[test_case("jjj", 3; "down")]
+    #[test_case("jjjk", 2; "up")]
+    #[test_case("k", 0; "up past the start")]
+    #[test_case(" ", 5; "page down")]
+    #[test_case("G", 15; "end")]
+    #[test_case("   ", 15; "page down past the end")]
+    #[test_case("Gg", 0; "home")]
+    fn test_scrolls(keys: &str, expected_top: usize) {
+        let mut pager = make_pager();
+        press(&mut pager, keys);
+
+        assert_eq!(expected_top, pager.top);
+    }
+
+    #[test_case("n", Some(5); "next match")]
+    #[test_case("nn", Some(10); "following match")]
+    #[test_case("nnnN", Some(10); "previous match")]
+    #[test_case("nnnn", Some(15); "past the last match")]
+    fn test_jumps_between_matches(keys: &str, expected_current: Option<usize>) {
+        let mut pager = make_pager();
+        press(&mut pager, keys);
+
+        assert_eq!(expected_current, pager.current);
+    }
+
+    #[test]
+    fn test_searches_within_results() {
+        let mut pager = make_pager();
+        press(&mut pager, "/line 1\r");
+        assert_eq!(Some(1), pager.current);
+
+        press(&mut pager, "n");
+        assert_eq!(Some(10), pager.current);
+
+        // Once the search is cleared, jumps go between matching lines again
+        press(&mut pager, "\x1bn");
+        assert_eq!(Some(15), pager.current);
+    }
+
+    #[test]
+    fn test_reports_invalid_search() {
+        let mut pager = make_pager();
+        press(&mut pager, "/(\r");
+
+        assert!(pager.search.is_none());
+        assert_eq!(Some("Invalid pattern: ("), pager.message.as_deref());
+    }
+
+    #[test]
+    fn test_renders_lines_and_status() {
+        let pager = make_pager();
+        let screen = pager.render(80, 3);
+
+        let expected = format!(
+            "\x1b[1;1H\x1b[2K{match_start}line 0{match_end}\x1b[2;1H\x1b[2K{start}line 1{end}\
+             \x1b[3;1H\x1b[2K",
+            match_start = style::Start(&Some(Theme::DEFAULT.match_highlight())),
+            match_end = style::End(&Some(Theme::DEFAULT.match_highlight())),
+            start = style::Start(&None::<style::Style>),
+            end = style::End(&None::<style::Style>),
+        );
+        assert!(screen.starts_with(&expected), "{screen:?}");
+        assert!(screen.contains("Lines 1-2 of 20, 4 matching"), "{screen:?}");
+    }
+
+    #[test]
+    fn test_renders_search_results_in_reverse() {
+        let mut pager = make_pager();
+        press(&mut pager, "/ne\r");
+
+        let expected = format!(
+            "li{}ne{} 1",
+            style::Start(&Attribute::Reverse),
+            style::End(&Attribute::Reverse)
+        );
+        assert!(pager.render_line(&pager.lines[1]).contains(&expected));
+    }
+
+    #[test]
+    fn test_shows_control_characters_in_caret_notation() {
+        assert_eq!("a\t^[[31mb^M", displayable(b"a\t\x1b[31mb\r"));
+    }
+}
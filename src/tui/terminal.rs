@@ -0,0 +1,111 @@
+use super::Key;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{Read, Write};
+use std::mem::MaybeUninit;
+use std::os::fd::AsRawFd;
+
+/// Switch to the alternate screen, so that the pager doesn't clobber what was already in the terminal, and hide the
+/// cursor.
+const ENTER_SCREEN: &[u8] = b"\x1b[?1049h\x1b[?25l";
+
+/// Undo [`ENTER_SCREEN`], bringing back what was in the terminal before the pager was shown.
+const LEAVE_SCREEN: &[u8] = b"\x1b[?25h\x1b[?1049l";
+
+/// `Terminal` is the controlling terminal of the program, put into raw mode so that keys are read as they are pressed,
+/// rather than a line at a time. It is restored to its original state when dropped.
+pub(super) struct Terminal {
+    tty: File,
+    original_attributes: libc::termios,
+}
+
+impl Terminal {
+    /// Open the controlling terminal, putting it into raw mode and switching to the alternate screen.
+    pub(super) fn open() -> io::Result<Self> {
+        let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        let mut attributes = MaybeUninit::uninit();
+        // SAFETY: the descriptor stays open for as long as `tty` does, and tcgetattr initializes the termios it is given
+        // when it succeeds
+        let original_attributes = unsafe {
+            if libc::tcgetattr(tty.as_raw_fd(), attributes.as_mut_ptr()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            attributes.assume_init()
+        };
+
+        let mut raw_attributes = original_attributes;
+        // SAFETY: cfmakeraw only modifies the termios it is given
+        unsafe { libc::cfmakeraw(&raw mut raw_attributes) };
+        set_attributes(&tty, &raw_attributes)?;
+
+        // From here on, dropping the terminal will restore it, even if entering the alternate screen fails
+        let mut terminal = Self {
+            tty,
+            original_attributes,
+        };
+        terminal.tty.write_all(ENTER_SCREEN)?;
+
+        Ok(terminal)
+    }
+
+    /// Get the size of the terminal, as its width in columns and its height in rows.
+    pub(super) fn size(&self) -> io::Result<(usize, usize)> {
+        let mut size = libc::winsize {
+            ws_row: 0,
+            ws_col: 0,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+
+        // SAFETY: the descriptor stays open for as long as `self.tty` does, and TIOCGWINSZ only writes to the winsize
+        // it is given
+        if unsafe { libc::ioctl(self.tty.as_raw_fd(), libc::TIOCGWINSZ, &raw mut size) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok((usize::from(size.ws_col), usize::from(size.ws_row)))
+    }
+
+    /// Wait for keys to be pressed, returning all of those that were.
+    pub(super) fn read_keys(&mut self) -> io::Result<Vec<Key>> {
+        let mut buf = [0; 64];
+        loop {
+            match self.tty.read(&mut buf) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "the terminal was closed",
+                    ))
+                }
+                Ok(len) => return Ok(super::parse_keys(&buf[..len])),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Draw the given screen, as rendered by the pager.
+    pub(super) fn draw(&mut self, screen: &[u8]) -> io::Result<()> {
+        self.tty.write_all(screen)?;
+        self.tty.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        // There's nothing more that can be done if the terminal can't be restored
+        let _ = self.tty.write_all(LEAVE_SCREEN);
+        let _ = set_attributes(&self.tty, &self.original_attributes);
+    }
+}
+
+/// Set the attributes of the given terminal, once any output to it has been written.
+fn set_attributes(tty: &File, attributes: &libc::termios) -> io::Result<()> {
+    // SAFETY: the descriptor stays open for as long as `tty` does, and tcsetattr only reads the termios it is given
+    if unsafe { libc::tcsetattr(tty.as_raw_fd(), libc::TCSADRAIN, attributes) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}